@@ -0,0 +1,228 @@
+// Cart products and orders come in as remaining accounts, so they are checked and created here
+
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use anchor_spl::token::{self, TokenAccount};
+
+use crate::{CartLine, ErrorCode, Order, OrderItem, Product};
+
+// Keeps a checkout within the transaction size limit
+pub const MAX_CART_LINES: usize = 16;
+
+pub struct FarmerGroup {
+    pub farmer: Pubkey,
+    pub currency_mint: Pubkey,
+    pub items: Vec<OrderItem>,
+    pub total_amount: u64,
+}
+
+// Any invalid line fails the whole checkout
+pub fn reserve_lines<'info>(
+    lines: &[CartLine],
+    products: &'info [AccountInfo<'info>],
+    currency_mint: Pubkey,
+) -> Result<Vec<FarmerGroup>> {
+    require!(!lines.is_empty() && lines.len() <= MAX_CART_LINES, ErrorCode::InvalidCart);
+    require!(products.len() == lines.len(), ErrorCode::InvalidCart);
+
+    let mut groups: Vec<FarmerGroup> = Vec::new();
+    for (i, (line, info)) in lines.iter().zip(products.iter()).enumerate() {
+        // Reading the same product twice would let the second write clobber the first
+        require!(
+            !products[..i].iter().any(|seen| seen.key == info.key),
+            ErrorCode::InvalidCart
+        );
+
+        let mut product = Account::<Product>::try_from(info)?;
+        require!(product.is_listed, ErrorCode::ProductNotListed);
+        require!(line.quantity > 0, ErrorCode::InvalidQuantity);
+        require!(product.stock >= line.quantity, ErrorCode::InsufficientStock);
+        require!(product.price <= line.max_unit_price, ErrorCode::PriceChanged);
+        require!(product.currency_mint == currency_mint, ErrorCode::InvalidMint);
+
+        let amount = product.price
+            .checked_mul(line.quantity as u64)
+            .ok_or(ErrorCode::MathOverflow)?;
+
+        product.stock = product.stock
+            .checked_sub(line.quantity)
+            .ok_or(ErrorCode::MathOverflow)?;
+        product.exit(&crate::ID)?;
+
+        let item = OrderItem {
+            product: info.key(),
+            quantity: line.quantity,
+            unit_price: product.price,
+        };
+
+        match groups.last_mut() {
+            Some(group) if group.farmer == product.farmer => {
+                group.total_amount = group.total_amount
+                    .checked_add(amount)
+                    .ok_or(ErrorCode::MathOverflow)?;
+                group.items.push(item);
+            }
+            _ => {
+                require!(
+                    !groups.iter().any(|group| group.farmer == product.farmer),
+                    ErrorCode::CartNotGrouped
+                );
+                groups.push(FarmerGroup {
+                    farmer: product.farmer,
+                    currency_mint: product.currency_mint,
+                    items: vec![item],
+                    total_amount: amount,
+                });
+            }
+        }
+    }
+
+    Ok(groups)
+}
+
+// Like Anchor's init, but a PDA pre-funded to block create_account is topped up and assigned instead
+fn create_pda_account<'info>(
+    target: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    space: usize,
+    owner: &Pubkey,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    let rent = Rent::get()?.minimum_balance(space);
+
+    if target.lamports() == 0 {
+        return system_program::create_account(
+            CpiContext::new_with_signer(
+                system_program.clone(),
+                system_program::CreateAccount {
+                    from: payer.clone(),
+                    to: target.clone(),
+                },
+                signer_seeds,
+            ),
+            rent,
+            space as u64,
+            owner,
+        );
+    }
+
+    let shortfall = rent.saturating_sub(target.lamports());
+    if shortfall > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                system_program.clone(),
+                system_program::Transfer {
+                    from: payer.clone(),
+                    to: target.clone(),
+                },
+            ),
+            shortfall,
+        )?;
+    }
+    system_program::allocate(
+        CpiContext::new_with_signer(
+            system_program.clone(),
+            system_program::Allocate {
+                account_to_allocate: target.clone(),
+            },
+            signer_seeds,
+        ),
+        space as u64,
+    )?;
+    system_program::assign(
+        CpiContext::new_with_signer(
+            system_program.clone(),
+            system_program::Assign {
+                account_to_assign: target.clone(),
+            },
+            signer_seeds,
+        ),
+        owner,
+    )?;
+
+    Ok(())
+}
+
+pub fn create_order_account<'info>(
+    order_info: &AccountInfo<'info>,
+    buyer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    mut order: Order,
+) -> Result<()> {
+    let buyer_key = buyer.key();
+    let order_id_bytes = order.order_id.to_le_bytes();
+    let (expected, bump) = Pubkey::find_program_address(
+        &[b"order", buyer_key.as_ref(), order_id_bytes.as_ref()],
+        &crate::ID,
+    );
+    require_keys_eq!(order_info.key(), expected, ErrorCode::InvalidCart);
+
+    let space = 8 + Order::LEN + order.items.len() * OrderItem::LEN;
+    let seeds = &[
+        b"order",
+        buyer_key.as_ref(),
+        order_id_bytes.as_ref(),
+        &[bump],
+    ];
+    let signer_seeds: &[&[&[u8]]] = &[seeds];
+
+    create_pda_account(order_info, buyer, system_program, space, &crate::ID, signer_seeds)?;
+
+    order.bump = bump;
+    let mut data = order_info.try_borrow_mut_data()?;
+    order.try_serialize(&mut &mut data[..])?;
+
+    Ok(())
+}
+
+pub fn escrow_bump(escrow: &AccountInfo, seed: &[u8], order: &Pubkey) -> Result<u8> {
+    let (expected, bump) = Pubkey::find_program_address(&[seed, order.as_ref()], &crate::ID);
+    require_keys_eq!(escrow.key(), expected, ErrorCode::InvalidCart);
+    Ok(bump)
+}
+
+pub fn create_escrow_token_account<'info>(
+    escrow: &AccountInfo<'info>,
+    order: &Pubkey,
+    currency_mint: &AccountInfo<'info>,
+    buyer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    token_program: &AccountInfo<'info>,
+) -> Result<()> {
+    let bump = escrow_bump(escrow, b"order_escrow_token", order)?;
+    let seeds = &[
+        b"order_escrow_token",
+        order.as_ref(),
+        &[bump],
+    ];
+    let signer_seeds: &[&[&[u8]]] = &[seeds];
+
+    create_pda_account(escrow, buyer, system_program, TokenAccount::LEN, token_program.key, signer_seeds)?;
+
+    token::initialize_account3(CpiContext::new(
+        token_program.clone(),
+        token::InitializeAccount3 {
+            account: escrow.clone(),
+            mint: currency_mint.clone(),
+            authority: escrow.clone(),
+        },
+    ))?;
+
+    Ok(())
+}
+
+pub fn restock_items<'info>(items: &[OrderItem], products: &'info [AccountInfo<'info>]) -> Result<()> {
+    require!(products.len() == items.len(), ErrorCode::InvalidCart);
+
+    for (item, info) in items.iter().zip(products.iter()) {
+        require_keys_eq!(info.key(), item.product, ErrorCode::InvalidCart);
+        let mut product = Account::<Product>::try_from(info)?;
+        product.stock = product.stock
+            .checked_add(item.quantity)
+            .ok_or(ErrorCode::MathOverflow)?;
+        product.exit(&crate::ID)?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,269 @@
+// Paged indexes clients walk until the first missing page instead of scanning program accounts
+// Listings are never removed, so entries may point at closed or delisted accounts
+
+use std::marker::PhantomData;
+
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+
+use crate::{
+    BackerCampaignsPage, CampaignBackersPage, Category, CategoryIndex, CategoryIndexPage, ErrorCode,
+    GEOHASH_ALPHABET, GEOHASH_INDEX_PRECISION, MAX_TAGS, MAX_TAG_LEN,
+};
+
+pub const INDEX_PAGE_SIZE: usize = 32;
+
+// Account seeds spell this out, since the IDL build can't resolve function calls in seeds
+pub fn page_for(count: u64) -> u32 {
+    (count / INDEX_PAGE_SIZE as u64) as u32
+}
+
+pub fn grow<'info>(
+    page: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+) -> Result<()> {
+    let new_len = page.data_len() + 32;
+    let shortfall = Rent::get()?
+        .minimum_balance(new_len)
+        .saturating_sub(page.lamports());
+
+    if shortfall > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                system_program.clone(),
+                system_program::Transfer {
+                    from: payer.clone(),
+                    to: page.clone(),
+                },
+            ),
+            shortfall,
+        )?;
+    }
+    page.realloc(new_len, false)?;
+
+    Ok(())
+}
+
+pub fn validate_listing(tags: &[String], geohash: &str) -> Result<()> {
+    require!(tags.len() <= MAX_TAGS, ErrorCode::TooManyTags);
+    require!(
+        tags.iter().all(|tag| !tag.is_empty() && tag.len() <= MAX_TAG_LEN),
+        ErrorCode::InvalidTag
+    );
+    require!(
+        geohash.len() >= GEOHASH_INDEX_PRECISION
+            && geohash.len() <= 12
+            && geohash.bytes().all(|c| GEOHASH_ALPHABET.contains(&c)),
+        ErrorCode::InvalidGeohash
+    );
+
+    Ok(())
+}
+
+// Shorter geohashes are rejected by validate_listing after the accounts are derived
+pub fn geohash_cell(geohash: &str) -> &[u8] {
+    &geohash.as_bytes()[..geohash.len().min(GEOHASH_INDEX_PRECISION)]
+}
+
+pub fn record_listing<'info>(
+    index: &mut Account<'info, CategoryIndex>,
+    page: &mut Account<'info, CategoryIndexPage>,
+    entry: Pubkey,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+) -> Result<()> {
+    grow(&page.to_account_info(), payer, system_program)?;
+    page.index = index.key();
+    page.page = page_for(index.count);
+    page.entries.push(entry);
+
+    index.count = index.count
+        .checked_add(1)
+        .ok_or(ErrorCode::MathOverflow)?;
+
+    Ok(())
+}
+
+// Counts are taken before this backing is added to them
+pub fn record_backing<'info>(
+    campaign_backers: &mut Account<'info, CampaignBackersPage>,
+    backer_campaigns: &mut Account<'info, BackerCampaignsPage>,
+    campaign: Pubkey,
+    backers_count: u64,
+    backings_count: u32,
+    backer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+) -> Result<()> {
+    grow(&campaign_backers.to_account_info(), backer, system_program)?;
+    campaign_backers.campaign = campaign;
+    campaign_backers.page = page_for(backers_count);
+    campaign_backers.backers.push(backer.key());
+
+    grow(&backer_campaigns.to_account_info(), backer, system_program)?;
+    backer_campaigns.backer = backer.key();
+    backer_campaigns.page = page_for(backings_count as u64);
+    backer_campaigns.campaigns.push(campaign);
+
+    Ok(())
+}
+
+pub fn page_address(seed: &[u8], owner: &Pubkey, page: u32) -> Pubkey {
+    Pubkey::find_program_address(&[seed, owner.as_ref(), &page.to_le_bytes()], &crate::ID).0
+}
+
+// Client-side; fetch returns None for a missing account, which ends the iteration
+pub struct PageIter<T, F> {
+    seed: &'static [u8],
+    owner: Pubkey,
+    next_page: Option<u32>,
+    fetch: F,
+    _page: PhantomData<T>,
+}
+
+impl<T, F> PageIter<T, F>
+where
+    F: FnMut(&Pubkey) -> Option<T>,
+{
+    pub fn new(seed: &'static [u8], owner: Pubkey, fetch: F) -> Self {
+        Self {
+            seed,
+            owner,
+            next_page: Some(0),
+            fetch,
+            _page: PhantomData,
+        }
+    }
+}
+
+impl<T, F> Iterator for PageIter<T, F>
+where
+    F: FnMut(&Pubkey) -> Option<T>,
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        let page = self.next_page?;
+        let item = (self.fetch)(&page_address(self.seed, &self.owner, page));
+        self.next_page = match item {
+            Some(_) => page.checked_add(1),
+            None => None,
+        };
+        item
+    }
+}
+
+pub fn campaign_backers_pages<F>(campaign: Pubkey, fetch: F) -> PageIter<CampaignBackersPage, F>
+where
+    F: FnMut(&Pubkey) -> Option<CampaignBackersPage>,
+{
+    PageIter::new(b"campaign_backers", campaign, fetch)
+}
+
+pub fn backer_campaigns_pages<F>(backer: Pubkey, fetch: F) -> PageIter<BackerCampaignsPage, F>
+where
+    F: FnMut(&Pubkey) -> Option<BackerCampaignsPage>,
+{
+    PageIter::new(b"backer_campaigns", backer, fetch)
+}
+
+pub fn campaign_backers<F>(campaign: Pubkey, fetch: F) -> impl Iterator<Item = Pubkey>
+where
+    F: FnMut(&Pubkey) -> Option<CampaignBackersPage>,
+{
+    campaign_backers_pages(campaign, fetch).flat_map(|page| page.backers)
+}
+
+pub fn backer_campaigns<F>(backer: Pubkey, fetch: F) -> impl Iterator<Item = Pubkey>
+where
+    F: FnMut(&Pubkey) -> Option<BackerCampaignsPage>,
+{
+    backer_campaigns_pages(backer, fetch).flat_map(|page| page.campaigns)
+}
+
+pub fn campaign_category_address(category: Category, cell: &str) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"campaign_category", &[category as u8], cell.as_bytes()],
+        &crate::ID,
+    )
+    .0
+}
+
+pub fn product_category_address(category: Category, cell: &str) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"product_category", &[category as u8], cell.as_bytes()],
+        &crate::ID,
+    )
+    .0
+}
+
+pub fn category_pages<F>(index: Pubkey, fetch: F) -> PageIter<CategoryIndexPage, F>
+where
+    F: FnMut(&Pubkey) -> Option<CategoryIndexPage>,
+{
+    PageIter::new(b"category_page", index, fetch)
+}
+
+pub fn category_listings<F>(index: Pubkey, fetch: F) -> impl Iterator<Item = Pubkey>
+where
+    F: FnMut(&Pubkey) -> Option<CategoryIndexPage>,
+{
+    category_pages(index, fetch).flat_map(|page| page.entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn page(campaign: Pubkey, page: u32) -> CampaignBackersPage {
+        CampaignBackersPage {
+            bump: 0,
+            campaign,
+            page,
+            backers: vec![Pubkey::new_unique()],
+        }
+    }
+
+    #[test]
+    fn page_iter_stops_at_first_missing_page() {
+        let campaign = Pubkey::new_unique();
+        let stored = [
+            page_address(b"campaign_backers", &campaign, 0),
+            page_address(b"campaign_backers", &campaign, 1),
+            // Page 3 exists past the gap at page 2 and must not be reached
+            page_address(b"campaign_backers", &campaign, 3),
+        ];
+        let mut fetched = Vec::new();
+
+        let pages: Vec<u32> = campaign_backers_pages(campaign, |address| {
+            fetched.push(*address);
+            stored
+                .iter()
+                .position(|stored| stored == address)
+                .map(|i| page(campaign, [0, 1, 3][i]))
+        })
+        .map(|page| page.page)
+        .collect();
+
+        assert_eq!(pages, vec![0, 1]);
+        assert_eq!(
+            fetched,
+            vec![stored[0], stored[1], page_address(b"campaign_backers", &campaign, 2)]
+        );
+    }
+
+    #[test]
+    fn page_iter_is_empty_without_first_page() {
+        let mut calls = 0;
+        {
+            let mut pages = campaign_backers_pages(Pubkey::new_unique(), |_| {
+                calls += 1;
+                None
+            });
+            assert!(pages.next().is_none());
+            assert!(pages.next().is_none());
+        }
+
+        assert_eq!(calls, 1);
+    }
+}
@@ -1,13 +1,58 @@
 use anchor_lang::prelude::*;
 use anchor_lang::system_program;
-use anchor_spl::token::{self, Token, TokenAccount, Mint, Transfer};
+use anchor_spl::token::{self, CloseAccount, Token, TokenAccount, Mint, Transfer};
+
+pub mod cart;
+pub mod index;
+pub mod merkle;
 
 declare_id!("7ETsTKTvvjbE89kEQJARuJcUnN18n28Fy972zik2tAnN");
 
+// Seconds after a campaign ends before it can be closed with backings still open
+pub const CLOSE_GRACE_PERIOD: i64 = 90 * 86400;
+
+// Seconds after delivery before anyone can release an order's escrow to the farmer
+pub const ORDER_AUTO_RELEASE_PERIOD: i64 = 14 * 86400;
+
+// Seconds after a pre-order's harvest date before the buyer can reclaim the deposit
+pub const PREORDER_GRACE_PERIOD: i64 = 7 * 86400;
+
+// Seconds a pre-order buyer has to pay the balance once the farmer calls for it
+pub const BALANCE_PAYMENT_PERIOD: i64 = 7 * 86400;
+
+// Seconds an arbiter has to resolve a dispute before it defaults to the claimant
+pub const DISPUTE_RESOLUTION_PERIOD: i64 = 7 * 86400;
+
+// Share of each undelivered reward kept in the vault at withdrawal to back disputes
+pub const REWARD_HOLDBACK_BPS: u16 = 2000;
+
+// Seconds after a campaign ends before its holdback is released with rewards still undelivered
+pub const HOLDBACK_RELEASE_PERIOD: i64 = 180 * 86400;
+
+// Maximum number of partners sharing the platform fee with the treasury
+pub const MAX_FEE_SPLITS: usize = 4;
+
+// Maximum number of co-owners sharing a campaign with its lead farmer
+pub const MAX_CO_OWNERS: usize = 4;
+
+// Seconds before a requested payout wallet change can be applied
+pub const PAYOUT_CHANGE_DELAY: i64 = 2 * 86400;
+
+// Base32 alphabet used by geohashes
+pub const GEOHASH_ALPHABET: &[u8] = b"0123456789bcdefghjkmnpqrstuvwxyz";
+
+// Geohash characters keying the category indexes (a cell of roughly 39km x 20km)
+pub const GEOHASH_INDEX_PRECISION: usize = 4;
+
+// Discovery tags on a campaign or product
+pub const MAX_TAGS: usize = 4;
+pub const MAX_TAG_LEN: usize = 16;
+
 #[program]
 pub mod workspace {
     use super::*;
 
+    // Creates the single platform config that roles, attestations and fees hang off
     // fee_bps: u16, Platform fee in basis points, 250 = 2.5%
     pub fn initialize_config(
         ctx: Context<InitializeConfig>,
@@ -24,32 +69,342 @@ pub mod workspace {
         config.is_active = true;
         config.is_paused = false;
         config.version = 1;
+        config.treasury = ctx.accounts.authority.key();
+        // Everyone stays Bronze until an admin configures the thresholds
+        config.loyalty_thresholds = [u64::MAX; 3];
+        config.fee_discount_thresholds = [u64::MAX; 3];
+        config.fee_discount_bps = [0; 3];
+        config.fee_splits = Vec::new();
+        
+        Ok(())
+    }
+
+    pub fn grant_role(
+        ctx: Context<GrantRole>,
+        member: Pubkey,
+        kind: RoleKind,
+    ) -> Result<()> {
+        let config = &ctx.accounts.config;
+        require!(config.authority == ctx.accounts.authority.key(), ErrorCode::Unauthorized);
+        
+        let clock = Clock::get()?;
+        
+        let role = &mut ctx.accounts.role;
+        role.bump = ctx.bumps.role;
+        role.config = config.key();
+        role.member = member;
+        role.kind = kind;
+        role.granted_by = ctx.accounts.authority.key();
+        role.granted_at = clock.unix_timestamp;
+        
+        emit!(RoleGranted {
+            config: role.config,
+            member,
+            kind,
+        });
+        
+        Ok(())
+    }
+
+    pub fn revoke_role(ctx: Context<RevokeRole>) -> Result<()> {
+        let config = &ctx.accounts.config;
+        require!(config.authority == ctx.accounts.authority.key(), ErrorCode::Unauthorized);
+        
+        emit!(RoleRevoked {
+            config: config.key(),
+            member: ctx.accounts.role.member,
+            kind: ctx.accounts.role.kind,
+        });
+        
+        Ok(())
+    }
+
+    pub fn pause_platform(ctx: Context<UpdateConfig>) -> Result<()> {
+        require_role(&ctx.accounts.config, &ctx.accounts.role, ctx.accounts.signer.key(), RoleKind::Pauser)?;
+        
+        let config = &mut ctx.accounts.config;
+        config.is_paused = true;
+        
+        Ok(())
+    }
+
+    pub fn unpause_platform(ctx: Context<UpdateConfig>) -> Result<()> {
+        // Pausers can only pause; resuming needs an admin
+        require_role(&ctx.accounts.config, &ctx.accounts.role, ctx.accounts.signer.key(), RoleKind::Admin)?;
+        
+        let config = &mut ctx.accounts.config;
+        config.is_paused = false;
+        
+        Ok(())
+    }
+
+    pub fn set_fee_bps(ctx: Context<UpdateConfig>, fee_bps: u16) -> Result<()> {
+        require!(fee_bps <= 10000, ErrorCode::InvalidFee);
+        require_role(&ctx.accounts.config, &ctx.accounts.role, ctx.accounts.signer.key(), RoleKind::FeeManager)?;
+        
+        let config = &mut ctx.accounts.config;
+        config.fee_bps = fee_bps;
+        
+        Ok(())
+    }
+
+    pub fn set_fee_discounts(
+        ctx: Context<UpdateConfig>,
+        thresholds: [u64; 3],
+        discount_bps: [u16; 3],
+    ) -> Result<()> {
+        require!(discount_bps.iter().all(|bps| *bps <= 10000), ErrorCode::InvalidFee);
+        require_role(&ctx.accounts.config, &ctx.accounts.role, ctx.accounts.signer.key(), RoleKind::FeeManager)?;
+        
+        let config = &mut ctx.accounts.config;
+        config.fee_discount_thresholds = thresholds;
+        config.fee_discount_bps = discount_bps;
+        
+        Ok(())
+    }
+
+    // Overrides the fee locked in at creation, e.g. waiving it for relief campaigns
+    pub fn set_campaign_fee(ctx: Context<SetCampaignFee>, fee_bps: u16) -> Result<()> {
+        require!(fee_bps <= 10000, ErrorCode::InvalidFee);
+        require_role(&ctx.accounts.config, &ctx.accounts.role, ctx.accounts.signer.key(), RoleKind::FeeManager)?;
+        require!(!ctx.accounts.campaign.is_withdrawn, ErrorCode::AlreadyWithdrawn);
+        require!(
+            fee_bps as u64 + ctx.accounts.campaign.referral_bps as u64 <= 10000,
+            ErrorCode::InvalidFee
+        );
+        // Backers committed at the current fee, so it can only come down
+        require!(
+            ctx.accounts.campaign.backers_count == 0 || fee_bps <= ctx.accounts.campaign.fee_bps,
+            ErrorCode::FeeIncreaseLocked
+        );
+        
+        let campaign = &mut ctx.accounts.campaign;
+        campaign.fee_bps = fee_bps;
+        
+        emit!(CampaignFeeSet {
+            campaign: campaign.key(),
+            fee_bps,
+            set_by: ctx.accounts.signer.key(),
+        });
+        
+        Ok(())
+    }
+
+    pub fn set_loyalty_thresholds(ctx: Context<UpdateConfig>, thresholds: [u64; 3]) -> Result<()> {
+        require!(
+            thresholds[0] <= thresholds[1] && thresholds[1] <= thresholds[2],
+            ErrorCode::InvalidLoyaltyThresholds
+        );
+        require_role(&ctx.accounts.config, &ctx.accounts.role, ctx.accounts.signer.key(), RoleKind::Admin)?;
+        
+        let config = &mut ctx.accounts.config;
+        config.loyalty_thresholds = thresholds;
+        
+        Ok(())
+    }
+
+    pub fn set_fee_splits(ctx: Context<UpdateConfig>, fee_splits: Vec<FeeSplit>) -> Result<()> {
+        require!(fee_splits.len() <= MAX_FEE_SPLITS, ErrorCode::InvalidFeeSplits);
+        let mut total_bps: u16 = 0;
+        for split in fee_splits.iter() {
+            require!(split.share_bps > 0, ErrorCode::InvalidFeeSplits);
+            total_bps = total_bps
+                .checked_add(split.share_bps)
+                .ok_or(ErrorCode::InvalidFeeSplits)?;
+        }
+        require!(total_bps <= 10000, ErrorCode::InvalidFeeSplits);
+        require_role(&ctx.accounts.config, &ctx.accounts.role, ctx.accounts.signer.key(), RoleKind::Treasurer)?;
+        
+        let config = &mut ctx.accounts.config;
+        config.fee_splits = fee_splits;
+        
+        Ok(())
+    }
+
+    pub fn set_treasury(ctx: Context<UpdateConfig>, treasury: Pubkey) -> Result<()> {
+        require_role(&ctx.accounts.config, &ctx.accounts.role, ctx.accounts.signer.key(), RoleKind::Treasurer)?;
+        
+        let config = &mut ctx.accounts.config;
+        config.treasury = treasury;
+        
+        Ok(())
+    }
+
+    // expires_at: i64, 0 for an attestation that never expires
+    pub fn attest_farmer(
+        ctx: Context<AttestFarmer>,
+        kind: AttestationKind,
+        expires_at: i64,
+        evidence_hash: [u8; 32],
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        
+        require_role(&ctx.accounts.config, &ctx.accounts.role, ctx.accounts.verifier.key(), RoleKind::Verifier)?;
+        require!(expires_at == 0 || expires_at > clock.unix_timestamp, ErrorCode::InvalidExpiry);
+        // Only the issuer can renew a live attestation; revoked ones can be reissued by any verifier
+        let attestation = &ctx.accounts.attestation;
+        require!(
+            attestation.issuer == Pubkey::default()
+                || attestation.is_revoked
+                || attestation.issuer == ctx.accounts.verifier.key(),
+            ErrorCode::AttestationIssuerMismatch
+        );
+        
+        // Re-attesting renews an expired or revoked attestation in place
+        let attestation = &mut ctx.accounts.attestation;
+        attestation.bump = ctx.bumps.attestation;
+        attestation.config = ctx.accounts.config.key();
+        attestation.farmer = ctx.accounts.farmer.key();
+        attestation.kind = kind;
+        attestation.issuer = ctx.accounts.verifier.key();
+        attestation.issued_at = clock.unix_timestamp;
+        attestation.expires_at = expires_at;
+        attestation.evidence_hash = evidence_hash;
+        attestation.is_revoked = false;
+        
+        emit!(FarmerAttested {
+            farmer: attestation.farmer,
+            kind,
+            issuer: attestation.issuer,
+            expires_at,
+            evidence_hash,
+        });
+        
+        Ok(())
+    }
+
+    pub fn revoke_attestation(ctx: Context<RevokeAttestation>) -> Result<()> {
+        let signer = ctx.accounts.verifier.key();
+        
+        // Issuers revoke their own attestations; anyone else's need an admin
+        let kind = if ctx.accounts.attestation.issuer == signer {
+            RoleKind::Verifier
+        } else {
+            RoleKind::Admin
+        };
+        require_role(&ctx.accounts.config, &ctx.accounts.role, signer, kind)?;
+        require!(!ctx.accounts.attestation.is_revoked, ErrorCode::AttestationRevoked);
+        
+        let attestation = &mut ctx.accounts.attestation;
+        attestation.is_revoked = true;
+        
+        emit!(AttestationRevoked {
+            farmer: attestation.farmer,
+            kind: attestation.kind,
+            revoked_by: signer,
+        });
+        
+        Ok(())
+    }
+
+    pub fn create_farmer_profile(
+        ctx: Context<CreateFarmerProfile>,
+        farm_name: String,
+        metadata_uri: String,
+        geohash: String,
+        is_organic: bool,
+        payout_wallet: Pubkey,
+    ) -> Result<()> {
+        require!(!farm_name.is_empty() && farm_name.len() <= 64, ErrorCode::FarmNameTooLong);
+        require!(metadata_uri.len() <= 200, ErrorCode::MetadataUriTooLong);
+        require!(
+            geohash.len() <= 12 && geohash.bytes().all(|c| GEOHASH_ALPHABET.contains(&c)),
+            ErrorCode::InvalidGeohash
+        );
+        // New campaigns pay out here until a timelocked change completes
+        require!(payout_wallet != Pubkey::default(), ErrorCode::InvalidPayoutWallet);
+        
+        let clock = Clock::get()?;
+        
+        let profile = &mut ctx.accounts.farmer_profile;
+        profile.bump = ctx.bumps.farmer_profile;
+        profile.farmer = ctx.accounts.farmer.key();
+        profile.farm_name = farm_name;
+        profile.metadata_uri = metadata_uri;
+        profile.geohash = geohash;
+        profile.is_organic = is_organic;
+        profile.payout_wallet = payout_wallet;
+        profile.campaigns_created = 0;
+        profile.campaigns_funded = 0;
+        profile.total_raised = 0;
+        profile.created_at = clock.unix_timestamp;
+        profile.rating_sum = 0;
+        profile.rating_count = 0;
+        profile.next_campaign_id = 0;
+        
+        Ok(())
+    }
+
+    pub fn update_farmer_profile(
+        ctx: Context<UpdateFarmerProfile>,
+        farm_name: String,
+        metadata_uri: String,
+        geohash: String,
+        is_organic: bool,
+        payout_wallet: Pubkey,
+    ) -> Result<()> {
+        require!(!farm_name.is_empty() && farm_name.len() <= 64, ErrorCode::FarmNameTooLong);
+        require!(metadata_uri.len() <= 200, ErrorCode::MetadataUriTooLong);
+        require!(
+            geohash.len() <= 12 && geohash.bytes().all(|c| GEOHASH_ALPHABET.contains(&c)),
+            ErrorCode::InvalidGeohash
+        );
+        require!(payout_wallet != Pubkey::default(), ErrorCode::InvalidPayoutWallet);
+        
+        let profile = &mut ctx.accounts.farmer_profile;
+        profile.farm_name = farm_name;
+        profile.metadata_uri = metadata_uri;
+        profile.geohash = geohash;
+        profile.is_organic = is_organic;
+        profile.payout_wallet = payout_wallet;
         
         Ok(())
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn create_campaign(
         ctx: Context<CreateCampaign>,
-        campaign_id: u64,
         title: String,
         description: String,
         goal_amount: u64,
         duration_days: u64,
+        requires_verification: bool,
+        category: Category,
+        tags: Vec<String>,
+        geohash: String,
     ) -> Result<()> {
         require!(title.len() <= 64, ErrorCode::TitleTooLong);
         require!(description.len() <= 256, ErrorCode::DescriptionTooLong);
         require!(goal_amount > 0, ErrorCode::InvalidAmount);
         require!(duration_days > 0 && duration_days <= 365, ErrorCode::InvalidDuration);
+        index::validate_listing(&tags, &geohash)?;
         
         let config = &ctx.accounts.config;
         require!(config.is_active && !config.is_paused, ErrorCode::PlatformInactive);
         
         let clock = Clock::get()?;
+        
+        if requires_verification {
+            let attestation = ctx.accounts.attestation
+                .as_ref()
+                .ok_or(ErrorCode::VerificationRequired)?;
+            require!(attestation.kind == AttestationKind::Verification, ErrorCode::VerificationRequired);
+            require!(!attestation.is_revoked, ErrorCode::AttestationRevoked);
+            require!(
+                attestation.expires_at == 0 || attestation.expires_at > clock.unix_timestamp,
+                ErrorCode::AttestationExpired
+            );
+            // The attestation only counts while its issuer is still a verifier
+            require_role(config, &ctx.accounts.issuer_role, attestation.issuer, RoleKind::Verifier)?;
+        }
         let start_time = clock.unix_timestamp;
         let end_time = start_time
             .checked_add((duration_days * 86400) as i64)
             .ok_or(ErrorCode::MathOverflow)?;
         
+        // Ids are handed out per farmer so clients can enumerate campaigns by seed
+        let campaign_id = ctx.accounts.farmer_profile.next_campaign_id;
+        
         let campaign = &mut ctx.accounts.campaign;
         campaign.bump = ctx.bumps.campaign;
         campaign.farmer = ctx.accounts.farmer.key();
@@ -65,15 +420,72 @@ pub mod workspace {
         campaign.is_finalized = false;
         campaign.backers_count = 0;
         campaign.tiers_count = 0;
+        campaign.open_backings = 0;
+        campaign.is_withdrawn = false;
+        campaign.allowlist_root = None;
+        campaign.referral_bps = 0;
+        campaign.referred_volume = 0;
+        campaign.is_verified = requires_verification;
+        campaign.open_disputes = 0;
+        campaign.fee_bps = ctx.accounts.config.campaign_fee_bps(ctx.accounts.farmer_profile.total_raised);
+        campaign.co_owners = Vec::new();
+        campaign.co_owner_threshold = 0;
+        campaign.authority = ctx.accounts.farmer.key();
+        campaign.payout_wallet = ctx.accounts.farmer_profile.payout_wallet;
+        campaign.pending_payout_wallet = None;
+        campaign.payout_change_at = 0;
+        campaign.category = category;
+        campaign.tags = tags;
+        campaign.geohash = geohash;
+        campaign.refunded_amount = 0;
+        campaign.referral_claimed_volume = 0;
+        campaign.fulfilled_amount = 0;
+        campaign.holdback_amount = 0;
+        campaign.pending_authority = None;
+        
+        // Keep the vault rent-exempt so payouts never strand a sub-rent balance in it
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.farmer.to_account_info(),
+                    to: ctx.accounts.vault.to_account_info(),
+                },
+            ),
+            Rent::get()?.minimum_balance(0),
+        )?;
+        
+        // List the campaign under its category and geohash cell
+        let category_index = &mut ctx.accounts.category_index;
+        category_index.bump = ctx.bumps.category_index;
+        category_index.category = category;
+        category_index.geohash_cell = campaign.geohash[..GEOHASH_INDEX_PRECISION].to_string();
+        ctx.accounts.category_page.bump = ctx.bumps.category_page;
+        index::record_listing(
+            category_index,
+            &mut ctx.accounts.category_page,
+            campaign.key(),
+            &ctx.accounts.farmer.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+        )?;
         
         let config = &mut ctx.accounts.config;
         config.total_campaigns = config.total_campaigns
             .checked_add(1)
             .ok_or(ErrorCode::MathOverflow)?;
         
+        let profile = &mut ctx.accounts.farmer_profile;
+        profile.campaigns_created = profile.campaigns_created
+            .checked_add(1)
+            .ok_or(ErrorCode::MathOverflow)?;
+        profile.next_campaign_id = profile.next_campaign_id
+            .checked_add(1)
+            .ok_or(ErrorCode::MathOverflow)?;
+        
         Ok(())
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn create_tier(
         ctx: Context<CreateTier>,
         tier_id: u8,
@@ -81,17 +493,33 @@ pub mod workspace {
         min_amount: u64,
         max_amount: u64,
         benefits: String,
-        max_backers: u32,
+        max_units: u32,
+        available_from: i64,
+        available_until: i64,
+        min_loyalty_level: LoyaltyLevel,
     ) -> Result<()> {
         require!(name.len() <= 32, ErrorCode::NameTooLong);
         require!(benefits.len() <= 256, ErrorCode::BenefitsTooLong);
         require!(min_amount > 0, ErrorCode::InvalidAmount);
         require!(max_amount == 0 || max_amount >= min_amount, ErrorCode::InvalidTierRange);
+        // 0 leaves either side of the availability window open
+        require!(available_from >= 0 && available_until >= 0, ErrorCode::InvalidTierWindow);
+        require!(
+            available_from == 0 || available_until == 0 || available_until > available_from,
+            ErrorCode::InvalidTierWindow
+        );
         
         let campaign = &ctx.accounts.campaign;
-        require!(campaign.farmer == ctx.accounts.farmer.key(), ErrorCode::Unauthorized);
+        require!(campaign.authority == ctx.accounts.authority.key(), ErrorCode::Unauthorized);
         require!(campaign.is_active, ErrorCode::CampaignNotActive);
         require!(!campaign.is_finalized, ErrorCode::CampaignFinalized);
+        // Loyalty levels only count lamports, so token backers could never reach a gate
+        require!(
+            min_loyalty_level == LoyaltyLevel::Bronze
+                || campaign.currency_mint == token::spl_token::native_mint::ID,
+            ErrorCode::LoyaltyGateRequiresSol
+        );
+        require_co_owner_approval(campaign, ctx.remaining_accounts)?;
         
         let tier = &mut ctx.accounts.tier;
         tier.bump = ctx.bumps.tier;
@@ -101,14 +529,29 @@ pub mod workspace {
         tier.min_amount = min_amount;
         tier.max_amount = max_amount;
         tier.benefits = benefits;
-        tier.max_backers = max_backers;
+        tier.max_units = max_units;
         tier.current_backers = 0;
+        tier.units_sold = 0;
+        tier.available_from = available_from;
+        tier.available_until = available_until;
+        tier.min_loyalty_level = min_loyalty_level;
         
         let campaign = &mut ctx.accounts.campaign;
         campaign.tiers_count = campaign.tiers_count
             .checked_add(1)
             .ok_or(ErrorCode::MathOverflow)?;
         
+        emit!(TierCreated {
+            campaign: campaign.key(),
+            tier: ctx.accounts.tier.key(),
+            tier_id,
+            min_amount,
+            max_amount,
+            max_units,
+            available_from,
+            available_until,
+        });
+        
         Ok(())
     }
 
@@ -116,6 +559,8 @@ pub mod workspace {
         ctx: Context<BackCampaignSol>,
         tier_id: u8,
         amount: u64,
+        quantity: u32,
+        proof: Vec<[u8; 32]>,
     ) -> Result<()> {
         let clock = Clock::get()?;
         let campaign = &ctx.accounts.campaign;
@@ -126,9 +571,44 @@ pub mod workspace {
         require!(clock.unix_timestamp >= campaign.start_time, ErrorCode::CampaignNotStarted);
         require!(clock.unix_timestamp <= campaign.end_time, ErrorCode::CampaignEnded);
         require!(tier.tier_id == tier_id, ErrorCode::InvalidTier);
-        require!(amount >= tier.min_amount, ErrorCode::AmountBelowMinimum);
-        require!(tier.max_amount == 0 || amount <= tier.max_amount, ErrorCode::AmountAboveMaximum);
-        require!(tier.max_backers == 0 || tier.current_backers < tier.max_backers, ErrorCode::TierFull);
+        require!(quantity > 0, ErrorCode::InvalidQuantity);
+        if let Some(root) = campaign.allowlist_root {
+            require!(
+                merkle::verify(&root, &ctx.accounts.backer.key(), &proof),
+                ErrorCode::NotAllowlisted
+            );
+        }
+        let referrer_wallet = ctx.accounts.referrer.as_ref().map(|referrer| referrer.wallet);
+        require!(referrer_wallet != Some(ctx.accounts.backer.key()), ErrorCode::SelfReferral);
+        
+        // Tier bounds are per reward unit
+        let min_total = tier.min_amount
+            .checked_mul(quantity as u64)
+            .ok_or(ErrorCode::MathOverflow)?;
+        require!(amount >= min_total, ErrorCode::AmountBelowMinimum);
+        if tier.max_amount > 0 {
+            let max_total = tier.max_amount
+                .checked_mul(quantity as u64)
+                .ok_or(ErrorCode::MathOverflow)?;
+            require!(amount <= max_total, ErrorCode::AmountAboveMaximum);
+        }
+        let units_after = tier.units_sold
+            .checked_add(quantity)
+            .ok_or(ErrorCode::MathOverflow)?;
+        require!(tier.max_units == 0 || units_after <= tier.max_units, ErrorCode::TierFull);
+        require!(
+            tier.available_from == 0 || clock.unix_timestamp >= tier.available_from,
+            ErrorCode::TierNotYetAvailable
+        );
+        require!(
+            tier.available_until == 0 || clock.unix_timestamp <= tier.available_until,
+            ErrorCode::TierExpired
+        );
+        let stats = &ctx.accounts.customer_stats;
+        require!(
+            ctx.accounts.config.loyalty_level(stats.lifetime_spend()?) >= tier.min_loyalty_level,
+            ErrorCode::LoyaltyLevelTooLow
+        );
         
         // Transfer SOL to vault
         system_program::transfer(
@@ -148,13 +628,31 @@ pub mod workspace {
         backing.campaign = ctx.accounts.campaign.key();
         backing.tier_id = tier_id;
         backing.amount = amount;
+        backing.quantity = quantity;
         backing.backed_at = clock.unix_timestamp;
         backing.is_refunded = false;
+        backing.referrer = referrer_wallet;
+        backing.is_fulfilled = false;
+        backing.refunded_amount = 0;
+        
+        // Index the backing on the current page of both indexes
+        ctx.accounts.campaign_backers.bump = ctx.bumps.campaign_backers;
+        ctx.accounts.backer_campaigns.bump = ctx.bumps.backer_campaigns;
+        index::record_backing(
+            &mut ctx.accounts.campaign_backers,
+            &mut ctx.accounts.backer_campaigns,
+            ctx.accounts.campaign.key(),
+            ctx.accounts.campaign.backers_count,
+            ctx.accounts.customer_stats.backings_count,
+            &ctx.accounts.backer.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+        )?;
         
         let tier = &mut ctx.accounts.tier;
         tier.current_backers = tier.current_backers
             .checked_add(1)
             .ok_or(ErrorCode::MathOverflow)?;
+        tier.units_sold = units_after;
         
         let campaign = &mut ctx.accounts.campaign;
         campaign.raised_amount = campaign.raised_amount
@@ -163,6 +661,44 @@ pub mod workspace {
         campaign.backers_count = campaign.backers_count
             .checked_add(1)
             .ok_or(ErrorCode::MathOverflow)?;
+        campaign.open_backings = campaign.open_backings
+            .checked_add(1)
+            .ok_or(ErrorCode::MathOverflow)?;
+        
+        let stats = &mut ctx.accounts.customer_stats;
+        stats.bump = ctx.bumps.customer_stats;
+        stats.wallet = ctx.accounts.backer.key();
+        stats.total_backed = stats.total_backed
+            .checked_add(amount)
+            .ok_or(ErrorCode::MathOverflow)?;
+        stats.backings_count = stats.backings_count
+            .checked_add(1)
+            .ok_or(ErrorCode::MathOverflow)?;
+        
+        if let Some(referrer) = ctx.accounts.referrer.as_mut() {
+            referrer.referred_volume = referrer.referred_volume
+                .checked_add(amount)
+                .ok_or(ErrorCode::MathOverflow)?;
+            referrer.referred_count = referrer.referred_count
+                .checked_add(1)
+                .ok_or(ErrorCode::MathOverflow)?;
+            campaign.referred_volume = campaign.referred_volume
+                .checked_add(amount)
+                .ok_or(ErrorCode::MathOverflow)?;
+        }
+        
+        emit!(CampaignBacked {
+            campaign: campaign.key(),
+            backer: ctx.accounts.backer.key(),
+            tier_id,
+            amount,
+            quantity,
+            referrer: referrer_wallet,
+            tier_backers: ctx.accounts.tier.current_backers,
+            tier_units_sold: ctx.accounts.tier.units_sold,
+            tier_available_until: ctx.accounts.tier.available_until,
+            backed_at: clock.unix_timestamp,
+        });
         
         Ok(())
     }
@@ -171,6 +707,8 @@ pub mod workspace {
         ctx: Context<BackCampaignToken>,
         tier_id: u8,
         amount: u64,
+        quantity: u32,
+        proof: Vec<[u8; 32]>,
     ) -> Result<()> {
         let clock = Clock::get()?;
         let campaign = &ctx.accounts.campaign;
@@ -181,9 +719,44 @@ pub mod workspace {
         require!(clock.unix_timestamp >= campaign.start_time, ErrorCode::CampaignNotStarted);
         require!(clock.unix_timestamp <= campaign.end_time, ErrorCode::CampaignEnded);
         require!(tier.tier_id == tier_id, ErrorCode::InvalidTier);
-        require!(amount >= tier.min_amount, ErrorCode::AmountBelowMinimum);
-        require!(tier.max_amount == 0 || amount <= tier.max_amount, ErrorCode::AmountAboveMaximum);
-        require!(tier.max_backers == 0 || tier.current_backers < tier.max_backers, ErrorCode::TierFull);
+        require!(quantity > 0, ErrorCode::InvalidQuantity);
+        if let Some(root) = campaign.allowlist_root {
+            require!(
+                merkle::verify(&root, &ctx.accounts.backer.key(), &proof),
+                ErrorCode::NotAllowlisted
+            );
+        }
+        let referrer_wallet = ctx.accounts.referrer.as_ref().map(|referrer| referrer.wallet);
+        require!(referrer_wallet != Some(ctx.accounts.backer.key()), ErrorCode::SelfReferral);
+        
+        // Tier bounds are per reward unit
+        let min_total = tier.min_amount
+            .checked_mul(quantity as u64)
+            .ok_or(ErrorCode::MathOverflow)?;
+        require!(amount >= min_total, ErrorCode::AmountBelowMinimum);
+        if tier.max_amount > 0 {
+            let max_total = tier.max_amount
+                .checked_mul(quantity as u64)
+                .ok_or(ErrorCode::MathOverflow)?;
+            require!(amount <= max_total, ErrorCode::AmountAboveMaximum);
+        }
+        let units_after = tier.units_sold
+            .checked_add(quantity)
+            .ok_or(ErrorCode::MathOverflow)?;
+        require!(tier.max_units == 0 || units_after <= tier.max_units, ErrorCode::TierFull);
+        require!(
+            tier.available_from == 0 || clock.unix_timestamp >= tier.available_from,
+            ErrorCode::TierNotYetAvailable
+        );
+        require!(
+            tier.available_until == 0 || clock.unix_timestamp <= tier.available_until,
+            ErrorCode::TierExpired
+        );
+        let stats = &ctx.accounts.customer_stats;
+        require!(
+            ctx.accounts.config.loyalty_level(stats.lifetime_spend()?) >= tier.min_loyalty_level,
+            ErrorCode::LoyaltyLevelTooLow
+        );
         require!(campaign.currency_mint == ctx.accounts.currency_mint.key(), ErrorCode::InvalidMint);
         
         // Transfer tokens to vault
@@ -205,13 +778,31 @@ pub mod workspace {
         backing.campaign = ctx.accounts.campaign.key();
         backing.tier_id = tier_id;
         backing.amount = amount;
+        backing.quantity = quantity;
         backing.backed_at = clock.unix_timestamp;
         backing.is_refunded = false;
+        backing.referrer = referrer_wallet;
+        backing.is_fulfilled = false;
+        backing.refunded_amount = 0;
+        
+        // Index the backing on the current page of both indexes
+        ctx.accounts.campaign_backers.bump = ctx.bumps.campaign_backers;
+        ctx.accounts.backer_campaigns.bump = ctx.bumps.backer_campaigns;
+        index::record_backing(
+            &mut ctx.accounts.campaign_backers,
+            &mut ctx.accounts.backer_campaigns,
+            ctx.accounts.campaign.key(),
+            ctx.accounts.campaign.backers_count,
+            ctx.accounts.customer_stats.backings_count,
+            &ctx.accounts.backer.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+        )?;
         
         let tier = &mut ctx.accounts.tier;
         tier.current_backers = tier.current_backers
             .checked_add(1)
             .ok_or(ErrorCode::MathOverflow)?;
+        tier.units_sold = units_after;
         
         let campaign = &mut ctx.accounts.campaign;
         campaign.raised_amount = campaign.raised_amount
@@ -220,116 +811,333 @@ pub mod workspace {
         campaign.backers_count = campaign.backers_count
             .checked_add(1)
             .ok_or(ErrorCode::MathOverflow)?;
+        campaign.open_backings = campaign.open_backings
+            .checked_add(1)
+            .ok_or(ErrorCode::MathOverflow)?;
+        
+        // Spend is tracked in lamports only, so token backings just count
+        let stats = &mut ctx.accounts.customer_stats;
+        stats.bump = ctx.bumps.customer_stats;
+        stats.wallet = ctx.accounts.backer.key();
+        stats.backings_count = stats.backings_count
+            .checked_add(1)
+            .ok_or(ErrorCode::MathOverflow)?;
+        
+        if let Some(referrer) = ctx.accounts.referrer.as_mut() {
+            referrer.referred_volume = referrer.referred_volume
+                .checked_add(amount)
+                .ok_or(ErrorCode::MathOverflow)?;
+            referrer.referred_count = referrer.referred_count
+                .checked_add(1)
+                .ok_or(ErrorCode::MathOverflow)?;
+            campaign.referred_volume = campaign.referred_volume
+                .checked_add(amount)
+                .ok_or(ErrorCode::MathOverflow)?;
+        }
+        
+        emit!(CampaignBacked {
+            campaign: campaign.key(),
+            backer: ctx.accounts.backer.key(),
+            tier_id,
+            amount,
+            quantity,
+            referrer: referrer_wallet,
+            tier_backers: ctx.accounts.tier.current_backers,
+            tier_units_sold: ctx.accounts.tier.units_sold,
+            tier_available_until: ctx.accounts.tier.available_until,
+            backed_at: clock.unix_timestamp,
+        });
         
         Ok(())
     }
 
-    pub fn finalize_campaign(ctx: Context<FinalizeCampaign>) -> Result<()> {
-        let clock = Clock::get()?;
+    // root: None opens the campaign to everyone
+    pub fn set_allowlist_root(
+        ctx: Context<SetAllowlistRoot>,
+        root: Option<[u8; 32]>,
+    ) -> Result<()> {
         let campaign = &ctx.accounts.campaign;
-        
+        require!(campaign.authority == ctx.accounts.authority.key(), ErrorCode::Unauthorized);
         require!(campaign.is_active, ErrorCode::CampaignNotActive);
-        require!(!campaign.is_finalized, ErrorCode::CampaignAlreadyFinalized);
-        require!(clock.unix_timestamp > campaign.end_time, ErrorCode::CampaignNotEnded);
+        require!(!campaign.is_finalized, ErrorCode::CampaignFinalized);
+        // The allow-list can only change while the campaign is still a draft with no backers
+        require!(campaign.backers_count == 0, ErrorCode::AllowlistLocked);
+        require_co_owner_approval(campaign, ctx.remaining_accounts)?;
         
         let campaign = &mut ctx.accounts.campaign;
-        campaign.is_active = false;
-        campaign.is_finalized = true;
+        campaign.allowlist_root = root;
         
         Ok(())
     }
 
-    pub fn withdraw_funds_sol(ctx: Context<WithdrawFundsSol>) -> Result<()> {
-        let campaign = &ctx.accounts.campaign;
-        let config = &ctx.accounts.config;
+    // referral_bps: u16, Share of referred volume referrers can claim after withdrawal
+    pub fn set_referral_bps(
+        ctx: Context<SetReferralBps>,
+        referral_bps: u16,
+    ) -> Result<()> {
+        require!(referral_bps <= 10000, ErrorCode::InvalidReferralBps);
         
-        require!(campaign.is_finalized, ErrorCode::CampaignNotFinalized);
-        require!(campaign.raised_amount >= campaign.goal_amount, ErrorCode::GoalNotReached);
-        require!(campaign.farmer == ctx.accounts.farmer.key(), ErrorCode::Unauthorized);
+        let campaign = &ctx.accounts.campaign;
+        require!(campaign.authority == ctx.accounts.authority.key(), ErrorCode::Unauthorized);
+        require!(campaign.is_active, ErrorCode::CampaignNotActive);
+        require!(!campaign.is_finalized, ErrorCode::CampaignFinalized);
+        // Fee and referral rewards both come out of the vault before the farmer's share
+        require!(
+            referral_bps as u64 + campaign.fee_bps as u64 <= 10000,
+            ErrorCode::InvalidReferralBps
+        );
+        // Referrers are promised a share at the time they bring in backers
+        require!(campaign.backers_count == 0, ErrorCode::ReferralLocked);
+        require_co_owner_approval(campaign, ctx.remaining_accounts)?;
         
-        let vault_balance = ctx.accounts.vault.lamports();
-        require!(vault_balance > 0, ErrorCode::NoFundsToWithdraw);
+        let campaign = &mut ctx.accounts.campaign;
+        campaign.referral_bps = referral_bps;
         
-        let fee = vault_balance
-            .checked_mul(config.fee_bps as u64)
-            .ok_or(ErrorCode::MathOverflow)?
-            .checked_div(10000)
-            .ok_or(ErrorCode::MathOverflow)?;
+        Ok(())
+    }
+
+    // remaining_accounts (here and in the other campaign management instructions):
+    // co-owner wallets signing to meet Campaign.co_owner_threshold
+    pub fn update_campaign(
+        ctx: Context<UpdateCampaign>,
+        title: String,
+        description: String,
+    ) -> Result<()> {
+        require!(title.len() <= 64, ErrorCode::TitleTooLong);
+        require!(description.len() <= 256, ErrorCode::DescriptionTooLong);
         
-        let farmer_amount = vault_balance
-            .checked_sub(fee)
-            .ok_or(ErrorCode::MathOverflow)?;
+        let campaign = &ctx.accounts.campaign;
+        require!(campaign.authority == ctx.accounts.authority.key(), ErrorCode::Unauthorized);
+        require!(campaign.is_active, ErrorCode::CampaignNotActive);
+        require!(!campaign.is_finalized, ErrorCode::CampaignFinalized);
+        require_co_owner_approval(campaign, ctx.remaining_accounts)?;
         
-        let farmer_key = ctx.accounts.farmer.key();
-        let campaign_id_bytes = campaign.campaign_id.to_le_bytes();
-        let bump = campaign.bump;
+        let campaign = &mut ctx.accounts.campaign;
+        campaign.title = title;
+        campaign.description = description;
         
-        let seeds = &[
-            b"vault",
-            farmer_key.as_ref(),
-            campaign_id_bytes.as_ref(),
-            &[bump],
-        ];
-        let signer_seeds: &[&[&[u8]]] = &[seeds];
+        Ok(())
+    }
+
+    // co_owners: Partners sharing withdrawn proceeds; the lead farmer keeps the remaining bps
+    // threshold: u8, Co-owner signatures required by management instructions
+    pub fn set_co_owners(
+        ctx: Context<UpdateCampaign>,
+        co_owners: Vec<CoOwner>,
+        threshold: u8,
+    ) -> Result<()> {
+        require!(co_owners.len() <= MAX_CO_OWNERS, ErrorCode::InvalidCoOwners);
+        require!(threshold as usize <= co_owners.len(), ErrorCode::InvalidCoOwners);
         
-        // Transfer fee to treasury
-        if fee > 0 {
-            system_program::transfer(
-                CpiContext::new_with_signer(
-                    ctx.accounts.system_program.to_account_info(),
-                    system_program::Transfer {
-                        from: ctx.accounts.vault.to_account_info(),
-                        to: ctx.accounts.treasury.to_account_info(),
-                    },
-                    signer_seeds,
-                ),
-                fee,
-            )?;
+        let campaign = &ctx.accounts.campaign;
+        let mut total_bps: u16 = 0;
+        for (i, co_owner) in co_owners.iter().enumerate() {
+            require!(co_owner.share_bps > 0, ErrorCode::InvalidCoOwners);
+            require!(co_owner.wallet != campaign.farmer, ErrorCode::InvalidCoOwners);
+            require!(
+                !co_owners[..i].iter().any(|seen| seen.wallet == co_owner.wallet),
+                ErrorCode::InvalidCoOwners
+            );
+            total_bps = total_bps
+                .checked_add(co_owner.share_bps)
+                .ok_or(ErrorCode::InvalidCoOwners)?;
         }
+        require!(total_bps <= 10000, ErrorCode::InvalidCoOwners);
         
-        // Transfer remaining to farmer
-        system_program::transfer(
-            CpiContext::new_with_signer(
-                ctx.accounts.system_program.to_account_info(),
-                system_program::Transfer {
-                    from: ctx.accounts.vault.to_account_info(),
-                    to: ctx.accounts.farmer.to_account_info(),
-                },
-                signer_seeds,
-            ),
-            farmer_amount,
-        )?;
+        require!(campaign.authority == ctx.accounts.authority.key(), ErrorCode::Unauthorized);
+        require!(campaign.is_active, ErrorCode::CampaignNotActive);
+        require!(!campaign.is_finalized, ErrorCode::CampaignFinalized);
+        // Backers commit knowing who shares the proceeds
+        require!(campaign.backers_count == 0, ErrorCode::CoOwnersLocked);
+        require_co_owner_approval(campaign, ctx.remaining_accounts)?;
         
-        let config = &mut ctx.accounts.config;
-        config.total_raised = config.total_raised
-            .checked_add(farmer_amount)
-            .ok_or(ErrorCode::MathOverflow)?;
+        let campaign = &mut ctx.accounts.campaign;
+        campaign.co_owners = co_owners;
+        campaign.co_owner_threshold = threshold;
+        
+        emit!(CoOwnersSet {
+            campaign: campaign.key(),
+            co_owners: campaign.co_owners.len() as u8,
+            threshold,
+        });
         
         Ok(())
     }
 
-    pub fn withdraw_funds_token(ctx: Context<WithdrawFundsToken>) -> Result<()> {
+    // new_authority: Key (e.g. a multisig) controlling the campaign; PDAs stay seeded by the farmer
+    // Authority moves in two steps so a mistyped key can't strand the campaign
+    pub fn propose_campaign_authority(
+        ctx: Context<UpdateCampaign>,
+        new_authority: Pubkey,
+    ) -> Result<()> {
         let campaign = &ctx.accounts.campaign;
-        let config = &ctx.accounts.config;
+        require!(campaign.authority == ctx.accounts.authority.key(), ErrorCode::Unauthorized);
+        require!(new_authority != Pubkey::default(), ErrorCode::InvalidAuthority);
+        require_co_owner_approval(campaign, ctx.remaining_accounts)?;
         
-        require!(campaign.is_finalized, ErrorCode::CampaignNotFinalized);
-        require!(campaign.raised_amount >= campaign.goal_amount, ErrorCode::GoalNotReached);
-        require!(campaign.farmer == ctx.accounts.farmer.key(), ErrorCode::Unauthorized);
+        let campaign = &mut ctx.accounts.campaign;
+        campaign.pending_authority = Some(new_authority);
         
-        let vault_balance = ctx.accounts.vault_token.amount;
-        require!(vault_balance > 0, ErrorCode::NoFundsToWithdraw);
+        emit!(CampaignAuthorityProposed {
+            campaign: campaign.key(),
+            authority: campaign.authority,
+            new_authority,
+        });
         
-        let fee = vault_balance
-            .checked_mul(config.fee_bps as u64)
+        Ok(())
+    }
+
+    pub fn accept_campaign_authority(ctx: Context<AcceptCampaignAuthority>) -> Result<()> {
+        let campaign = &ctx.accounts.campaign;
+        let new_authority = campaign.pending_authority.ok_or(ErrorCode::NoPendingAuthority)?;
+        require!(new_authority == ctx.accounts.new_authority.key(), ErrorCode::Unauthorized);
+        
+        let previous_authority = campaign.authority;
+        let campaign = &mut ctx.accounts.campaign;
+        campaign.authority = new_authority;
+        campaign.pending_authority = None;
+        
+        emit!(CampaignAuthorityTransferred {
+            campaign: campaign.key(),
+            previous_authority,
+            new_authority,
+        });
+        
+        Ok(())
+    }
+
+    // Payout changes are timelocked so backers and co-owners can see them coming
+    pub fn request_payout_change(
+        ctx: Context<UpdateCampaign>,
+        payout_wallet: Pubkey,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        let campaign = &ctx.accounts.campaign;
+        require!(campaign.authority == ctx.accounts.authority.key(), ErrorCode::Unauthorized);
+        require!(!campaign.is_withdrawn, ErrorCode::AlreadyWithdrawn);
+        require!(payout_wallet != Pubkey::default(), ErrorCode::InvalidPayoutWallet);
+        require_co_owner_approval(campaign, ctx.remaining_accounts)?;
+        
+        let effective_at = clock.unix_timestamp
+            .checked_add(PAYOUT_CHANGE_DELAY)
+            .ok_or(ErrorCode::MathOverflow)?;
+        
+        let campaign = &mut ctx.accounts.campaign;
+        campaign.pending_payout_wallet = Some(payout_wallet);
+        campaign.payout_change_at = effective_at;
+        
+        emit!(PayoutChangeRequested {
+            campaign: campaign.key(),
+            payout_wallet,
+            effective_at,
+        });
+        
+        Ok(())
+    }
+
+    pub fn apply_payout_change(ctx: Context<UpdateCampaign>) -> Result<()> {
+        let clock = Clock::get()?;
+        let campaign = &ctx.accounts.campaign;
+        require!(campaign.authority == ctx.accounts.authority.key(), ErrorCode::Unauthorized);
+        let payout_wallet = campaign.pending_payout_wallet.ok_or(ErrorCode::NoPendingPayoutChange)?;
+        require!(clock.unix_timestamp >= campaign.payout_change_at, ErrorCode::PayoutChangeTimelocked);
+        
+        let campaign = &mut ctx.accounts.campaign;
+        campaign.payout_wallet = payout_wallet;
+        campaign.pending_payout_wallet = None;
+        campaign.payout_change_at = 0;
+        
+        emit!(PayoutWalletChanged {
+            campaign: campaign.key(),
+            payout_wallet,
+        });
+        
+        Ok(())
+    }
+
+    pub fn register_referrer(ctx: Context<RegisterReferrer>) -> Result<()> {
+        let campaign = &ctx.accounts.campaign;
+        require!(campaign.is_active, ErrorCode::CampaignNotActive);
+        require!(!campaign.is_finalized, ErrorCode::CampaignFinalized);
+        
+        let referrer = &mut ctx.accounts.referrer;
+        referrer.bump = ctx.bumps.referrer;
+        referrer.campaign = ctx.accounts.campaign.key();
+        referrer.wallet = ctx.accounts.wallet.key();
+        referrer.referred_volume = 0;
+        referrer.referred_count = 0;
+        referrer.reward_amount = 0;
+        referrer.is_paid = false;
+        
+        Ok(())
+    }
+
+    pub fn claim_referral_sol(ctx: Context<ClaimReferralSol>) -> Result<()> {
+        let campaign = &ctx.accounts.campaign;
+        let referrer = &ctx.accounts.referrer;
+        
+        require!(campaign.is_withdrawn, ErrorCode::CampaignNotSettled);
+        require!(!referrer.is_paid, ErrorCode::ReferralAlreadyPaid);
+        
+        let reward = referrer.referred_volume
+            .checked_mul(campaign.referral_bps as u64)
             .ok_or(ErrorCode::MathOverflow)?
             .checked_div(10000)
             .ok_or(ErrorCode::MathOverflow)?;
         
-        let farmer_amount = vault_balance
-            .checked_sub(fee)
+        let farmer_key = campaign.farmer;
+        let campaign_id_bytes = campaign.campaign_id.to_le_bytes();
+        let bump = ctx.bumps.vault;
+        
+        let seeds = &[
+            b"vault",
+            farmer_key.as_ref(),
+            campaign_id_bytes.as_ref(),
+            &[bump],
+        ];
+        let signer_seeds: &[&[&[u8]]] = &[seeds];
+        
+        if reward > 0 {
+            system_program::transfer(
+                CpiContext::new_with_signer(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: ctx.accounts.vault.to_account_info(),
+                        to: ctx.accounts.wallet.to_account_info(),
+                    },
+                    signer_seeds,
+                ),
+                reward,
+            )?;
+        }
+        
+        let referred_volume = referrer.referred_volume;
+        let referrer = &mut ctx.accounts.referrer;
+        referrer.reward_amount = reward;
+        referrer.is_paid = true;
+        
+        let campaign = &mut ctx.accounts.campaign;
+        campaign.referral_claimed_volume = campaign.referral_claimed_volume
+            .checked_add(referred_volume)
+            .ok_or(ErrorCode::MathOverflow)?;
+        
+        Ok(())
+    }
+
+    pub fn claim_referral_token(ctx: Context<ClaimReferralToken>) -> Result<()> {
+        let campaign = &ctx.accounts.campaign;
+        let referrer = &ctx.accounts.referrer;
+        
+        require!(campaign.is_withdrawn, ErrorCode::CampaignNotSettled);
+        require!(!referrer.is_paid, ErrorCode::ReferralAlreadyPaid);
+        
+        let reward = referrer.referred_volume
+            .checked_mul(campaign.referral_bps as u64)
+            .ok_or(ErrorCode::MathOverflow)?
+            .checked_div(10000)
             .ok_or(ErrorCode::MathOverflow)?;
         
-        let farmer_key = ctx.accounts.farmer.key();
+        let farmer_key = campaign.farmer;
         let campaign_id_bytes = campaign.campaign_id.to_le_bytes();
         let bump = ctx.bumps.vault_token;
         
@@ -341,58 +1149,77 @@ pub mod workspace {
         ];
         let signer_seeds: &[&[&[u8]]] = &[seeds];
         
-        // Transfer fee to treasury
-        if fee > 0 {
+        if reward > 0 {
             token::transfer(
                 CpiContext::new_with_signer(
                     ctx.accounts.token_program.to_account_info(),
                     Transfer {
                         from: ctx.accounts.vault_token.to_account_info(),
-                        to: ctx.accounts.treasury_token.to_account_info(),
+                        to: ctx.accounts.wallet_token.to_account_info(),
                         authority: ctx.accounts.vault_token.to_account_info(),
                     },
                     signer_seeds,
                 ),
-                fee,
+                reward,
             )?;
         }
         
-        // Transfer remaining to farmer
-        token::transfer(
-            CpiContext::new_with_signer(
-                ctx.accounts.token_program.to_account_info(),
-                Transfer {
-                    from: ctx.accounts.vault_token.to_account_info(),
-                    to: ctx.accounts.farmer_token.to_account_info(),
-                    authority: ctx.accounts.vault_token.to_account_info(),
-                },
-                signer_seeds,
-            ),
-            farmer_amount,
-        )?;
+        let referred_volume = referrer.referred_volume;
+        let referrer = &mut ctx.accounts.referrer;
+        referrer.reward_amount = reward;
+        referrer.is_paid = true;
         
-        let config = &mut ctx.accounts.config;
-        config.total_raised = config.total_raised
-            .checked_add(farmer_amount)
+        let campaign = &mut ctx.accounts.campaign;
+        campaign.referral_claimed_volume = campaign.referral_claimed_volume
+            .checked_add(referred_volume)
             .ok_or(ErrorCode::MathOverflow)?;
         
         Ok(())
     }
 
-    pub fn claim_refund_sol(ctx: Context<ClaimRefundSol>) -> Result<()> {
+    pub fn finalize_campaign(ctx: Context<FinalizeCampaign>) -> Result<()> {
+        let clock = Clock::get()?;
+        let campaign = &ctx.accounts.campaign;
+        
+        require!(campaign.is_active, ErrorCode::CampaignNotActive);
+        require!(!campaign.is_finalized, ErrorCode::CampaignAlreadyFinalized);
+        require!(clock.unix_timestamp > campaign.end_time, ErrorCode::CampaignNotEnded);
+        
+        let campaign = &mut ctx.accounts.campaign;
+        campaign.is_active = false;
+        campaign.is_finalized = true;
+        
+        Ok(())
+    }
+
+    // remaining_accounts: one wallet per Config.fee_splits entry, then one wallet
+    // per Campaign.co_owners entry
+    pub fn withdraw_funds_sol<'info>(
+        ctx: Context<'_, '_, 'info, 'info, WithdrawFundsSol<'info>>,
+    ) -> Result<()> {
         let campaign = &ctx.accounts.campaign;
-        let backing = &ctx.accounts.backing;
         
         require!(campaign.is_finalized, ErrorCode::CampaignNotFinalized);
-        require!(campaign.raised_amount < campaign.goal_amount, ErrorCode::GoalReached);
-        require!(!backing.is_refunded, ErrorCode::AlreadyRefunded);
-        require!(backing.backer == ctx.accounts.backer.key(), ErrorCode::Unauthorized);
+        require!(campaign.raised_amount >= campaign.goal_amount, ErrorCode::GoalNotReached);
+        require!(campaign.authority == ctx.accounts.authority.key(), ErrorCode::Unauthorized);
+        require!(!campaign.is_withdrawn, ErrorCode::AlreadyWithdrawn);
+        require!(campaign.open_disputes == 0, ErrorCode::DisputesPending);
         
-        let refund_amount = backing.amount;
+        // The rent reserve stays in the vault until close_campaign sweeps it
+        let vault_balance = ctx.accounts.vault.lamports()
+            .saturating_sub(Rent::get()?.minimum_balance(0));
+        require!(vault_balance > 0, ErrorCode::NoFundsToWithdraw);
+        
+        // Fee was fixed when the campaign was created, unless the fee manager overrode it
+        let fee = vault_balance
+            .checked_mul(campaign.fee_bps as u64)
+            .ok_or(ErrorCode::MathOverflow)?
+            .checked_div(10000)
+            .ok_or(ErrorCode::MathOverflow)?;
         
         let farmer_key = campaign.farmer;
         let campaign_id_bytes = campaign.campaign_id.to_le_bytes();
-        let bump = campaign.bump;
+        let bump = ctx.bumps.vault;
         
         let seeds = &[
             b"vault",
@@ -402,34 +1229,162 @@ pub mod workspace {
         ];
         let signer_seeds: &[&[&[u8]]] = &[seeds];
         
+        let fee_splits = &ctx.accounts.config.fee_splits;
+        let split_at = fee_splits.len().min(ctx.remaining_accounts.len());
+        let (fee_accounts, payout_accounts) = ctx.remaining_accounts.split_at(split_at);
+        require!(fee_accounts.len() == fee_splits.len(), ErrorCode::FeeRecipientsMissing);
+        let co_owner_accounts = payout_accounts;
+        require!(co_owner_accounts.len() == campaign.co_owners.len(), ErrorCode::CoOwnerRecipientsMissing);
+        
+        // Pay fee partners their share
+        let mut partner_fees: u64 = 0;
+        for (split, recipient_info) in fee_splits.iter().zip(fee_accounts) {
+            require_keys_eq!(recipient_info.key(), split.wallet, ErrorCode::InvalidFeeRecipient);
+            
+            let share = split.share_of(fee)?;
+            if share > 0 {
+                system_program::transfer(
+                    CpiContext::new_with_signer(
+                        ctx.accounts.system_program.to_account_info(),
+                        system_program::Transfer {
+                            from: ctx.accounts.vault.to_account_info(),
+                            to: recipient_info.clone(),
+                        },
+                        signer_seeds,
+                    ),
+                    share,
+                )?;
+            }
+            partner_fees = partner_fees
+                .checked_add(share)
+                .ok_or(ErrorCode::MathOverflow)?;
+        }
+        
+        // Transfer the rest of the fee, including rounding dust, to treasury
+        let treasury_fee = fee
+            .checked_sub(partner_fees)
+            .ok_or(ErrorCode::MathOverflow)?;
+        if treasury_fee > 0 {
+            system_program::transfer(
+                CpiContext::new_with_signer(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: ctx.accounts.vault.to_account_info(),
+                        to: ctx.accounts.treasury.to_account_info(),
+                    },
+                    signer_seeds,
+                ),
+                treasury_fee,
+            )?;
+        }
+        
+        // Referral rewards stay in the vault for referrers to claim
+        let referral_reserve = campaign.referred_volume
+            .checked_mul(campaign.referral_bps as u64)
+            .ok_or(ErrorCode::MathOverflow)?
+            .checked_div(10000)
+            .ok_or(ErrorCode::MathOverflow)?;
+        
+        let farmer_amount = vault_balance
+            .checked_sub(fee)
+            .ok_or(ErrorCode::MathOverflow)?
+            .checked_sub(referral_reserve)
+            .ok_or(ErrorCode::MathOverflow)?;
+        
+        // Pay co-owners their share of the proceeds
+        let mut co_owner_paid: u64 = 0;
+        for (co_owner, recipient_info) in campaign.co_owners.iter().zip(co_owner_accounts) {
+            require_keys_eq!(recipient_info.key(), co_owner.wallet, ErrorCode::InvalidCoOwnerRecipient);
+            
+            let share = co_owner.share_of(farmer_amount)?;
+            if share > 0 {
+                system_program::transfer(
+                    CpiContext::new_with_signer(
+                        ctx.accounts.system_program.to_account_info(),
+                        system_program::Transfer {
+                            from: ctx.accounts.vault.to_account_info(),
+                            to: recipient_info.clone(),
+                        },
+                        signer_seeds,
+                    ),
+                    share,
+                )?;
+            }
+            co_owner_paid = co_owner_paid
+                .checked_add(share)
+                .ok_or(ErrorCode::MathOverflow)?;
+        }
+        // The lead farmer delivers the rewards, so their share carries the holdback
+        let holdback = campaign.raised_amount
+            .checked_sub(campaign.fulfilled_amount)
+            .ok_or(ErrorCode::MathOverflow)?
+            .checked_mul(REWARD_HOLDBACK_BPS as u64)
+            .ok_or(ErrorCode::MathOverflow)?
+            .checked_div(10000)
+            .ok_or(ErrorCode::MathOverflow)?;
+        let lead_amount = farmer_amount
+            .checked_sub(co_owner_paid)
+            .ok_or(ErrorCode::MathOverflow)?;
+        let holdback = holdback.min(lead_amount);
+        let lead_amount = lead_amount
+            .checked_sub(holdback)
+            .ok_or(ErrorCode::MathOverflow)?;
+        
+        // Transfer remaining to farmer
         system_program::transfer(
             CpiContext::new_with_signer(
                 ctx.accounts.system_program.to_account_info(),
                 system_program::Transfer {
                     from: ctx.accounts.vault.to_account_info(),
-                    to: ctx.accounts.backer.to_account_info(),
+                    to: ctx.accounts.payout_wallet.to_account_info(),
                 },
                 signer_seeds,
             ),
-            refund_amount,
+            lead_amount,
         )?;
         
-        let backing = &mut ctx.accounts.backing;
-        backing.is_refunded = true;
+        let config = &mut ctx.accounts.config;
+        config.total_raised = config.total_raised
+            .checked_add(farmer_amount)
+            .ok_or(ErrorCode::MathOverflow)?;
+        
+        let profile = &mut ctx.accounts.farmer_profile;
+        profile.campaigns_funded = profile.campaigns_funded
+            .checked_add(1)
+            .ok_or(ErrorCode::MathOverflow)?;
+        profile.total_raised = profile.total_raised
+            .checked_add(farmer_amount)
+            .ok_or(ErrorCode::MathOverflow)?;
+        
+        let campaign = &mut ctx.accounts.campaign;
+        campaign.is_withdrawn = true;
+        campaign.holdback_amount = holdback;
         
         Ok(())
     }
 
-    pub fn claim_refund_token(ctx: Context<ClaimRefundToken>) -> Result<()> {
+    // remaining_accounts: one token account per Config.fee_splits entry, then one
+    // per Campaign.co_owners entry
+    pub fn withdraw_funds_token<'info>(
+        ctx: Context<'_, '_, 'info, 'info, WithdrawFundsToken<'info>>,
+    ) -> Result<()> {
         let campaign = &ctx.accounts.campaign;
-        let backing = &ctx.accounts.backing;
         
         require!(campaign.is_finalized, ErrorCode::CampaignNotFinalized);
-        require!(campaign.raised_amount < campaign.goal_amount, ErrorCode::GoalReached);
-        require!(!backing.is_refunded, ErrorCode::AlreadyRefunded);
-        require!(backing.backer == ctx.accounts.backer.key(), ErrorCode::Unauthorized);
+        require!(campaign.raised_amount >= campaign.goal_amount, ErrorCode::GoalNotReached);
+        require!(campaign.authority == ctx.accounts.authority.key(), ErrorCode::Unauthorized);
+        require!(!campaign.is_withdrawn, ErrorCode::AlreadyWithdrawn);
+        require!(campaign.open_disputes == 0, ErrorCode::DisputesPending);
+        
+        let vault_balance = ctx.accounts.vault_token.amount;
+        require!(vault_balance > 0, ErrorCode::NoFundsToWithdraw);
         
-        let refund_amount = backing.amount;
+        // Fee was fixed when the campaign was created, unless the fee manager overrode it
+        let fee = vault_balance
+            .checked_mul(campaign.fee_bps as u64)
+            .ok_or(ErrorCode::MathOverflow)?
+            .checked_div(10000)
+            .ok_or(ErrorCode::MathOverflow)?;
         
         let farmer_key = campaign.farmer;
         let campaign_id_bytes = campaign.campaign_id.to_le_bytes();
@@ -443,302 +1398,5169 @@ pub mod workspace {
         ];
         let signer_seeds: &[&[&[u8]]] = &[seeds];
         
+        let fee_splits = &ctx.accounts.config.fee_splits;
+        let split_at = fee_splits.len().min(ctx.remaining_accounts.len());
+        let (fee_accounts, payout_accounts) = ctx.remaining_accounts.split_at(split_at);
+        require!(fee_accounts.len() == fee_splits.len(), ErrorCode::FeeRecipientsMissing);
+        let co_owner_accounts = payout_accounts;
+        require!(co_owner_accounts.len() == campaign.co_owners.len(), ErrorCode::CoOwnerRecipientsMissing);
+        
+        // Pay fee partners their share
+        let mut partner_fees: u64 = 0;
+        for (split, recipient_info) in fee_splits.iter().zip(fee_accounts) {
+            let recipient_token = Account::<TokenAccount>::try_from(recipient_info)?;
+            require!(recipient_token.owner == split.wallet, ErrorCode::InvalidFeeRecipient);
+            require!(recipient_token.mint == ctx.accounts.currency_mint.key(), ErrorCode::InvalidMint);
+            
+            let share = split.share_of(fee)?;
+            if share > 0 {
+                token::transfer(
+                    CpiContext::new_with_signer(
+                        ctx.accounts.token_program.to_account_info(),
+                        Transfer {
+                            from: ctx.accounts.vault_token.to_account_info(),
+                            to: recipient_info.clone(),
+                            authority: ctx.accounts.vault_token.to_account_info(),
+                        },
+                        signer_seeds,
+                    ),
+                    share,
+                )?;
+            }
+            partner_fees = partner_fees
+                .checked_add(share)
+                .ok_or(ErrorCode::MathOverflow)?;
+        }
+        
+        // Transfer the rest of the fee, including rounding dust, to treasury
+        let treasury_fee = fee
+            .checked_sub(partner_fees)
+            .ok_or(ErrorCode::MathOverflow)?;
+        if treasury_fee > 0 {
+            token::transfer(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    Transfer {
+                        from: ctx.accounts.vault_token.to_account_info(),
+                        to: ctx.accounts.treasury_token.to_account_info(),
+                        authority: ctx.accounts.vault_token.to_account_info(),
+                    },
+                    signer_seeds,
+                ),
+                treasury_fee,
+            )?;
+        }
+        
+        // Referral rewards stay in the vault for referrers to claim
+        let referral_reserve = campaign.referred_volume
+            .checked_mul(campaign.referral_bps as u64)
+            .ok_or(ErrorCode::MathOverflow)?
+            .checked_div(10000)
+            .ok_or(ErrorCode::MathOverflow)?;
+        
+        let farmer_amount = vault_balance
+            .checked_sub(fee)
+            .ok_or(ErrorCode::MathOverflow)?
+            .checked_sub(referral_reserve)
+            .ok_or(ErrorCode::MathOverflow)?;
+        
+        // Pay co-owners their share of the proceeds
+        let mut co_owner_paid: u64 = 0;
+        for (co_owner, recipient_info) in campaign.co_owners.iter().zip(co_owner_accounts) {
+            let recipient_token = Account::<TokenAccount>::try_from(recipient_info)?;
+            require!(recipient_token.owner == co_owner.wallet, ErrorCode::InvalidCoOwnerRecipient);
+            require!(recipient_token.mint == ctx.accounts.currency_mint.key(), ErrorCode::InvalidMint);
+            
+            let share = co_owner.share_of(farmer_amount)?;
+            if share > 0 {
+                token::transfer(
+                    CpiContext::new_with_signer(
+                        ctx.accounts.token_program.to_account_info(),
+                        Transfer {
+                            from: ctx.accounts.vault_token.to_account_info(),
+                            to: recipient_info.clone(),
+                            authority: ctx.accounts.vault_token.to_account_info(),
+                        },
+                        signer_seeds,
+                    ),
+                    share,
+                )?;
+            }
+            co_owner_paid = co_owner_paid
+                .checked_add(share)
+                .ok_or(ErrorCode::MathOverflow)?;
+        }
+        // The lead farmer delivers the rewards, so their share carries the holdback
+        let holdback = campaign.raised_amount
+            .checked_sub(campaign.fulfilled_amount)
+            .ok_or(ErrorCode::MathOverflow)?
+            .checked_mul(REWARD_HOLDBACK_BPS as u64)
+            .ok_or(ErrorCode::MathOverflow)?
+            .checked_div(10000)
+            .ok_or(ErrorCode::MathOverflow)?;
+        let lead_amount = farmer_amount
+            .checked_sub(co_owner_paid)
+            .ok_or(ErrorCode::MathOverflow)?;
+        let holdback = holdback.min(lead_amount);
+        let lead_amount = lead_amount
+            .checked_sub(holdback)
+            .ok_or(ErrorCode::MathOverflow)?;
+        
+        // Transfer remaining to farmer
         token::transfer(
             CpiContext::new_with_signer(
                 ctx.accounts.token_program.to_account_info(),
                 Transfer {
                     from: ctx.accounts.vault_token.to_account_info(),
-                    to: ctx.accounts.backer_token.to_account_info(),
+                    to: ctx.accounts.payout_token.to_account_info(),
                     authority: ctx.accounts.vault_token.to_account_info(),
                 },
                 signer_seeds,
             ),
-            refund_amount,
+            lead_amount,
         )?;
         
-        let backing = &mut ctx.accounts.backing;
-        backing.is_refunded = true;
+        let config = &mut ctx.accounts.config;
+        config.total_raised = config.total_raised
+            .checked_add(farmer_amount)
+            .ok_or(ErrorCode::MathOverflow)?;
+        
+        let profile = &mut ctx.accounts.farmer_profile;
+        profile.campaigns_funded = profile.campaigns_funded
+            .checked_add(1)
+            .ok_or(ErrorCode::MathOverflow)?;
+        profile.total_raised = profile.total_raised
+            .checked_add(farmer_amount)
+            .ok_or(ErrorCode::MathOverflow)?;
+        
+        let campaign = &mut ctx.accounts.campaign;
+        campaign.is_withdrawn = true;
+        campaign.holdback_amount = holdback;
         
         Ok(())
     }
-}
 
-// ==================== ACCOUNT STRUCTURES ====================
-
-#[account]
-pub struct Config {
+    pub fn claim_refund_sol(ctx: Context<ClaimRefundSol>) -> Result<()> {
+        let campaign = &ctx.accounts.campaign;
+        let backing = &ctx.accounts.backing;
+        
+        require!(campaign.is_finalized, ErrorCode::CampaignNotFinalized);
+        require!(campaign.raised_amount < campaign.goal_amount, ErrorCode::GoalReached);
+        require!(!backing.is_refunded, ErrorCode::AlreadyRefunded);
+        require!(backing.backer == ctx.accounts.backer.key(), ErrorCode::Unauthorized);
+        
+        // Part of the backing may already have gone back through a dispute
+        let refund_amount = backing.amount
+            .checked_sub(backing.refunded_amount)
+            .ok_or(ErrorCode::MathOverflow)?;
+        
+        let farmer_key = campaign.farmer;
+        let campaign_id_bytes = campaign.campaign_id.to_le_bytes();
+        let bump = ctx.bumps.vault;
+        
+        let seeds = &[
+            b"vault",
+            farmer_key.as_ref(),
+            campaign_id_bytes.as_ref(),
+            &[bump],
+        ];
+        let signer_seeds: &[&[&[u8]]] = &[seeds];
+        
+        system_program::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.vault.to_account_info(),
+                    to: ctx.accounts.backer.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            refund_amount,
+        )?;
+        
+        let backing = &mut ctx.accounts.backing;
+        backing.is_refunded = true;
+        backing.refunded_amount = backing.amount;
+        
+        let campaign = &mut ctx.accounts.campaign;
+        campaign.refunded_amount = campaign.refunded_amount
+            .checked_add(refund_amount)
+            .ok_or(ErrorCode::MathOverflow)?;
+        
+        let stats = &mut ctx.accounts.customer_stats;
+        stats.total_backed = stats.total_backed
+            .checked_sub(refund_amount)
+            .ok_or(ErrorCode::MathOverflow)?;
+        
+        Ok(())
+    }
+
+    pub fn claim_refund_token(ctx: Context<ClaimRefundToken>) -> Result<()> {
+        let campaign = &ctx.accounts.campaign;
+        let backing = &ctx.accounts.backing;
+        
+        require!(campaign.is_finalized, ErrorCode::CampaignNotFinalized);
+        require!(campaign.raised_amount < campaign.goal_amount, ErrorCode::GoalReached);
+        require!(!backing.is_refunded, ErrorCode::AlreadyRefunded);
+        require!(backing.backer == ctx.accounts.backer.key(), ErrorCode::Unauthorized);
+        
+        // Part of the backing may already have gone back through a dispute
+        let refund_amount = backing.amount
+            .checked_sub(backing.refunded_amount)
+            .ok_or(ErrorCode::MathOverflow)?;
+        
+        let farmer_key = campaign.farmer;
+        let campaign_id_bytes = campaign.campaign_id.to_le_bytes();
+        let bump = ctx.bumps.vault_token;
+        
+        let seeds = &[
+            b"vault_token",
+            farmer_key.as_ref(),
+            campaign_id_bytes.as_ref(),
+            &[bump],
+        ];
+        let signer_seeds: &[&[&[u8]]] = &[seeds];
+        
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.vault_token.to_account_info(),
+                    to: ctx.accounts.backer_token.to_account_info(),
+                    authority: ctx.accounts.vault_token.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            refund_amount,
+        )?;
+        
+        let backing = &mut ctx.accounts.backing;
+        backing.is_refunded = true;
+        backing.refunded_amount = backing.amount;
+        
+        let campaign = &mut ctx.accounts.campaign;
+        campaign.refunded_amount = campaign.refunded_amount
+            .checked_add(refund_amount)
+            .ok_or(ErrorCode::MathOverflow)?;
+        
+        Ok(())
+    }
+
+    pub fn close_backing(ctx: Context<CloseBacking>) -> Result<()> {
+        let backing = &ctx.accounts.backing;
+        
+        require!(backing.backer == ctx.accounts.backer.key(), ErrorCode::Unauthorized);
+        
+        let dispute_info = &ctx.accounts.dispute;
+        if dispute_info.owner == &crate::ID {
+            let dispute = Dispute::try_deserialize(&mut &dispute_info.try_borrow_data()?[..])?;
+            require!(dispute.status != DisputeStatus::Open, ErrorCode::DisputesPending);
+        }
+        
+        // A campaign only closes once it is settled, so a closed one needs no checks
+        let campaign_info = &ctx.accounts.campaign;
+        if campaign_info.owner == &crate::ID {
+            let mut data = campaign_info.try_borrow_mut_data()?;
+            let mut campaign = Campaign::try_deserialize(&mut &data[..])?;
+            require!(backing.is_refunded || campaign.is_withdrawn, ErrorCode::BackingNotSettled);
+            
+            campaign.open_backings = campaign.open_backings
+                .checked_sub(1)
+                .ok_or(ErrorCode::MathOverflow)?;
+            campaign.try_serialize(&mut &mut data[..])?;
+        }
+        
+        Ok(())
+    }
+
+    pub fn close_tier(ctx: Context<CloseTier>) -> Result<()> {
+        let campaign = &ctx.accounts.campaign;
+        
+        require!(campaign.authority == ctx.accounts.authority.key(), ErrorCode::Unauthorized);
+        require!(campaign.is_finalized, ErrorCode::CampaignNotFinalized);
+        
+        let campaign = &mut ctx.accounts.campaign;
+        campaign.tiers_count = campaign.tiers_count
+            .checked_sub(1)
+            .ok_or(ErrorCode::MathOverflow)?;
+        
+        Ok(())
+    }
+
+    pub fn close_campaign(ctx: Context<CloseCampaign>) -> Result<()> {
+        let clock = Clock::get()?;
+        let campaign = &ctx.accounts.campaign;
+        
+        require!(campaign.authority == ctx.accounts.authority.key(), ErrorCode::Unauthorized);
+        require!(campaign.is_finalized, ErrorCode::CampaignNotFinalized);
+        require!(
+            campaign.is_withdrawn || campaign.raised_amount < campaign.goal_amount,
+            ErrorCode::CampaignNotSettled
+        );
+        require!(campaign.tiers_count == 0, ErrorCode::OpenTiersRemain);
+        // Closing strands whatever refunds are still sitting in the vault
+        require!(
+            campaign.is_withdrawn || campaign.refunded_amount == campaign.raised_amount,
+            ErrorCode::RefundsOutstanding
+        );
+        require!(
+            !campaign.is_withdrawn
+                || campaign.referral_bps == 0
+                || campaign.referral_claimed_volume == campaign.referred_volume,
+            ErrorCode::ReferralsUnclaimed
+        );
+        require!(campaign.holdback_amount == 0, ErrorCode::HoldbackOutstanding);
+        require!(campaign.open_disputes == 0, ErrorCode::DisputesPending);
+        
+        // Backers get the grace period to close their settled backings
+        let grace_end = campaign.end_time
+            .checked_add(CLOSE_GRACE_PERIOD)
+            .ok_or(ErrorCode::MathOverflow)?;
+        require!(
+            campaign.open_backings == 0 || clock.unix_timestamp > grace_end,
+            ErrorCode::OpenBackingsRemain
+        );
+        
+        let farmer_key = campaign.farmer;
+        let campaign_id_bytes = campaign.campaign_id.to_le_bytes();
+        
+        // Sweep the vault's rent reserve and any rounding dust to the authority
+        let vault_lamports = ctx.accounts.vault.lamports();
+        if vault_lamports > 0 {
+            let bump = ctx.bumps.vault;
+            let seeds = &[
+                b"vault",
+                farmer_key.as_ref(),
+                campaign_id_bytes.as_ref(),
+                &[bump],
+            ];
+            let signer_seeds: &[&[&[u8]]] = &[seeds];
+            
+            system_program::transfer(
+                CpiContext::new_with_signer(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: ctx.accounts.vault.to_account_info(),
+                        to: ctx.accounts.authority.to_account_info(),
+                    },
+                    signer_seeds,
+                ),
+                vault_lamports,
+            )?;
+        }
+        
+        // Token campaigns also close their vault, sweeping rounding dust to the authority
+        let vault_token_info = ctx.accounts.vault_token.to_account_info();
+        if vault_token_info.owner == &token::ID {
+            let vault_token = TokenAccount::try_deserialize(&mut &vault_token_info.try_borrow_data()?[..])?;
+            let token_program = ctx.accounts.token_program
+                .as_ref()
+                .ok_or(ErrorCode::TokenAccountsMissing)?
+                .to_account_info();
+            
+            let bump = ctx.bumps.vault_token;
+            let seeds = &[
+                b"vault_token",
+                farmer_key.as_ref(),
+                campaign_id_bytes.as_ref(),
+                &[bump],
+            ];
+            let signer_seeds: &[&[&[u8]]] = &[seeds];
+            
+            if vault_token.amount > 0 {
+                let authority_token = ctx.accounts.authority_token
+                    .as_ref()
+                    .ok_or(ErrorCode::TokenAccountsMissing)?;
+                require!(authority_token.mint == vault_token.mint, ErrorCode::InvalidMint);
+                require!(authority_token.owner == ctx.accounts.authority.key(), ErrorCode::Unauthorized);
+                
+                token::transfer(
+                    CpiContext::new_with_signer(
+                        token_program.clone(),
+                        Transfer {
+                            from: vault_token_info.clone(),
+                            to: authority_token.to_account_info(),
+                            authority: vault_token_info.clone(),
+                        },
+                        signer_seeds,
+                    ),
+                    vault_token.amount,
+                )?;
+            }
+            
+            token::close_account(CpiContext::new_with_signer(
+                token_program,
+                CloseAccount {
+                    account: vault_token_info.clone(),
+                    destination: ctx.accounts.authority.to_account_info(),
+                    authority: vault_token_info,
+                },
+                signer_seeds,
+            ))?;
+        }
+        
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_product(
+        ctx: Context<CreateProduct>,
+        product_id: u64,
+        price: u64,
+        unit: String,
+        stock: u32,
+        growth_stage: GrowthStage,
+        harvest_date: i64,
+        metadata_uri: String,
+        category: Category,
+        tags: Vec<String>,
+        geohash: String,
+    ) -> Result<()> {
+        require!(price > 0, ErrorCode::InvalidAmount);
+        require!(!unit.is_empty() && unit.len() <= 16, ErrorCode::UnitTooLong);
+        require!(metadata_uri.len() <= 200, ErrorCode::MetadataUriTooLong);
+        index::validate_listing(&tags, &geohash)?;
+        
+        let config = &ctx.accounts.config;
+        require!(config.is_active && !config.is_paused, ErrorCode::PlatformInactive);
+        
+        let clock = Clock::get()?;
+        
+        let product = &mut ctx.accounts.product;
+        product.bump = ctx.bumps.product;
+        product.farmer = ctx.accounts.farmer.key();
+        product.product_id = product_id;
+        product.price = price;
+        product.currency_mint = ctx.accounts.currency_mint.key();
+        product.unit = unit;
+        product.stock = stock;
+        product.growth_stage = growth_stage;
+        product.harvest_date = harvest_date;
+        product.metadata_uri = metadata_uri;
+        product.is_listed = true;
+        product.created_at = clock.unix_timestamp;
+        product.deposit_bps = 0;
+        product.category = category;
+        product.tags = tags;
+        product.geohash = geohash;
+        
+        // List the product under its category and geohash cell
+        let category_index = &mut ctx.accounts.category_index;
+        category_index.bump = ctx.bumps.category_index;
+        category_index.category = category;
+        category_index.geohash_cell = product.geohash[..GEOHASH_INDEX_PRECISION].to_string();
+        ctx.accounts.category_page.bump = ctx.bumps.category_page;
+        index::record_listing(
+            category_index,
+            &mut ctx.accounts.category_page,
+            product.key(),
+            &ctx.accounts.farmer.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+        )?;
+        
+        emit!(ProductListed {
+            product: product.key(),
+            farmer: product.farmer,
+            price,
+            currency_mint: product.currency_mint,
+            stock,
+        });
+        
+        Ok(())
+    }
+
+    pub fn update_product(
+        ctx: Context<UpdateProduct>,
+        price: u64,
+        growth_stage: GrowthStage,
+        harvest_date: i64,
+        metadata_uri: String,
+    ) -> Result<()> {
+        require!(price > 0, ErrorCode::InvalidAmount);
+        require!(metadata_uri.len() <= 200, ErrorCode::MetadataUriTooLong);
+        
+        let config = &ctx.accounts.config;
+        require!(config.is_active && !config.is_paused, ErrorCode::PlatformInactive);
+        
+        let product = &ctx.accounts.product;
+        require!(product.farmer == ctx.accounts.farmer.key(), ErrorCode::Unauthorized);
+        require!(product.is_listed, ErrorCode::ProductNotListed);
+        
+        let product = &mut ctx.accounts.product;
+        product.price = price;
+        product.growth_stage = growth_stage;
+        product.harvest_date = harvest_date;
+        product.metadata_uri = metadata_uri;
+        
+        Ok(())
+    }
+
+    pub fn restock(ctx: Context<UpdateProduct>, quantity: u32) -> Result<()> {
+        require!(quantity > 0, ErrorCode::InvalidQuantity);
+        
+        let config = &ctx.accounts.config;
+        require!(config.is_active && !config.is_paused, ErrorCode::PlatformInactive);
+        
+        let product = &ctx.accounts.product;
+        require!(product.farmer == ctx.accounts.farmer.key(), ErrorCode::Unauthorized);
+        require!(product.is_listed, ErrorCode::ProductNotListed);
+        
+        let product = &mut ctx.accounts.product;
+        product.stock = product.stock
+            .checked_add(quantity)
+            .ok_or(ErrorCode::MathOverflow)?;
+        
+        Ok(())
+    }
+
+    // Delisting stays available while the platform is paused
+    pub fn delist(ctx: Context<UpdateProduct>) -> Result<()> {
+        let product = &ctx.accounts.product;
+        require!(product.farmer == ctx.accounts.farmer.key(), ErrorCode::Unauthorized);
+        require!(product.is_listed, ErrorCode::ProductNotListed);
+        
+        let product = &mut ctx.accounts.product;
+        product.is_listed = false;
+        
+        emit!(ProductDelisted {
+            product: product.key(),
+            farmer: product.farmer,
+        });
+        
+        Ok(())
+    }
+
+    pub fn place_order_sol(
+        ctx: Context<PlaceOrderSol>,
+        order_id: u64,
+        quantity: u32,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        let config = &ctx.accounts.config;
+        let product = &ctx.accounts.product;
+        
+        require!(config.is_active && !config.is_paused, ErrorCode::PlatformInactive);
+        require!(product.is_listed, ErrorCode::ProductNotListed);
+        require!(product.currency_mint == token::spl_token::native_mint::ID, ErrorCode::InvalidMint);
+        require!(quantity > 0, ErrorCode::InvalidQuantity);
+        require!(product.stock >= quantity, ErrorCode::InsufficientStock);
+        
+        let total_amount = product.price
+            .checked_mul(quantity as u64)
+            .ok_or(ErrorCode::MathOverflow)?;
+        
+        // Transfer SOL to order escrow
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.buyer.to_account_info(),
+                    to: ctx.accounts.escrow.to_account_info(),
+                },
+            ),
+            total_amount,
+        )?;
+        
+        let order = &mut ctx.accounts.order;
+        order.bump = ctx.bumps.order;
+        order.buyer = ctx.accounts.buyer.key();
+        order.farmer = product.farmer;
+        order.product = product.key();
+        order.order_id = order_id;
+        order.quantity = quantity;
+        order.unit_price = product.price;
+        order.total_amount = total_amount;
+        order.currency_mint = product.currency_mint;
+        order.status = OrderStatus::Pending;
+        order.created_at = clock.unix_timestamp;
+        order.stage_timestamps = [0; 6];
+        order.evidence_hash = None;
+        order.auto_release_at = 0;
+        order.is_preorder = false;
+        order.paid_amount = total_amount;
+        order.harvest_deadline = 0;
+        order.balance_deadline = 0;
+        order.items = Vec::new();
+        
+        let product = &mut ctx.accounts.product;
+        product.stock = product.stock
+            .checked_sub(quantity)
+            .ok_or(ErrorCode::MathOverflow)?;
+        
+        emit!(OrderPlaced {
+            order: order.key(),
+            buyer: order.buyer,
+            farmer: order.farmer,
+            product: order.product,
+            quantity,
+            total_amount,
+        });
+        
+        Ok(())
+    }
+
+    pub fn place_order_token(
+        ctx: Context<PlaceOrderToken>,
+        order_id: u64,
+        quantity: u32,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        let config = &ctx.accounts.config;
+        let product = &ctx.accounts.product;
+        
+        require!(config.is_active && !config.is_paused, ErrorCode::PlatformInactive);
+        require!(product.is_listed, ErrorCode::ProductNotListed);
+        require!(quantity > 0, ErrorCode::InvalidQuantity);
+        require!(product.stock >= quantity, ErrorCode::InsufficientStock);
+        require!(product.currency_mint == ctx.accounts.currency_mint.key(), ErrorCode::InvalidMint);
+        
+        let total_amount = product.price
+            .checked_mul(quantity as u64)
+            .ok_or(ErrorCode::MathOverflow)?;
+        
+        // Transfer tokens to order escrow
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.buyer_token.to_account_info(),
+                    to: ctx.accounts.escrow_token.to_account_info(),
+                    authority: ctx.accounts.buyer.to_account_info(),
+                },
+            ),
+            total_amount,
+        )?;
+        
+        let order = &mut ctx.accounts.order;
+        order.bump = ctx.bumps.order;
+        order.buyer = ctx.accounts.buyer.key();
+        order.farmer = product.farmer;
+        order.product = product.key();
+        order.order_id = order_id;
+        order.quantity = quantity;
+        order.unit_price = product.price;
+        order.total_amount = total_amount;
+        order.currency_mint = product.currency_mint;
+        order.status = OrderStatus::Pending;
+        order.created_at = clock.unix_timestamp;
+        order.stage_timestamps = [0; 6];
+        order.evidence_hash = None;
+        order.auto_release_at = 0;
+        order.is_preorder = false;
+        order.paid_amount = total_amount;
+        order.harvest_deadline = 0;
+        order.balance_deadline = 0;
+        order.items = Vec::new();
+        
+        let product = &mut ctx.accounts.product;
+        product.stock = product.stock
+            .checked_sub(quantity)
+            .ok_or(ErrorCode::MathOverflow)?;
+        
+        emit!(OrderPlaced {
+            order: order.key(),
+            buyer: order.buyer,
+            farmer: order.farmer,
+            product: order.product,
+            quantity,
+            total_amount,
+        });
+        
+        Ok(())
+    }
+
+    pub fn accept_order(ctx: Context<UpdateOrder>) -> Result<()> {
+        let clock = Clock::get()?;
+        let order = &ctx.accounts.order;
+        
+        require!(order.farmer == ctx.accounts.farmer.key(), ErrorCode::Unauthorized);
+        require!(order.status == OrderStatus::Pending, ErrorCode::InvalidOrderStatus);
+        
+        let order = &mut ctx.accounts.order;
+        order.status = OrderStatus::Seed;
+        order.stage_timestamps[OrderStatus::Seed.stage_index()] = clock.unix_timestamp;
+        
+        emit!(OrderAccepted {
+            order: order.key(),
+            farmer: order.farmer,
+        });
+        
+        Ok(())
+    }
+
+    pub fn advance_order(
+        ctx: Context<UpdateOrder>,
+        status: OrderStatus,
+        evidence_hash: Option<[u8; 32]>,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        let order = &ctx.accounts.order;
+        
+        require!(order.farmer == ctx.accounts.farmer.key(), ErrorCode::Unauthorized);
+        require!(order.status.is_in_progress(), ErrorCode::InvalidOrderStatus);
+        // Stages only move forward, but the farmer may skip ones that don't apply
+        require!(
+            status.is_in_progress() && (status as u8) > (order.status as u8),
+            ErrorCode::InvalidOrderTransition
+        );
+        
+        let order = &mut ctx.accounts.order;
+        order.status = status;
+        order.stage_timestamps[status.stage_index()] = clock.unix_timestamp;
+        if evidence_hash.is_some() {
+            order.evidence_hash = evidence_hash;
+        }
+        
+        // The auto-release timeout starts once the farmer reports delivery
+        if status == OrderStatus::Delivered {
+            order.auto_release_at = clock.unix_timestamp
+                .checked_add(ORDER_AUTO_RELEASE_PERIOD)
+                .ok_or(ErrorCode::MathOverflow)?;
+        }
+        
+        emit!(OrderAdvanced {
+            order: order.key(),
+            farmer: order.farmer,
+            status,
+            evidence_hash,
+            timestamp: clock.unix_timestamp,
+        });
+        
+        Ok(())
+    }
+
+    pub fn cancel_order_sol<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CancelOrderSol<'info>>,
+    ) -> Result<()> {
+        let order = &ctx.accounts.order;
+        
+        require!(order.buyer == ctx.accounts.buyer.key(), ErrorCode::Unauthorized);
+        require!(order.status == OrderStatus::Pending, ErrorCode::InvalidOrderStatus);
+        
+        let order_key = order.key();
+        let bump = ctx.bumps.escrow;
+        
+        let seeds = &[
+            b"order_escrow",
+            order_key.as_ref(),
+            &[bump],
+        ];
+        let signer_seeds: &[&[&[u8]]] = &[seeds];
+        
+        system_program::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.escrow.to_account_info(),
+                    to: ctx.accounts.buyer.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            order.total_amount,
+        )?;
+        
+        // Cart orders restock each of their products from remaining accounts
+        if order.items.is_empty() {
+            let product = ctx.accounts.product.as_mut().ok_or(ErrorCode::InvalidCart)?;
+            product.stock = product.stock
+                .checked_add(order.quantity)
+                .ok_or(ErrorCode::MathOverflow)?;
+        } else {
+            cart::restock_items(&order.items, ctx.remaining_accounts)?;
+        }
+        
+        let order = &mut ctx.accounts.order;
+        order.status = OrderStatus::Cancelled;
+        
+        emit!(OrderCancelled {
+            order: order_key,
+            buyer: order.buyer,
+        });
+        
+        Ok(())
+    }
+
+    pub fn cancel_order_token<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CancelOrderToken<'info>>,
+    ) -> Result<()> {
+        let order = &ctx.accounts.order;
+        
+        require!(order.buyer == ctx.accounts.buyer.key(), ErrorCode::Unauthorized);
+        require!(order.status == OrderStatus::Pending, ErrorCode::InvalidOrderStatus);
+        
+        let order_key = order.key();
+        let bump = ctx.bumps.escrow_token;
+        
+        let seeds = &[
+            b"order_escrow_token",
+            order_key.as_ref(),
+            &[bump],
+        ];
+        let signer_seeds: &[&[&[u8]]] = &[seeds];
+        
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.escrow_token.to_account_info(),
+                    to: ctx.accounts.buyer_token.to_account_info(),
+                    authority: ctx.accounts.escrow_token.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            order.total_amount,
+        )?;
+        
+        // Return the escrow account rent to the buyer
+        token::close_account(CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            CloseAccount {
+                account: ctx.accounts.escrow_token.to_account_info(),
+                destination: ctx.accounts.buyer.to_account_info(),
+                authority: ctx.accounts.escrow_token.to_account_info(),
+            },
+            signer_seeds,
+        ))?;
+        
+        // Cart orders restock each of their products from remaining accounts
+        if order.items.is_empty() {
+            let product = ctx.accounts.product.as_mut().ok_or(ErrorCode::InvalidCart)?;
+            product.stock = product.stock
+                .checked_add(order.quantity)
+                .ok_or(ErrorCode::MathOverflow)?;
+        } else {
+            cart::restock_items(&order.items, ctx.remaining_accounts)?;
+        }
+        
+        let order = &mut ctx.accounts.order;
+        order.status = OrderStatus::Cancelled;
+        
+        emit!(OrderCancelled {
+            order: order_key,
+            buyer: order.buyer,
+        });
+        
+        Ok(())
+    }
+
+    pub fn confirm_delivery_sol(ctx: Context<ConfirmDeliverySol>) -> Result<()> {
+        let clock = Clock::get()?;
+        let order = &ctx.accounts.order;
+        let config = &ctx.accounts.config;
+        
+        require!(order.status.is_in_progress(), ErrorCode::InvalidOrderStatus);
+        // Anyone can release the escrow once the buyer has let the timeout pass after delivery
+        require!(
+            order.buyer == ctx.accounts.caller.key()
+                || (order.status == OrderStatus::Delivered && clock.unix_timestamp >= order.auto_release_at),
+            ErrorCode::Unauthorized
+        );
+        
+        let fee = order.total_amount
+            .checked_mul(config.fee_bps as u64)
+            .ok_or(ErrorCode::MathOverflow)?
+            .checked_div(10000)
+            .ok_or(ErrorCode::MathOverflow)?;
+        
+        let farmer_amount = order.total_amount
+            .checked_sub(fee)
+            .ok_or(ErrorCode::MathOverflow)?;
+        
+        let order_key = order.key();
+        let bump = ctx.bumps.escrow;
+        
+        let seeds = &[
+            b"order_escrow",
+            order_key.as_ref(),
+            &[bump],
+        ];
+        let signer_seeds: &[&[&[u8]]] = &[seeds];
+        
+        // Transfer fee to treasury
+        if fee > 0 {
+            system_program::transfer(
+                CpiContext::new_with_signer(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: ctx.accounts.escrow.to_account_info(),
+                        to: ctx.accounts.treasury.to_account_info(),
+                    },
+                    signer_seeds,
+                ),
+                fee,
+            )?;
+        }
+        
+        // Transfer remaining to farmer
+        system_program::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.escrow.to_account_info(),
+                    to: ctx.accounts.farmer.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            farmer_amount,
+        )?;
+        
+        let order = &mut ctx.accounts.order;
+        order.status = OrderStatus::Completed;
+        
+        let stats = &mut ctx.accounts.customer_stats;
+        stats.bump = ctx.bumps.customer_stats;
+        stats.wallet = order.buyer;
+        stats.total_spent = stats.total_spent
+            .checked_add(order.total_amount)
+            .ok_or(ErrorCode::MathOverflow)?;
+        stats.orders_count = stats.orders_count
+            .checked_add(1)
+            .ok_or(ErrorCode::MathOverflow)?;
+        
+        emit!(OrderCompleted {
+            order: order_key,
+            farmer: order.farmer,
+            farmer_amount,
+            fee,
+        });
+        
+        Ok(())
+    }
+
+    pub fn confirm_delivery_token(ctx: Context<ConfirmDeliveryToken>) -> Result<()> {
+        let clock = Clock::get()?;
+        let order = &ctx.accounts.order;
+        let config = &ctx.accounts.config;
+        
+        require!(order.status.is_in_progress(), ErrorCode::InvalidOrderStatus);
+        // Anyone can release the escrow once the buyer has let the timeout pass after delivery
+        require!(
+            order.buyer == ctx.accounts.caller.key()
+                || (order.status == OrderStatus::Delivered && clock.unix_timestamp >= order.auto_release_at),
+            ErrorCode::Unauthorized
+        );
+        
+        let fee = order.total_amount
+            .checked_mul(config.fee_bps as u64)
+            .ok_or(ErrorCode::MathOverflow)?
+            .checked_div(10000)
+            .ok_or(ErrorCode::MathOverflow)?;
+        
+        let farmer_amount = order.total_amount
+            .checked_sub(fee)
+            .ok_or(ErrorCode::MathOverflow)?;
+        
+        let order_key = order.key();
+        let bump = ctx.bumps.escrow_token;
+        
+        let seeds = &[
+            b"order_escrow_token",
+            order_key.as_ref(),
+            &[bump],
+        ];
+        let signer_seeds: &[&[&[u8]]] = &[seeds];
+        
+        // Transfer fee to treasury
+        if fee > 0 {
+            token::transfer(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    Transfer {
+                        from: ctx.accounts.escrow_token.to_account_info(),
+                        to: ctx.accounts.treasury_token.to_account_info(),
+                        authority: ctx.accounts.escrow_token.to_account_info(),
+                    },
+                    signer_seeds,
+                ),
+                fee,
+            )?;
+        }
+        
+        // Transfer remaining to farmer
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.escrow_token.to_account_info(),
+                    to: ctx.accounts.farmer_token.to_account_info(),
+                    authority: ctx.accounts.escrow_token.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            farmer_amount,
+        )?;
+        
+        // Return the escrow account rent to the buyer
+        token::close_account(CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            CloseAccount {
+                account: ctx.accounts.escrow_token.to_account_info(),
+                destination: ctx.accounts.buyer.to_account_info(),
+                authority: ctx.accounts.escrow_token.to_account_info(),
+            },
+            signer_seeds,
+        ))?;
+        
+        let order = &mut ctx.accounts.order;
+        order.status = OrderStatus::Completed;
+        
+        // Spend is tracked in lamports only, so token orders just count
+        let stats = &mut ctx.accounts.customer_stats;
+        stats.bump = ctx.bumps.customer_stats;
+        stats.wallet = order.buyer;
+        stats.orders_count = stats.orders_count
+            .checked_add(1)
+            .ok_or(ErrorCode::MathOverflow)?;
+        
+        emit!(OrderCompleted {
+            order: order_key,
+            farmer: order.farmer,
+            farmer_amount,
+            fee,
+        });
+        
+        Ok(())
+    }
+
+    pub fn open_order_dispute(ctx: Context<OpenOrderDispute>, reason_hash: [u8; 32]) -> Result<()> {
+        let clock = Clock::get()?;
+        let order = &ctx.accounts.order;
+        
+        require!(order.buyer == ctx.accounts.buyer.key(), ErrorCode::Unauthorized);
+        require!(order.status.is_in_progress(), ErrorCode::InvalidOrderStatus);
+        
+        let dispute = &mut ctx.accounts.dispute;
+        dispute.bump = ctx.bumps.dispute;
+        dispute.subject = order.key();
+        dispute.kind = DisputeKind::Order;
+        dispute.claimant = order.buyer;
+        dispute.respondent = order.farmer;
+        dispute.amount = order.total_amount;
+        dispute.reason_hash = reason_hash;
+        dispute.status = DisputeStatus::Open;
+        dispute.claimant_bps = 0;
+        dispute.opened_at = clock.unix_timestamp;
+        dispute.deadline = clock.unix_timestamp
+            .checked_add(DISPUTE_RESOLUTION_PERIOD)
+            .ok_or(ErrorCode::MathOverflow)?;
+        dispute.resolved_at = 0;
+        
+        // Freezes the escrow until the dispute is resolved
+        let order = &mut ctx.accounts.order;
+        order.status = OrderStatus::Disputed;
+        
+        emit!(DisputeOpened {
+            dispute: dispute.key(),
+            subject: dispute.subject,
+            kind: DisputeKind::Order,
+            claimant: dispute.claimant,
+            deadline: dispute.deadline,
+        });
+        
+        Ok(())
+    }
+
+    pub fn open_backing_dispute(ctx: Context<OpenBackingDispute>, reason_hash: [u8; 32]) -> Result<()> {
+        let clock = Clock::get()?;
+        let campaign = &ctx.accounts.campaign;
+        let backing = &ctx.accounts.backing;
+        
+        require!(backing.backer == ctx.accounts.backer.key(), ErrorCode::Unauthorized);
+        require!(campaign.is_finalized, ErrorCode::CampaignNotFinalized);
+        require!(campaign.raised_amount >= campaign.goal_amount, ErrorCode::GoalNotReached);
+        require!(!backing.is_refunded, ErrorCode::AlreadyRefunded);
+        require!(!backing.is_fulfilled, ErrorCode::RewardFulfilled);
+        
+        // After withdrawal an undelivered reward can still be disputed, against the holdback
+        let amount = if campaign.is_withdrawn {
+            require!(campaign.holdback_amount > 0, ErrorCode::HoldbackReleased);
+            backing.amount
+                .checked_mul(REWARD_HOLDBACK_BPS as u64)
+                .ok_or(ErrorCode::MathOverflow)?
+                .checked_div(10000)
+                .ok_or(ErrorCode::MathOverflow)?
+                .min(campaign.holdback_amount)
+        } else {
+            backing.amount
+        };
+        
+        let dispute = &mut ctx.accounts.dispute;
+        dispute.bump = ctx.bumps.dispute;
+        dispute.subject = backing.key();
+        dispute.kind = DisputeKind::Backing;
+        dispute.claimant = backing.backer;
+        dispute.respondent = campaign.farmer;
+        dispute.amount = amount;
+        dispute.reason_hash = reason_hash;
+        dispute.status = DisputeStatus::Open;
+        dispute.claimant_bps = 0;
+        dispute.opened_at = clock.unix_timestamp;
+        dispute.deadline = clock.unix_timestamp
+            .checked_add(DISPUTE_RESOLUTION_PERIOD)
+            .ok_or(ErrorCode::MathOverflow)?;
+        dispute.resolved_at = 0;
+        
+        // Withdrawal and the holdback release stay blocked while any backing dispute is open
+        let campaign = &mut ctx.accounts.campaign;
+        campaign.open_disputes = campaign.open_disputes
+            .checked_add(1)
+            .ok_or(ErrorCode::MathOverflow)?;
+        if campaign.is_withdrawn {
+            campaign.holdback_amount = campaign.holdback_amount
+                .checked_sub(amount)
+                .ok_or(ErrorCode::MathOverflow)?;
+        }
+        
+        emit!(DisputeOpened {
+            dispute: dispute.key(),
+            subject: dispute.subject,
+            kind: DisputeKind::Backing,
+            claimant: dispute.claimant,
+            deadline: dispute.deadline,
+        });
+        
+        Ok(())
+    }
+
+    pub fn resolve_order_dispute_sol(ctx: Context<ResolveOrderDisputeSol>, claimant_bps: u16) -> Result<()> {
+        let clock = Clock::get()?;
+        let dispute = &ctx.accounts.dispute;
+        
+        require!(dispute.status == DisputeStatus::Open, ErrorCode::DisputeClosed);
+        
+        // Past the deadline the dispute defaults to the claimant and anyone can settle it
+        let defaulted = clock.unix_timestamp >= dispute.deadline;
+        let claimant_bps = if defaulted {
+            10000
+        } else {
+            require_role(&ctx.accounts.config, &ctx.accounts.role, ctx.accounts.caller.key(), RoleKind::Arbiter)?;
+            require!(claimant_bps <= 10000, ErrorCode::InvalidBps);
+            claimant_bps
+        };
+        
+        let claimant_amount = dispute.amount
+            .checked_mul(claimant_bps as u64)
+            .ok_or(ErrorCode::MathOverflow)?
+            .checked_div(10000)
+            .ok_or(ErrorCode::MathOverflow)?;
+        
+        let respondent_amount = dispute.amount
+            .checked_sub(claimant_amount)
+            .ok_or(ErrorCode::MathOverflow)?;
+        
+        let fee = respondent_amount
+            .checked_mul(ctx.accounts.config.fee_bps as u64)
+            .ok_or(ErrorCode::MathOverflow)?
+            .checked_div(10000)
+            .ok_or(ErrorCode::MathOverflow)?;
+        
+        let farmer_amount = respondent_amount
+            .checked_sub(fee)
+            .ok_or(ErrorCode::MathOverflow)?;
+        
+        let order_key = ctx.accounts.order.key();
+        let bump = ctx.bumps.escrow;
+        
+        let seeds = &[
+            b"order_escrow",
+            order_key.as_ref(),
+            &[bump],
+        ];
+        let signer_seeds: &[&[&[u8]]] = &[seeds];
+        
+        // Transfer claimant share to buyer
+        if claimant_amount > 0 {
+            system_program::transfer(
+                CpiContext::new_with_signer(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: ctx.accounts.escrow.to_account_info(),
+                        to: ctx.accounts.buyer.to_account_info(),
+                    },
+                    signer_seeds,
+                ),
+                claimant_amount,
+            )?;
+        }
+        
+        // Transfer fee to treasury
+        if fee > 0 {
+            system_program::transfer(
+                CpiContext::new_with_signer(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: ctx.accounts.escrow.to_account_info(),
+                        to: ctx.accounts.treasury.to_account_info(),
+                    },
+                    signer_seeds,
+                ),
+                fee,
+            )?;
+        }
+        
+        // Transfer remaining to farmer
+        if farmer_amount > 0 {
+            system_program::transfer(
+                CpiContext::new_with_signer(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: ctx.accounts.escrow.to_account_info(),
+                        to: ctx.accounts.farmer.to_account_info(),
+                    },
+                    signer_seeds,
+                ),
+                farmer_amount,
+            )?;
+        }
+        
+        let order = &mut ctx.accounts.order;
+        order.status = OrderStatus::Resolved;
+        
+        let dispute = &mut ctx.accounts.dispute;
+        dispute.status = DisputeStatus::Resolved;
+        dispute.claimant_bps = claimant_bps;
+        dispute.resolved_at = clock.unix_timestamp;
+        
+        emit!(DisputeResolved {
+            dispute: dispute.key(),
+            subject: dispute.subject,
+            claimant_bps,
+            claimant_amount,
+            respondent_amount,
+            defaulted,
+        });
+        
+        Ok(())
+    }
+
+    pub fn resolve_order_dispute_token(ctx: Context<ResolveOrderDisputeToken>, claimant_bps: u16) -> Result<()> {
+        let clock = Clock::get()?;
+        let dispute = &ctx.accounts.dispute;
+        
+        require!(dispute.status == DisputeStatus::Open, ErrorCode::DisputeClosed);
+        
+        // Past the deadline the dispute defaults to the claimant and anyone can settle it
+        let defaulted = clock.unix_timestamp >= dispute.deadline;
+        let claimant_bps = if defaulted {
+            10000
+        } else {
+            require_role(&ctx.accounts.config, &ctx.accounts.role, ctx.accounts.caller.key(), RoleKind::Arbiter)?;
+            require!(claimant_bps <= 10000, ErrorCode::InvalidBps);
+            claimant_bps
+        };
+        
+        let claimant_amount = dispute.amount
+            .checked_mul(claimant_bps as u64)
+            .ok_or(ErrorCode::MathOverflow)?
+            .checked_div(10000)
+            .ok_or(ErrorCode::MathOverflow)?;
+        
+        let respondent_amount = dispute.amount
+            .checked_sub(claimant_amount)
+            .ok_or(ErrorCode::MathOverflow)?;
+        
+        let fee = respondent_amount
+            .checked_mul(ctx.accounts.config.fee_bps as u64)
+            .ok_or(ErrorCode::MathOverflow)?
+            .checked_div(10000)
+            .ok_or(ErrorCode::MathOverflow)?;
+        
+        let farmer_amount = respondent_amount
+            .checked_sub(fee)
+            .ok_or(ErrorCode::MathOverflow)?;
+        
+        let order_key = ctx.accounts.order.key();
+        let bump = ctx.bumps.escrow_token;
+        
+        let seeds = &[
+            b"order_escrow_token",
+            order_key.as_ref(),
+            &[bump],
+        ];
+        let signer_seeds: &[&[&[u8]]] = &[seeds];
+        
+        // Transfer claimant share to buyer
+        if claimant_amount > 0 {
+            token::transfer(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    Transfer {
+                        from: ctx.accounts.escrow_token.to_account_info(),
+                        to: ctx.accounts.buyer_token.to_account_info(),
+                        authority: ctx.accounts.escrow_token.to_account_info(),
+                    },
+                    signer_seeds,
+                ),
+                claimant_amount,
+            )?;
+        }
+        
+        // Transfer fee to treasury
+        if fee > 0 {
+            token::transfer(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    Transfer {
+                        from: ctx.accounts.escrow_token.to_account_info(),
+                        to: ctx.accounts.treasury_token.to_account_info(),
+                        authority: ctx.accounts.escrow_token.to_account_info(),
+                    },
+                    signer_seeds,
+                ),
+                fee,
+            )?;
+        }
+        
+        // Transfer remaining to farmer
+        if farmer_amount > 0 {
+            token::transfer(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    Transfer {
+                        from: ctx.accounts.escrow_token.to_account_info(),
+                        to: ctx.accounts.farmer_token.to_account_info(),
+                        authority: ctx.accounts.escrow_token.to_account_info(),
+                    },
+                    signer_seeds,
+                ),
+                farmer_amount,
+            )?;
+        }
+        
+        // Return the escrow account rent to the buyer
+        token::close_account(CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            CloseAccount {
+                account: ctx.accounts.escrow_token.to_account_info(),
+                destination: ctx.accounts.buyer.to_account_info(),
+                authority: ctx.accounts.escrow_token.to_account_info(),
+            },
+            signer_seeds,
+        ))?;
+        
+        let order = &mut ctx.accounts.order;
+        order.status = OrderStatus::Resolved;
+        
+        let dispute = &mut ctx.accounts.dispute;
+        dispute.status = DisputeStatus::Resolved;
+        dispute.claimant_bps = claimant_bps;
+        dispute.resolved_at = clock.unix_timestamp;
+        
+        emit!(DisputeResolved {
+            dispute: dispute.key(),
+            subject: dispute.subject,
+            claimant_bps,
+            claimant_amount,
+            respondent_amount,
+            defaulted,
+        });
+        
+        Ok(())
+    }
+
+    pub fn resolve_backing_dispute_sol(ctx: Context<ResolveBackingDisputeSol>, claimant_bps: u16) -> Result<()> {
+        let clock = Clock::get()?;
+        let dispute = &ctx.accounts.dispute;
+        
+        require!(dispute.status == DisputeStatus::Open, ErrorCode::DisputeClosed);
+        
+        // Past the deadline the dispute defaults to the claimant and anyone can settle it
+        let defaulted = clock.unix_timestamp >= dispute.deadline;
+        let claimant_bps = if defaulted {
+            10000
+        } else {
+            require_role(&ctx.accounts.config, &ctx.accounts.role, ctx.accounts.caller.key(), RoleKind::Arbiter)?;
+            require!(claimant_bps <= 10000, ErrorCode::InvalidBps);
+            claimant_bps
+        };
+        
+        let claimant_amount = dispute.amount
+            .checked_mul(claimant_bps as u64)
+            .ok_or(ErrorCode::MathOverflow)?
+            .checked_div(10000)
+            .ok_or(ErrorCode::MathOverflow)?;
+        
+        let respondent_amount = dispute.amount
+            .checked_sub(claimant_amount)
+            .ok_or(ErrorCode::MathOverflow)?;
+        
+        let campaign = &ctx.accounts.campaign;
+        let farmer_key = campaign.farmer;
+        let campaign_id_bytes = campaign.campaign_id.to_le_bytes();
+        let bump = ctx.bumps.vault;
+        
+        let seeds = &[
+            b"vault",
+            farmer_key.as_ref(),
+            campaign_id_bytes.as_ref(),
+            &[bump],
+        ];
+        let signer_seeds: &[&[&[u8]]] = &[seeds];
+        
+        // Refund claimant share to backer; the rest stays in the vault for the farmer
+        if claimant_amount > 0 {
+            system_program::transfer(
+                CpiContext::new_with_signer(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: ctx.accounts.vault.to_account_info(),
+                        to: ctx.accounts.backer.to_account_info(),
+                    },
+                    signer_seeds,
+                ),
+                claimant_amount,
+            )?;
+            
+            let stats = &mut ctx.accounts.customer_stats;
+            stats.total_backed = stats.total_backed
+                .checked_sub(claimant_amount)
+                .ok_or(ErrorCode::MathOverflow)?;
+        }
+        
+        // The refunded part can't be claimed again
+        let backing = &mut ctx.accounts.backing;
+        backing.refunded_amount = backing.refunded_amount
+            .checked_add(claimant_amount)
+            .ok_or(ErrorCode::MathOverflow)?;
+        backing.is_refunded = backing.refunded_amount >= backing.amount;
+        
+        // Disputes opened after withdrawal hand the farmer's part back to the holdback
+        let campaign = &mut ctx.accounts.campaign;
+        campaign.open_disputes = campaign.open_disputes
+            .checked_sub(1)
+            .ok_or(ErrorCode::MathOverflow)?;
+        campaign.refunded_amount = campaign.refunded_amount
+            .checked_add(claimant_amount)
+            .ok_or(ErrorCode::MathOverflow)?;
+        if campaign.is_withdrawn {
+            campaign.holdback_amount = campaign.holdback_amount
+                .checked_add(respondent_amount)
+                .ok_or(ErrorCode::MathOverflow)?;
+        }
+        
+        let dispute = &mut ctx.accounts.dispute;
+        dispute.status = DisputeStatus::Resolved;
+        dispute.claimant_bps = claimant_bps;
+        dispute.resolved_at = clock.unix_timestamp;
+        
+        emit!(DisputeResolved {
+            dispute: dispute.key(),
+            subject: dispute.subject,
+            claimant_bps,
+            claimant_amount,
+            respondent_amount,
+            defaulted,
+        });
+        
+        Ok(())
+    }
+
+    pub fn resolve_backing_dispute_token(ctx: Context<ResolveBackingDisputeToken>, claimant_bps: u16) -> Result<()> {
+        let clock = Clock::get()?;
+        let dispute = &ctx.accounts.dispute;
+        
+        require!(dispute.status == DisputeStatus::Open, ErrorCode::DisputeClosed);
+        
+        // Past the deadline the dispute defaults to the claimant and anyone can settle it
+        let defaulted = clock.unix_timestamp >= dispute.deadline;
+        let claimant_bps = if defaulted {
+            10000
+        } else {
+            require_role(&ctx.accounts.config, &ctx.accounts.role, ctx.accounts.caller.key(), RoleKind::Arbiter)?;
+            require!(claimant_bps <= 10000, ErrorCode::InvalidBps);
+            claimant_bps
+        };
+        
+        let claimant_amount = dispute.amount
+            .checked_mul(claimant_bps as u64)
+            .ok_or(ErrorCode::MathOverflow)?
+            .checked_div(10000)
+            .ok_or(ErrorCode::MathOverflow)?;
+        
+        let respondent_amount = dispute.amount
+            .checked_sub(claimant_amount)
+            .ok_or(ErrorCode::MathOverflow)?;
+        
+        let campaign = &ctx.accounts.campaign;
+        let farmer_key = campaign.farmer;
+        let campaign_id_bytes = campaign.campaign_id.to_le_bytes();
+        let bump = ctx.bumps.vault_token;
+        
+        let seeds = &[
+            b"vault_token",
+            farmer_key.as_ref(),
+            campaign_id_bytes.as_ref(),
+            &[bump],
+        ];
+        let signer_seeds: &[&[&[u8]]] = &[seeds];
+        
+        // Refund claimant share to backer; the rest stays in the vault for the farmer
+        if claimant_amount > 0 {
+            token::transfer(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    Transfer {
+                        from: ctx.accounts.vault_token.to_account_info(),
+                        to: ctx.accounts.backer_token.to_account_info(),
+                        authority: ctx.accounts.vault_token.to_account_info(),
+                    },
+                    signer_seeds,
+                ),
+                claimant_amount,
+            )?;
+        }
+        
+        // The refunded part can't be claimed again
+        let backing = &mut ctx.accounts.backing;
+        backing.refunded_amount = backing.refunded_amount
+            .checked_add(claimant_amount)
+            .ok_or(ErrorCode::MathOverflow)?;
+        backing.is_refunded = backing.refunded_amount >= backing.amount;
+        
+        // Disputes opened after withdrawal hand the farmer's part back to the holdback
+        let campaign = &mut ctx.accounts.campaign;
+        campaign.open_disputes = campaign.open_disputes
+            .checked_sub(1)
+            .ok_or(ErrorCode::MathOverflow)?;
+        campaign.refunded_amount = campaign.refunded_amount
+            .checked_add(claimant_amount)
+            .ok_or(ErrorCode::MathOverflow)?;
+        if campaign.is_withdrawn {
+            campaign.holdback_amount = campaign.holdback_amount
+                .checked_add(respondent_amount)
+                .ok_or(ErrorCode::MathOverflow)?;
+        }
+        
+        let dispute = &mut ctx.accounts.dispute;
+        dispute.status = DisputeStatus::Resolved;
+        dispute.claimant_bps = claimant_bps;
+        dispute.resolved_at = clock.unix_timestamp;
+        
+        emit!(DisputeResolved {
+            dispute: dispute.key(),
+            subject: dispute.subject,
+            claimant_bps,
+            claimant_amount,
+            respondent_amount,
+            defaulted,
+        });
+        
+        Ok(())
+    }
+
+    pub fn set_preorder_deposit(ctx: Context<UpdateProduct>, deposit_bps: u16) -> Result<()> {
+        require!(deposit_bps <= 10000, ErrorCode::InvalidBps);
+        
+        let config = &ctx.accounts.config;
+        require!(config.is_active && !config.is_paused, ErrorCode::PlatformInactive);
+        
+        let product = &ctx.accounts.product;
+        require!(product.farmer == ctx.accounts.farmer.key(), ErrorCode::Unauthorized);
+        require!(product.is_listed, ErrorCode::ProductNotListed);
+        
+        let product = &mut ctx.accounts.product;
+        product.deposit_bps = deposit_bps;
+        
+        Ok(())
+    }
+
+    // Pre-orders reserve future harvest and don't draw down current stock
+    pub fn place_preorder_sol(
+        ctx: Context<PlaceOrderSol>,
+        order_id: u64,
+        quantity: u32,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        let config = &ctx.accounts.config;
+        let product = &ctx.accounts.product;
+        
+        require!(config.is_active && !config.is_paused, ErrorCode::PlatformInactive);
+        require!(product.is_listed, ErrorCode::ProductNotListed);
+        require!(product.currency_mint == token::spl_token::native_mint::ID, ErrorCode::InvalidMint);
+        require!(product.deposit_bps > 0, ErrorCode::PreordersDisabled);
+        require!(product.harvest_date > clock.unix_timestamp, ErrorCode::HarvestDatePassed);
+        require!(quantity > 0, ErrorCode::InvalidQuantity);
+        
+        let total_amount = product.price
+            .checked_mul(quantity as u64)
+            .ok_or(ErrorCode::MathOverflow)?;
+        
+        let deposit_amount = total_amount
+            .checked_mul(product.deposit_bps as u64)
+            .ok_or(ErrorCode::MathOverflow)?
+            .checked_div(10000)
+            .ok_or(ErrorCode::MathOverflow)?;
+        
+        let harvest_deadline = product.harvest_date
+            .checked_add(PREORDER_GRACE_PERIOD)
+            .ok_or(ErrorCode::MathOverflow)?;
+        
+        // Transfer deposit to order escrow
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.buyer.to_account_info(),
+                    to: ctx.accounts.escrow.to_account_info(),
+                },
+            ),
+            deposit_amount,
+        )?;
+        
+        let order = &mut ctx.accounts.order;
+        order.bump = ctx.bumps.order;
+        order.buyer = ctx.accounts.buyer.key();
+        order.farmer = product.farmer;
+        order.product = product.key();
+        order.order_id = order_id;
+        order.quantity = quantity;
+        order.unit_price = product.price;
+        order.total_amount = total_amount;
+        order.currency_mint = product.currency_mint;
+        order.status = OrderStatus::PreOrdered;
+        order.created_at = clock.unix_timestamp;
+        order.stage_timestamps = [0; 6];
+        order.evidence_hash = None;
+        order.auto_release_at = 0;
+        order.is_preorder = true;
+        order.paid_amount = deposit_amount;
+        order.harvest_deadline = harvest_deadline;
+        order.balance_deadline = 0;
+        order.items = Vec::new();
+        
+        emit!(PreorderPlaced {
+            order: order.key(),
+            buyer: order.buyer,
+            product: order.product,
+            quantity,
+            deposit_amount,
+            harvest_deadline,
+        });
+        
+        Ok(())
+    }
+
+    // Pre-orders reserve future harvest and don't draw down current stock
+    pub fn place_preorder_token(
+        ctx: Context<PlaceOrderToken>,
+        order_id: u64,
+        quantity: u32,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        let config = &ctx.accounts.config;
+        let product = &ctx.accounts.product;
+        
+        require!(config.is_active && !config.is_paused, ErrorCode::PlatformInactive);
+        require!(product.is_listed, ErrorCode::ProductNotListed);
+        require!(product.deposit_bps > 0, ErrorCode::PreordersDisabled);
+        require!(product.harvest_date > clock.unix_timestamp, ErrorCode::HarvestDatePassed);
+        require!(quantity > 0, ErrorCode::InvalidQuantity);
+        require!(product.currency_mint == ctx.accounts.currency_mint.key(), ErrorCode::InvalidMint);
+        
+        let total_amount = product.price
+            .checked_mul(quantity as u64)
+            .ok_or(ErrorCode::MathOverflow)?;
+        
+        let deposit_amount = total_amount
+            .checked_mul(product.deposit_bps as u64)
+            .ok_or(ErrorCode::MathOverflow)?
+            .checked_div(10000)
+            .ok_or(ErrorCode::MathOverflow)?;
+        
+        let harvest_deadline = product.harvest_date
+            .checked_add(PREORDER_GRACE_PERIOD)
+            .ok_or(ErrorCode::MathOverflow)?;
+        
+        // Transfer deposit to order escrow
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.buyer_token.to_account_info(),
+                    to: ctx.accounts.escrow_token.to_account_info(),
+                    authority: ctx.accounts.buyer.to_account_info(),
+                },
+            ),
+            deposit_amount,
+        )?;
+        
+        let order = &mut ctx.accounts.order;
+        order.bump = ctx.bumps.order;
+        order.buyer = ctx.accounts.buyer.key();
+        order.farmer = product.farmer;
+        order.product = product.key();
+        order.order_id = order_id;
+        order.quantity = quantity;
+        order.unit_price = product.price;
+        order.total_amount = total_amount;
+        order.currency_mint = product.currency_mint;
+        order.status = OrderStatus::PreOrdered;
+        order.created_at = clock.unix_timestamp;
+        order.stage_timestamps = [0; 6];
+        order.evidence_hash = None;
+        order.auto_release_at = 0;
+        order.is_preorder = true;
+        order.paid_amount = deposit_amount;
+        order.harvest_deadline = harvest_deadline;
+        order.balance_deadline = 0;
+        order.items = Vec::new();
+        
+        emit!(PreorderPlaced {
+            order: order.key(),
+            buyer: order.buyer,
+            product: order.product,
+            quantity,
+            deposit_amount,
+            harvest_deadline,
+        });
+        
+        Ok(())
+    }
+
+    pub fn open_balance_payment(ctx: Context<UpdateOrder>) -> Result<()> {
+        let clock = Clock::get()?;
+        let order = &ctx.accounts.order;
+        
+        require!(order.farmer == ctx.accounts.farmer.key(), ErrorCode::Unauthorized);
+        require!(order.status == OrderStatus::PreOrdered, ErrorCode::InvalidOrderStatus);
+        require!(clock.unix_timestamp <= order.harvest_deadline, ErrorCode::HarvestDatePassed);
+        
+        let balance_deadline = clock.unix_timestamp
+            .checked_add(BALANCE_PAYMENT_PERIOD)
+            .ok_or(ErrorCode::MathOverflow)?;
+        
+        let order = &mut ctx.accounts.order;
+        order.status = OrderStatus::BalanceDue;
+        order.balance_deadline = balance_deadline;
+        
+        emit!(BalancePaymentOpened {
+            order: order.key(),
+            farmer: order.farmer,
+            balance: order.total_amount
+                .checked_sub(order.paid_amount)
+                .ok_or(ErrorCode::MathOverflow)?,
+            balance_deadline,
+        });
+        
+        Ok(())
+    }
+
+    pub fn pay_balance_sol(ctx: Context<PayBalanceSol>) -> Result<()> {
+        let clock = Clock::get()?;
+        let order = &ctx.accounts.order;
+        
+        require!(order.buyer == ctx.accounts.buyer.key(), ErrorCode::Unauthorized);
+        require!(order.status == OrderStatus::BalanceDue, ErrorCode::InvalidOrderStatus);
+        require!(clock.unix_timestamp <= order.balance_deadline, ErrorCode::BalanceOverdue);
+        
+        let balance = order.total_amount
+            .checked_sub(order.paid_amount)
+            .ok_or(ErrorCode::MathOverflow)?;
+        
+        // Transfer balance to order escrow
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.buyer.to_account_info(),
+                    to: ctx.accounts.escrow.to_account_info(),
+                },
+            ),
+            balance,
+        )?;
+        
+        // A fully paid pre-order joins the regular lifecycle at harvest
+        let order = &mut ctx.accounts.order;
+        order.paid_amount = order.total_amount;
+        order.status = OrderStatus::Harvesting;
+        order.stage_timestamps[OrderStatus::Harvesting.stage_index()] = clock.unix_timestamp;
+        
+        emit!(BalancePaid {
+            order: order.key(),
+            buyer: order.buyer,
+            amount: balance,
+        });
+        
+        Ok(())
+    }
+
+    pub fn pay_balance_token(ctx: Context<PayBalanceToken>) -> Result<()> {
+        let clock = Clock::get()?;
+        let order = &ctx.accounts.order;
+        
+        require!(order.buyer == ctx.accounts.buyer.key(), ErrorCode::Unauthorized);
+        require!(order.status == OrderStatus::BalanceDue, ErrorCode::InvalidOrderStatus);
+        require!(clock.unix_timestamp <= order.balance_deadline, ErrorCode::BalanceOverdue);
+        
+        let balance = order.total_amount
+            .checked_sub(order.paid_amount)
+            .ok_or(ErrorCode::MathOverflow)?;
+        
+        // Transfer balance to order escrow
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.buyer_token.to_account_info(),
+                    to: ctx.accounts.escrow_token.to_account_info(),
+                    authority: ctx.accounts.buyer.to_account_info(),
+                },
+            ),
+            balance,
+        )?;
+        
+        // A fully paid pre-order joins the regular lifecycle at harvest
+        let order = &mut ctx.accounts.order;
+        order.paid_amount = order.total_amount;
+        order.status = OrderStatus::Harvesting;
+        order.stage_timestamps[OrderStatus::Harvesting.stage_index()] = clock.unix_timestamp;
+        
+        emit!(BalancePaid {
+            order: order.key(),
+            buyer: order.buyer,
+            amount: balance,
+        });
+        
+        Ok(())
+    }
+
+    // Permissionless so a missed harvest window refunds the buyer without their signature
+    pub fn reclaim_deposit_sol(ctx: Context<ReclaimDepositSol>) -> Result<()> {
+        let clock = Clock::get()?;
+        let order = &ctx.accounts.order;
+        
+        require!(order.status == OrderStatus::PreOrdered, ErrorCode::InvalidOrderStatus);
+        require!(clock.unix_timestamp > order.harvest_deadline, ErrorCode::HarvestWindowOpen);
+        
+        let order_key = order.key();
+        let bump = ctx.bumps.escrow;
+        
+        let seeds = &[
+            b"order_escrow",
+            order_key.as_ref(),
+            &[bump],
+        ];
+        let signer_seeds: &[&[&[u8]]] = &[seeds];
+        
+        system_program::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.escrow.to_account_info(),
+                    to: ctx.accounts.buyer.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            order.paid_amount,
+        )?;
+        
+        let refunded = order.paid_amount;
+        let order = &mut ctx.accounts.order;
+        order.paid_amount = 0;
+        order.status = OrderStatus::Cancelled;
+        
+        emit!(DepositReclaimed {
+            order: order_key,
+            buyer: order.buyer,
+            amount: refunded,
+        });
+        
+        Ok(())
+    }
+
+    // Permissionless so a missed harvest window refunds the buyer without their signature
+    pub fn reclaim_deposit_token(ctx: Context<ReclaimDepositToken>) -> Result<()> {
+        let clock = Clock::get()?;
+        let order = &ctx.accounts.order;
+        
+        require!(order.status == OrderStatus::PreOrdered, ErrorCode::InvalidOrderStatus);
+        require!(clock.unix_timestamp > order.harvest_deadline, ErrorCode::HarvestWindowOpen);
+        
+        let order_key = order.key();
+        let bump = ctx.bumps.escrow_token;
+        
+        let seeds = &[
+            b"order_escrow_token",
+            order_key.as_ref(),
+            &[bump],
+        ];
+        let signer_seeds: &[&[&[u8]]] = &[seeds];
+        
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.escrow_token.to_account_info(),
+                    to: ctx.accounts.buyer_token.to_account_info(),
+                    authority: ctx.accounts.escrow_token.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            order.paid_amount,
+        )?;
+        
+        // Return the escrow account rent to the buyer
+        token::close_account(CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            CloseAccount {
+                account: ctx.accounts.escrow_token.to_account_info(),
+                destination: ctx.accounts.buyer.to_account_info(),
+                authority: ctx.accounts.escrow_token.to_account_info(),
+            },
+            signer_seeds,
+        ))?;
+        
+        let refunded = order.paid_amount;
+        let order = &mut ctx.accounts.order;
+        order.paid_amount = 0;
+        order.status = OrderStatus::Cancelled;
+        
+        emit!(DepositReclaimed {
+            order: order_key,
+            buyer: order.buyer,
+            amount: refunded,
+        });
+        
+        Ok(())
+    }
+
+    // Pre-orders never drew down stock, so a forfeited one has nothing to restock
+    pub fn forfeit_deposit_sol(ctx: Context<ForfeitDepositSol>) -> Result<()> {
+        let clock = Clock::get()?;
+        let order = &ctx.accounts.order;
+        
+        require!(order.farmer == ctx.accounts.farmer.key(), ErrorCode::Unauthorized);
+        require!(order.status == OrderStatus::BalanceDue, ErrorCode::InvalidOrderStatus);
+        require!(clock.unix_timestamp > order.balance_deadline, ErrorCode::BalanceWindowOpen);
+        
+        let order_key = order.key();
+        let bump = ctx.bumps.escrow;
+        
+        let seeds = &[
+            b"order_escrow",
+            order_key.as_ref(),
+            &[bump],
+        ];
+        let signer_seeds: &[&[&[u8]]] = &[seeds];
+        
+        system_program::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.escrow.to_account_info(),
+                    to: ctx.accounts.farmer.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            order.paid_amount,
+        )?;
+        
+        let forfeited = order.paid_amount;
+        let order = &mut ctx.accounts.order;
+        order.paid_amount = 0;
+        order.status = OrderStatus::Cancelled;
+        
+        emit!(DepositForfeited {
+            order: order_key,
+            farmer: order.farmer,
+            amount: forfeited,
+        });
+        
+        Ok(())
+    }
+
+    pub fn forfeit_deposit_token(ctx: Context<ForfeitDepositToken>) -> Result<()> {
+        let clock = Clock::get()?;
+        let order = &ctx.accounts.order;
+        
+        require!(order.farmer == ctx.accounts.farmer.key(), ErrorCode::Unauthorized);
+        require!(order.status == OrderStatus::BalanceDue, ErrorCode::InvalidOrderStatus);
+        require!(clock.unix_timestamp > order.balance_deadline, ErrorCode::BalanceWindowOpen);
+        
+        let order_key = order.key();
+        let bump = ctx.bumps.escrow_token;
+        
+        let seeds = &[
+            b"order_escrow_token",
+            order_key.as_ref(),
+            &[bump],
+        ];
+        let signer_seeds: &[&[&[u8]]] = &[seeds];
+        
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.escrow_token.to_account_info(),
+                    to: ctx.accounts.farmer_token.to_account_info(),
+                    authority: ctx.accounts.escrow_token.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            order.paid_amount,
+        )?;
+        
+        // Return the escrow account rent to the buyer
+        token::close_account(CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            CloseAccount {
+                account: ctx.accounts.escrow_token.to_account_info(),
+                destination: ctx.accounts.buyer.to_account_info(),
+                authority: ctx.accounts.escrow_token.to_account_info(),
+            },
+            signer_seeds,
+        ))?;
+        
+        let forfeited = order.paid_amount;
+        let order = &mut ctx.accounts.order;
+        order.paid_amount = 0;
+        order.status = OrderStatus::Cancelled;
+        
+        emit!(DepositForfeited {
+            order: order_key,
+            farmer: order.farmer,
+            amount: forfeited,
+        });
+        
+        Ok(())
+    }
+
+    pub fn subscribe_sol(
+        ctx: Context<SubscribeSol>,
+        subscription_id: u64,
+        quantity: u32,
+        period_days: u16,
+        periods: u32,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        let config = &ctx.accounts.config;
+        let product = &ctx.accounts.product;
+        
+        require!(config.is_active && !config.is_paused, ErrorCode::PlatformInactive);
+        require!(product.is_listed, ErrorCode::ProductNotListed);
+        require!(product.currency_mint == token::spl_token::native_mint::ID, ErrorCode::InvalidMint);
+        require!(quantity > 0, ErrorCode::InvalidQuantity);
+        require!(period_days > 0 && periods > 0, ErrorCode::InvalidSubscriptionTerms);
+        
+        let price_per_period = product.price
+            .checked_mul(quantity as u64)
+            .ok_or(ErrorCode::MathOverflow)?;
+        
+        let total_amount = price_per_period
+            .checked_mul(periods as u64)
+            .ok_or(ErrorCode::MathOverflow)?;
+        
+        let period_length = (period_days as i64) * 86400;
+        
+        // Fund every period up front into the subscription escrow
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.buyer.to_account_info(),
+                    to: ctx.accounts.escrow.to_account_info(),
+                },
+            ),
+            total_amount,
+        )?;
+        
+        let subscription = &mut ctx.accounts.subscription;
+        subscription.bump = ctx.bumps.subscription;
+        subscription.buyer = ctx.accounts.buyer.key();
+        subscription.farmer = product.farmer;
+        subscription.product = product.key();
+        subscription.subscription_id = subscription_id;
+        subscription.currency_mint = product.currency_mint;
+        subscription.quantity = quantity;
+        subscription.price_per_period = price_per_period;
+        subscription.period_length = period_length;
+        subscription.total_periods = periods;
+        subscription.remaining_periods = periods;
+        subscription.next_settlement_at = clock.unix_timestamp
+            .checked_add(period_length)
+            .ok_or(ErrorCode::MathOverflow)?;
+        subscription.created_at = clock.unix_timestamp;
+        subscription.is_active = true;
+        
+        emit!(SubscriptionCreated {
+            subscription: subscription.key(),
+            buyer: subscription.buyer,
+            farmer: subscription.farmer,
+            price_per_period,
+            periods,
+        });
+        
+        Ok(())
+    }
+
+    pub fn subscribe_token(
+        ctx: Context<SubscribeToken>,
+        subscription_id: u64,
+        quantity: u32,
+        period_days: u16,
+        periods: u32,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        let config = &ctx.accounts.config;
+        let product = &ctx.accounts.product;
+        
+        require!(config.is_active && !config.is_paused, ErrorCode::PlatformInactive);
+        require!(product.is_listed, ErrorCode::ProductNotListed);
+        require!(quantity > 0, ErrorCode::InvalidQuantity);
+        require!(period_days > 0 && periods > 0, ErrorCode::InvalidSubscriptionTerms);
+        require!(product.currency_mint == ctx.accounts.currency_mint.key(), ErrorCode::InvalidMint);
+        
+        let price_per_period = product.price
+            .checked_mul(quantity as u64)
+            .ok_or(ErrorCode::MathOverflow)?;
+        
+        let total_amount = price_per_period
+            .checked_mul(periods as u64)
+            .ok_or(ErrorCode::MathOverflow)?;
+        
+        let period_length = (period_days as i64) * 86400;
+        
+        // Fund every period up front into the subscription escrow
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.buyer_token.to_account_info(),
+                    to: ctx.accounts.escrow_token.to_account_info(),
+                    authority: ctx.accounts.buyer.to_account_info(),
+                },
+            ),
+            total_amount,
+        )?;
+        
+        let subscription = &mut ctx.accounts.subscription;
+        subscription.bump = ctx.bumps.subscription;
+        subscription.buyer = ctx.accounts.buyer.key();
+        subscription.farmer = product.farmer;
+        subscription.product = product.key();
+        subscription.subscription_id = subscription_id;
+        subscription.currency_mint = product.currency_mint;
+        subscription.quantity = quantity;
+        subscription.price_per_period = price_per_period;
+        subscription.period_length = period_length;
+        subscription.total_periods = periods;
+        subscription.remaining_periods = periods;
+        subscription.next_settlement_at = clock.unix_timestamp
+            .checked_add(period_length)
+            .ok_or(ErrorCode::MathOverflow)?;
+        subscription.created_at = clock.unix_timestamp;
+        subscription.is_active = true;
+        
+        emit!(SubscriptionCreated {
+            subscription: subscription.key(),
+            buyer: subscription.buyer,
+            farmer: subscription.farmer,
+            price_per_period,
+            periods,
+        });
+        
+        Ok(())
+    }
+
+    // Permissionless crank releasing one elapsed period's payment to the farmer
+    pub fn settle_period_sol(ctx: Context<SettlePeriodSol>) -> Result<()> {
+        let clock = Clock::get()?;
+        let subscription = &ctx.accounts.subscription;
+        let config = &ctx.accounts.config;
+        
+        require!(subscription.is_active, ErrorCode::SubscriptionInactive);
+        require!(subscription.remaining_periods > 0, ErrorCode::SubscriptionInactive);
+        require!(clock.unix_timestamp >= subscription.next_settlement_at, ErrorCode::PeriodNotElapsed);
+        
+        let fee = subscription.price_per_period
+            .checked_mul(config.fee_bps as u64)
+            .ok_or(ErrorCode::MathOverflow)?
+            .checked_div(10000)
+            .ok_or(ErrorCode::MathOverflow)?;
+        
+        let farmer_amount = subscription.price_per_period
+            .checked_sub(fee)
+            .ok_or(ErrorCode::MathOverflow)?;
+        
+        let subscription_key = subscription.key();
+        let bump = ctx.bumps.escrow;
+        
+        let seeds = &[
+            b"subscription_escrow",
+            subscription_key.as_ref(),
+            &[bump],
+        ];
+        let signer_seeds: &[&[&[u8]]] = &[seeds];
+        
+        // Transfer fee to treasury
+        if fee > 0 {
+            system_program::transfer(
+                CpiContext::new_with_signer(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: ctx.accounts.escrow.to_account_info(),
+                        to: ctx.accounts.treasury.to_account_info(),
+                    },
+                    signer_seeds,
+                ),
+                fee,
+            )?;
+        }
+        
+        // Transfer remaining to farmer
+        system_program::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.escrow.to_account_info(),
+                    to: ctx.accounts.farmer.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            farmer_amount,
+        )?;
+        
+        let subscription = &mut ctx.accounts.subscription;
+        subscription.remaining_periods = subscription.remaining_periods
+            .checked_sub(1)
+            .ok_or(ErrorCode::MathOverflow)?;
+        subscription.next_settlement_at = subscription.next_settlement_at
+            .checked_add(subscription.period_length)
+            .ok_or(ErrorCode::MathOverflow)?;
+        if subscription.remaining_periods == 0 {
+            subscription.is_active = false;
+        }
+        
+        emit!(PeriodSettled {
+            subscription: subscription_key,
+            farmer: subscription.farmer,
+            farmer_amount,
+            fee,
+            remaining_periods: subscription.remaining_periods,
+        });
+        
+        Ok(())
+    }
+
+    // Permissionless crank releasing one elapsed period's payment to the farmer
+    pub fn settle_period_token(ctx: Context<SettlePeriodToken>) -> Result<()> {
+        let clock = Clock::get()?;
+        let subscription = &ctx.accounts.subscription;
+        let config = &ctx.accounts.config;
+        
+        require!(subscription.is_active, ErrorCode::SubscriptionInactive);
+        require!(subscription.remaining_periods > 0, ErrorCode::SubscriptionInactive);
+        require!(clock.unix_timestamp >= subscription.next_settlement_at, ErrorCode::PeriodNotElapsed);
+        
+        let fee = subscription.price_per_period
+            .checked_mul(config.fee_bps as u64)
+            .ok_or(ErrorCode::MathOverflow)?
+            .checked_div(10000)
+            .ok_or(ErrorCode::MathOverflow)?;
+        
+        let farmer_amount = subscription.price_per_period
+            .checked_sub(fee)
+            .ok_or(ErrorCode::MathOverflow)?;
+        
+        let subscription_key = subscription.key();
+        let bump = ctx.bumps.escrow_token;
+        
+        let seeds = &[
+            b"subscription_escrow_token",
+            subscription_key.as_ref(),
+            &[bump],
+        ];
+        let signer_seeds: &[&[&[u8]]] = &[seeds];
+        
+        // Transfer fee to treasury
+        if fee > 0 {
+            token::transfer(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    Transfer {
+                        from: ctx.accounts.escrow_token.to_account_info(),
+                        to: ctx.accounts.treasury_token.to_account_info(),
+                        authority: ctx.accounts.escrow_token.to_account_info(),
+                    },
+                    signer_seeds,
+                ),
+                fee,
+            )?;
+        }
+        
+        // Transfer remaining to farmer
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.escrow_token.to_account_info(),
+                    to: ctx.accounts.farmer_token.to_account_info(),
+                    authority: ctx.accounts.escrow_token.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            farmer_amount,
+        )?;
+        
+        let subscription = &mut ctx.accounts.subscription;
+        subscription.remaining_periods = subscription.remaining_periods
+            .checked_sub(1)
+            .ok_or(ErrorCode::MathOverflow)?;
+        subscription.next_settlement_at = subscription.next_settlement_at
+            .checked_add(subscription.period_length)
+            .ok_or(ErrorCode::MathOverflow)?;
+        if subscription.remaining_periods == 0 {
+            subscription.is_active = false;
+        }
+        
+        emit!(PeriodSettled {
+            subscription: subscription_key,
+            farmer: subscription.farmer,
+            farmer_amount,
+            fee,
+            remaining_periods: subscription.remaining_periods,
+        });
+        
+        Ok(())
+    }
+
+    pub fn cancel_subscription_sol(ctx: Context<CancelSubscriptionSol>) -> Result<()> {
+        let clock = Clock::get()?;
+        let subscription = &ctx.accounts.subscription;
+        
+        require!(subscription.buyer == ctx.accounts.buyer.key(), ErrorCode::Unauthorized);
+        require!(subscription.is_active, ErrorCode::SubscriptionInactive);
+        
+        // Elapsed periods stay in escrow for the crank; only future periods are refunded
+        let owed_periods = subscription.elapsed_periods(clock.unix_timestamp);
+        let future_periods = subscription.remaining_periods
+            .checked_sub(owed_periods)
+            .ok_or(ErrorCode::MathOverflow)?;
+        let refund_amount = subscription.price_per_period
+            .checked_mul(future_periods as u64)
+            .ok_or(ErrorCode::MathOverflow)?;
+        
+        let subscription_key = subscription.key();
+        let bump = ctx.bumps.escrow;
+        
+        let seeds = &[
+            b"subscription_escrow",
+            subscription_key.as_ref(),
+            &[bump],
+        ];
+        let signer_seeds: &[&[&[u8]]] = &[seeds];
+        
+        system_program::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.escrow.to_account_info(),
+                    to: ctx.accounts.buyer.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            refund_amount,
+        )?;
+        
+        let subscription = &mut ctx.accounts.subscription;
+        subscription.remaining_periods = owed_periods;
+        subscription.is_active = owed_periods > 0;
+        
+        emit!(SubscriptionCancelled {
+            subscription: subscription_key,
+            buyer: subscription.buyer,
+            refund_amount,
+        });
+        
+        Ok(())
+    }
+
+    pub fn cancel_subscription_token(ctx: Context<CancelSubscriptionToken>) -> Result<()> {
+        let clock = Clock::get()?;
+        let subscription = &ctx.accounts.subscription;
+        
+        require!(subscription.buyer == ctx.accounts.buyer.key(), ErrorCode::Unauthorized);
+        require!(subscription.is_active, ErrorCode::SubscriptionInactive);
+        
+        // Elapsed periods stay in escrow for the crank; only future periods are refunded
+        let owed_periods = subscription.elapsed_periods(clock.unix_timestamp);
+        let future_periods = subscription.remaining_periods
+            .checked_sub(owed_periods)
+            .ok_or(ErrorCode::MathOverflow)?;
+        let refund_amount = subscription.price_per_period
+            .checked_mul(future_periods as u64)
+            .ok_or(ErrorCode::MathOverflow)?;
+        
+        let subscription_key = subscription.key();
+        let bump = ctx.bumps.escrow_token;
+        
+        let seeds = &[
+            b"subscription_escrow_token",
+            subscription_key.as_ref(),
+            &[bump],
+        ];
+        let signer_seeds: &[&[&[u8]]] = &[seeds];
+        
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.escrow_token.to_account_info(),
+                    to: ctx.accounts.buyer_token.to_account_info(),
+                    authority: ctx.accounts.escrow_token.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            refund_amount,
+        )?;
+        
+        let subscription = &mut ctx.accounts.subscription;
+        subscription.remaining_periods = owed_periods;
+        subscription.is_active = owed_periods > 0;
+        
+        emit!(SubscriptionCancelled {
+            subscription: subscription_key,
+            buyer: subscription.buyer,
+            refund_amount,
+        });
+        
+        Ok(())
+    }
+
+    pub fn checkout_sol<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CheckoutSol<'info>>,
+        first_order_id: u64,
+        lines: Vec<CartLine>,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        let config = &ctx.accounts.config;
+        
+        require!(config.is_active && !config.is_paused, ErrorCode::PlatformInactive);
+        
+        // Remaining accounts: one product per line, then an (order, escrow) pair per farmer
+        let split = lines.len().min(ctx.remaining_accounts.len());
+        let (products, order_accounts) = ctx.remaining_accounts.split_at(split);
+        let groups = cart::reserve_lines(&lines, products, token::spl_token::native_mint::ID)?;
+        require!(order_accounts.len() == groups.len() * 2, ErrorCode::InvalidCart);
+        
+        let buyer = ctx.accounts.buyer.to_account_info();
+        let system_program = ctx.accounts.system_program.to_account_info();
+        
+        for (i, (group, pair)) in groups.into_iter().zip(order_accounts.chunks(2)).enumerate() {
+            let [order_info, escrow_info] = pair else {
+                return err!(ErrorCode::InvalidCart);
+            };
+            let order_id = first_order_id
+                .checked_add(i as u64)
+                .ok_or(ErrorCode::MathOverflow)?;
+            let quantity = group.items
+                .iter()
+                .try_fold(0u32, |sum, item| sum.checked_add(item.quantity))
+                .ok_or(ErrorCode::MathOverflow)?;
+            
+            cart::escrow_bump(escrow_info, b"order_escrow", order_info.key)?;
+            
+            // Transfer SOL to order escrow
+            system_program::transfer(
+                CpiContext::new(
+                    system_program.clone(),
+                    system_program::Transfer {
+                        from: buyer.clone(),
+                        to: escrow_info.clone(),
+                    },
+                ),
+                group.total_amount,
+            )?;
+            
+            emit!(CartOrderPlaced {
+                order: order_info.key(),
+                buyer: buyer.key(),
+                farmer: group.farmer,
+                item_count: group.items.len() as u8,
+                total_amount: group.total_amount,
+            });
+            
+            cart::create_order_account(order_info, &buyer, &system_program, Order {
+                bump: 0,
+                buyer: buyer.key(),
+                farmer: group.farmer,
+                product: Pubkey::default(),
+                order_id,
+                quantity,
+                unit_price: 0,
+                total_amount: group.total_amount,
+                currency_mint: group.currency_mint,
+                status: OrderStatus::Pending,
+                created_at: clock.unix_timestamp,
+                stage_timestamps: [0; 6],
+                evidence_hash: None,
+                auto_release_at: 0,
+                is_preorder: false,
+                paid_amount: group.total_amount,
+                harvest_deadline: 0,
+                balance_deadline: 0,
+                items: group.items,
+            })?;
+        }
+        
+        Ok(())
+    }
+
+    pub fn checkout_token<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CheckoutToken<'info>>,
+        first_order_id: u64,
+        lines: Vec<CartLine>,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        let config = &ctx.accounts.config;
+        
+        require!(config.is_active && !config.is_paused, ErrorCode::PlatformInactive);
+        
+        // Remaining accounts: one product per line, then an (order, escrow) pair per farmer
+        let split = lines.len().min(ctx.remaining_accounts.len());
+        let (products, order_accounts) = ctx.remaining_accounts.split_at(split);
+        let groups = cart::reserve_lines(&lines, products, ctx.accounts.currency_mint.key())?;
+        require!(order_accounts.len() == groups.len() * 2, ErrorCode::InvalidCart);
+        
+        let buyer = ctx.accounts.buyer.to_account_info();
+        let system_program = ctx.accounts.system_program.to_account_info();
+        let currency_mint = ctx.accounts.currency_mint.to_account_info();
+        let token_program = ctx.accounts.token_program.to_account_info();
+        
+        for (i, (group, pair)) in groups.into_iter().zip(order_accounts.chunks(2)).enumerate() {
+            let [order_info, escrow_info] = pair else {
+                return err!(ErrorCode::InvalidCart);
+            };
+            let order_id = first_order_id
+                .checked_add(i as u64)
+                .ok_or(ErrorCode::MathOverflow)?;
+            let quantity = group.items
+                .iter()
+                .try_fold(0u32, |sum, item| sum.checked_add(item.quantity))
+                .ok_or(ErrorCode::MathOverflow)?;
+            
+            cart::create_escrow_token_account(
+                escrow_info,
+                order_info.key,
+                &currency_mint,
+                &buyer,
+                &system_program,
+                &token_program,
+            )?;
+            
+            // Transfer tokens to order escrow
+            token::transfer(
+                CpiContext::new(
+                    token_program.clone(),
+                    Transfer {
+                        from: ctx.accounts.buyer_token.to_account_info(),
+                        to: escrow_info.clone(),
+                        authority: buyer.clone(),
+                    },
+                ),
+                group.total_amount,
+            )?;
+            
+            emit!(CartOrderPlaced {
+                order: order_info.key(),
+                buyer: buyer.key(),
+                farmer: group.farmer,
+                item_count: group.items.len() as u8,
+                total_amount: group.total_amount,
+            });
+            
+            cart::create_order_account(order_info, &buyer, &system_program, Order {
+                bump: 0,
+                buyer: buyer.key(),
+                farmer: group.farmer,
+                product: Pubkey::default(),
+                order_id,
+                quantity,
+                unit_price: 0,
+                total_amount: group.total_amount,
+                currency_mint: group.currency_mint,
+                status: OrderStatus::Pending,
+                created_at: clock.unix_timestamp,
+                stage_timestamps: [0; 6],
+                evidence_hash: None,
+                auto_release_at: 0,
+                is_preorder: false,
+                paid_amount: group.total_amount,
+                harvest_deadline: 0,
+                balance_deadline: 0,
+                items: group.items,
+            })?;
+        }
+        
+        Ok(())
+    }
+
+    pub fn fulfill_reward(ctx: Context<FulfillReward>) -> Result<()> {
+        let campaign = &ctx.accounts.campaign;
+        let backing = &ctx.accounts.backing;
+        
+        require!(campaign.authority == ctx.accounts.authority.key(), ErrorCode::Unauthorized);
+        require!(campaign.is_finalized, ErrorCode::CampaignNotFinalized);
+        require!(campaign.raised_amount >= campaign.goal_amount, ErrorCode::GoalNotReached);
+        require!(!backing.is_refunded, ErrorCode::AlreadyRefunded);
+        require!(!backing.is_fulfilled, ErrorCode::RewardFulfilled);
+        
+        let amount = backing.amount;
+        let backing = &mut ctx.accounts.backing;
+        backing.is_fulfilled = true;
+        
+        let campaign = &mut ctx.accounts.campaign;
+        campaign.fulfilled_amount = campaign.fulfilled_amount
+            .checked_add(amount)
+            .ok_or(ErrorCode::MathOverflow)?;
+        
+        Ok(())
+    }
+
+    pub fn release_holdback_sol(ctx: Context<ReleaseHoldbackSol>) -> Result<()> {
+        let clock = Clock::get()?;
+        let campaign = &ctx.accounts.campaign;
+        
+        require!(campaign.authority == ctx.accounts.authority.key(), ErrorCode::Unauthorized);
+        require!(campaign.is_withdrawn, ErrorCode::CampaignNotSettled);
+        require!(campaign.open_disputes == 0, ErrorCode::DisputesPending);
+        require!(campaign.holdback_amount > 0, ErrorCode::HoldbackReleased);
+        
+        // Released once every reward is delivered, or after backers had the release period to dispute
+        let release_at = campaign.end_time
+            .checked_add(HOLDBACK_RELEASE_PERIOD)
+            .ok_or(ErrorCode::MathOverflow)?;
+        require!(
+            campaign.fulfilled_amount >= campaign.raised_amount || clock.unix_timestamp > release_at,
+            ErrorCode::HoldbackLocked
+        );
+        
+        let holdback = campaign.holdback_amount;
+        let farmer_key = campaign.farmer;
+        let campaign_id_bytes = campaign.campaign_id.to_le_bytes();
+        let bump = ctx.bumps.vault;
+        
+        let seeds = &[
+            b"vault",
+            farmer_key.as_ref(),
+            campaign_id_bytes.as_ref(),
+            &[bump],
+        ];
+        let signer_seeds: &[&[&[u8]]] = &[seeds];
+        
+        system_program::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.vault.to_account_info(),
+                    to: ctx.accounts.payout_wallet.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            holdback,
+        )?;
+        
+        let campaign = &mut ctx.accounts.campaign;
+        campaign.holdback_amount = 0;
+        
+        Ok(())
+    }
+
+    pub fn release_holdback_token(ctx: Context<ReleaseHoldbackToken>) -> Result<()> {
+        let clock = Clock::get()?;
+        let campaign = &ctx.accounts.campaign;
+        
+        require!(campaign.authority == ctx.accounts.authority.key(), ErrorCode::Unauthorized);
+        require!(campaign.is_withdrawn, ErrorCode::CampaignNotSettled);
+        require!(campaign.open_disputes == 0, ErrorCode::DisputesPending);
+        require!(campaign.holdback_amount > 0, ErrorCode::HoldbackReleased);
+        
+        // Released once every reward is delivered, or after backers had the release period to dispute
+        let release_at = campaign.end_time
+            .checked_add(HOLDBACK_RELEASE_PERIOD)
+            .ok_or(ErrorCode::MathOverflow)?;
+        require!(
+            campaign.fulfilled_amount >= campaign.raised_amount || clock.unix_timestamp > release_at,
+            ErrorCode::HoldbackLocked
+        );
+        
+        let holdback = campaign.holdback_amount;
+        let farmer_key = campaign.farmer;
+        let campaign_id_bytes = campaign.campaign_id.to_le_bytes();
+        let bump = ctx.bumps.vault_token;
+        
+        let seeds = &[
+            b"vault_token",
+            farmer_key.as_ref(),
+            campaign_id_bytes.as_ref(),
+            &[bump],
+        ];
+        let signer_seeds: &[&[&[u8]]] = &[seeds];
+        
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.vault_token.to_account_info(),
+                    to: ctx.accounts.payout_token.to_account_info(),
+                    authority: ctx.accounts.vault_token.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            holdback,
+        )?;
+        
+        let campaign = &mut ctx.accounts.campaign;
+        campaign.holdback_amount = 0;
+        
+        Ok(())
+    }
+
+    pub fn review_order(ctx: Context<ReviewOrder>, rating: u8, content_hash: [u8; 32]) -> Result<()> {
+        let clock = Clock::get()?;
+        let order = &ctx.accounts.order;
+        
+        require!(order.buyer == ctx.accounts.reviewer.key(), ErrorCode::Unauthorized);
+        require!(order.status == OrderStatus::Completed, ErrorCode::InvalidOrderStatus);
+        require!((1..=5).contains(&rating), ErrorCode::InvalidRating);
+        
+        let review = &mut ctx.accounts.review;
+        review.bump = ctx.bumps.review;
+        review.subject = order.key();
+        review.reviewer = ctx.accounts.reviewer.key();
+        review.farmer = order.farmer;
+        review.kind = ReviewKind::Order;
+        review.rating = rating;
+        review.content_hash = content_hash;
+        review.created_at = clock.unix_timestamp;
+        
+        let profile = &mut ctx.accounts.farmer_profile;
+        profile.rating_sum = profile.rating_sum
+            .checked_add(rating as u64)
+            .ok_or(ErrorCode::MathOverflow)?;
+        profile.rating_count = profile.rating_count
+            .checked_add(1)
+            .ok_or(ErrorCode::MathOverflow)?;
+        
+        emit!(ReviewSubmitted {
+            review: review.key(),
+            farmer: review.farmer,
+            reviewer: review.reviewer,
+            kind: ReviewKind::Order,
+            rating,
+        });
+        
+        Ok(())
+    }
+
+    pub fn review_backing(ctx: Context<ReviewBacking>, rating: u8, content_hash: [u8; 32]) -> Result<()> {
+        let clock = Clock::get()?;
+        let campaign = &ctx.accounts.campaign;
+        let backing = &ctx.accounts.backing;
+        
+        require!(backing.backer == ctx.accounts.reviewer.key(), ErrorCode::Unauthorized);
+        require!(backing.is_fulfilled, ErrorCode::RewardNotFulfilled);
+        require!((1..=5).contains(&rating), ErrorCode::InvalidRating);
+        
+        let review = &mut ctx.accounts.review;
+        review.bump = ctx.bumps.review;
+        review.subject = backing.key();
+        review.reviewer = ctx.accounts.reviewer.key();
+        review.farmer = campaign.farmer;
+        review.kind = ReviewKind::Backing;
+        review.rating = rating;
+        review.content_hash = content_hash;
+        review.created_at = clock.unix_timestamp;
+        
+        let profile = &mut ctx.accounts.farmer_profile;
+        profile.rating_sum = profile.rating_sum
+            .checked_add(rating as u64)
+            .ok_or(ErrorCode::MathOverflow)?;
+        profile.rating_count = profile.rating_count
+            .checked_add(1)
+            .ok_or(ErrorCode::MathOverflow)?;
+        
+        emit!(ReviewSubmitted {
+            review: review.key(),
+            farmer: review.farmer,
+            reviewer: review.reviewer,
+            kind: ReviewKind::Backing,
+            rating,
+        });
+        
+        Ok(())
+    }
+}
+
+// ==================== ACCESS CONTROL ====================
+
+// The super-admin (config.authority) and Admin role holders pass every role check
+pub fn require_role(
+    config: &Account<Config>,
+    role: &Option<Account<Role>>,
+    member: Pubkey,
+    kind: RoleKind,
+) -> Result<()> {
+    if member == config.authority {
+        return Ok(());
+    }
+    
+    let role = role.as_ref().ok_or(ErrorCode::MissingRole)?;
+    require!(role.config == config.key(), ErrorCode::MissingRole);
+    require!(role.member == member, ErrorCode::MissingRole);
+    require!(role.kind == kind || role.kind == RoleKind::Admin, ErrorCode::MissingRole);
+    
+    Ok(())
+}
+
+// Counts distinct co-owner wallets among the signers against the campaign's threshold
+pub fn require_co_owner_approval(campaign: &Campaign, signers: &[AccountInfo]) -> Result<()> {
+    let mut approvals: Vec<Pubkey> = Vec::new();
+    for info in signers.iter().filter(|info| info.is_signer) {
+        let is_co_owner = campaign.co_owners.iter().any(|co_owner| co_owner.wallet == info.key());
+        if is_co_owner && !approvals.contains(info.key) {
+            approvals.push(info.key());
+        }
+    }
+    require!(
+        approvals.len() >= campaign.co_owner_threshold as usize,
+        ErrorCode::CoOwnerApprovalRequired
+    );
+    
+    Ok(())
+}
+
+// ==================== ACCOUNT STRUCTURES ====================
+
+#[account]
+pub struct Config {
     pub bump: u8,
     pub authority: Pubkey,
     pub fee_bps: u16,
-    pub total_campaigns: u64,
-    pub total_raised: u64,
+    pub total_campaigns: u64,
+    pub total_raised: u64,
+    pub is_active: bool,
+    pub is_paused: bool,
+    pub version: u8,
+    pub treasury: Pubkey,
+    // Lifetime SOL spend in lamports needed for Silver, Gold and Platinum
+    pub loyalty_thresholds: [u64; 3],
+    // Farmer lifetime raise needed for each volume discount step
+    pub fee_discount_thresholds: [u64; 3],
+    pub fee_discount_bps: [u16; 3],
+    // Partners sharing withdrawal fees; the treasury keeps the remaining bps
+    pub fee_splits: Vec<FeeSplit>,
+}
+
+impl Config {
+    pub const LEN: usize = 1 + 32 + 2 + 8 + 8 + 1 + 1 + 1 + 32 + (8 * 3) + (8 * 3) + (2 * 3)
+        + (4 + MAX_FEE_SPLITS * FeeSplit::LEN);
+    
+    // Applies the best volume discount the farmer qualifies for
+    pub fn campaign_fee_bps(&self, farmer_total_raised: u64) -> u16 {
+        let discount = self.fee_discount_thresholds
+            .iter()
+            .zip(self.fee_discount_bps.iter())
+            .filter(|(threshold, _)| farmer_total_raised >= **threshold)
+            .map(|(_, discount)| *discount)
+            .max()
+            .unwrap_or(0);
+        self.fee_bps.saturating_sub(discount)
+    }
+    
+    pub fn loyalty_level(&self, lifetime_spend: u64) -> LoyaltyLevel {
+        let [silver, gold, platinum] = self.loyalty_thresholds;
+        if lifetime_spend >= platinum {
+            LoyaltyLevel::Platinum
+        } else if lifetime_spend >= gold {
+            LoyaltyLevel::Gold
+        } else if lifetime_spend >= silver {
+            LoyaltyLevel::Silver
+        } else {
+            LoyaltyLevel::Bronze
+        }
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct FeeSplit {
+    pub wallet: Pubkey,
+    pub share_bps: u16,
+}
+
+impl FeeSplit {
+    pub const LEN: usize = 32 + 2;
+    
+    pub fn share_of(&self, fee: u64) -> Result<u64> {
+        Ok(fee
+            .checked_mul(self.share_bps as u64)
+            .ok_or(ErrorCode::MathOverflow)?
+            .checked_div(10000)
+            .ok_or(ErrorCode::MathOverflow)?)
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct CoOwner {
+    pub wallet: Pubkey,
+    pub share_bps: u16,
+}
+
+impl CoOwner {
+    pub const LEN: usize = 32 + 2;
+    
+    pub fn share_of(&self, proceeds: u64) -> Result<u64> {
+        Ok(proceeds
+            .checked_mul(self.share_bps as u64)
+            .ok_or(ErrorCode::MathOverflow)?
+            .checked_div(10000)
+            .ok_or(ErrorCode::MathOverflow)?)
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LoyaltyLevel {
+    Bronze,
+    Silver,
+    Gold,
+    Platinum,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum RoleKind {
+    Admin,
+    Pauser,
+    FeeManager,
+    Verifier,
+    Treasurer,
+    Arbiter,
+}
+
+#[account]
+pub struct Role {
+    pub bump: u8,
+    pub config: Pubkey,
+    pub member: Pubkey,
+    pub kind: RoleKind,
+    pub granted_by: Pubkey,
+    pub granted_at: i64,
+}
+
+impl Role {
+    pub const LEN: usize = 1 + 32 + 32 + 1 + 32 + 8;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum AttestationKind {
+    Verification,
+    Organic,
+}
+
+#[account]
+pub struct Attestation {
+    pub bump: u8,
+    pub config: Pubkey,
+    pub farmer: Pubkey,
+    pub kind: AttestationKind,
+    pub issuer: Pubkey,
+    pub issued_at: i64,
+    pub expires_at: i64,
+    pub evidence_hash: [u8; 32],
+    pub is_revoked: bool,
+}
+
+impl Attestation {
+    pub const LEN: usize = 1 + 32 + 32 + 1 + 32 + 8 + 8 + 32 + 1;
+}
+
+#[account]
+pub struct FarmerProfile {
+    pub bump: u8,
+    pub farmer: Pubkey,
+    pub farm_name: String,
+    pub metadata_uri: String,
+    pub geohash: String,
+    pub is_organic: bool,
+    pub payout_wallet: Pubkey,
+    pub campaigns_created: u32,
+    pub campaigns_funded: u32,
+    pub total_raised: u64,
+    pub created_at: i64,
+    pub rating_sum: u64,
+    pub rating_count: u32,
+    // Seed of the farmer's next campaign; campaigns so far used ids 0..next_campaign_id
+    pub next_campaign_id: u64,
+}
+
+impl FarmerProfile {
+    pub const LEN: usize = 1 + 32 + (4 + 64) + (4 + 200) + (4 + 12) + 1 + 32 + 4 + 4 + 8 + 8 + 8 + 4 + 8;
+}
+
+#[account]
+pub struct Campaign {
+    pub bump: u8,
+    pub farmer: Pubkey,
+    pub campaign_id: u64,
+    pub title: String,
+    pub description: String,
+    pub goal_amount: u64,
+    pub raised_amount: u64,
+    pub currency_mint: Pubkey,
+    pub start_time: i64,
+    pub end_time: i64,
+    pub is_active: bool,
+    pub is_finalized: bool,
+    pub backers_count: u64,
+    pub tiers_count: u8,
+    pub open_backings: u64,
+    pub is_withdrawn: bool,
+    pub allowlist_root: Option<[u8; 32]>,
+    pub referral_bps: u16,
+    pub referred_volume: u64,
+    pub is_verified: bool,
+    pub open_disputes: u32,
+    pub fee_bps: u16,
+    // Partners sharing the proceeds; the lead farmer keeps the remaining bps
+    pub co_owners: Vec<CoOwner>,
+    pub co_owner_threshold: u8,
+    // Signer for management instructions; starts as the farmer and can be handed to a multisig
+    pub authority: Pubkey,
+    // Receives the lead farmer's proceeds; changes wait out PAYOUT_CHANGE_DELAY
+    pub payout_wallet: Pubkey,
+    pub pending_payout_wallet: Option<Pubkey>,
+    pub payout_change_at: i64,
+    pub category: Category,
+    pub tags: Vec<String>,
+    pub geohash: String,
+    // Refunds paid out of the vault after a failed campaign
+    pub refunded_amount: u64,
+    // Referred volume whose referrers have claimed their reward
+    pub referral_claimed_volume: u64,
+    // Backing amounts whose rewards have been delivered
+    pub fulfilled_amount: u64,
+    // Withheld from the farmer at withdrawal until rewards are delivered
+    pub holdback_amount: u64,
+    // Proposed authority; takes over once it accepts
+    pub pending_authority: Option<Pubkey>,
+}
+
+impl Campaign {
+    pub const LEN: usize = 1 + 32 + 8 + (4 + 64) + (4 + 256) + 8 + 8 + 32 + 8 + 8 + 1 + 1 + 8 + 1 + 8 + 1 + (1 + 32) + 2 + 8 + 1 + 4 + 2
+        + (4 + MAX_CO_OWNERS * CoOwner::LEN) + 1 + 32 + 32 + (1 + 32) + 8
+        + 1 + (4 + MAX_TAGS * (4 + MAX_TAG_LEN)) + (4 + 12) + 8 + 8 + 8 + 8 + (1 + 32);
+}
+
+#[account]
+pub struct CampaignTier {
+    pub bump: u8,
+    pub campaign: Pubkey,
+    pub tier_id: u8,
+    pub name: String,
+    pub min_amount: u64,
+    pub max_amount: u64,
+    pub benefits: String,
+    pub max_units: u32,
+    pub current_backers: u32,
+    pub units_sold: u32,
+    pub available_from: i64,
+    pub available_until: i64,
+    pub min_loyalty_level: LoyaltyLevel,
+}
+
+impl CampaignTier {
+    pub const LEN: usize = 1 + 32 + 1 + (4 + 32) + 8 + 8 + (4 + 256) + 4 + 4 + 4 + 8 + 8 + 1;
+}
+
+#[account]
+pub struct Backing {
+    pub bump: u8,
+    pub backer: Pubkey,
+    pub campaign: Pubkey,
+    pub tier_id: u8,
+    pub amount: u64,
+    pub quantity: u32,
+    pub backed_at: i64,
+    pub is_refunded: bool,
+    pub referrer: Option<Pubkey>,
+    pub is_fulfilled: bool,
+    pub refunded_amount: u64,
+}
+
+impl Backing {
+    pub const LEN: usize = 1 + 32 + 32 + 1 + 8 + 4 + 8 + 1 + (1 + 32) + 1 + 8;
+}
+
+#[account]
+pub struct Referrer {
+    pub bump: u8,
+    pub campaign: Pubkey,
+    pub wallet: Pubkey,
+    pub referred_volume: u64,
+    pub referred_count: u32,
+    pub reward_amount: u64,
+    pub is_paid: bool,
+}
+
+impl Referrer {
+    pub const LEN: usize = 1 + 32 + 32 + 8 + 4 + 8 + 1;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum Category {
+    Vegetables,
+    Fruit,
+    Dairy,
+    Meat,
+    Grain,
+    Eggs,
+    Honey,
+    Flowers,
+    Other,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum GrowthStage {
+    Seed,
+    Growing,
+    Maturing,
+    Harvest,
+    Ready,
+}
+
+#[account]
+pub struct Product {
+    pub bump: u8,
+    pub farmer: Pubkey,
+    pub product_id: u64,
+    pub price: u64,
+    pub currency_mint: Pubkey,
+    pub unit: String,
+    pub stock: u32,
+    pub growth_stage: GrowthStage,
+    pub harvest_date: i64,
+    pub metadata_uri: String,
+    pub is_listed: bool,
+    pub created_at: i64,
+    pub deposit_bps: u16,
+    pub category: Category,
+    pub tags: Vec<String>,
+    pub geohash: String,
+}
+
+impl Product {
+    pub const LEN: usize = 1 + 32 + 8 + 8 + 32 + (4 + 16) + 4 + 1 + 8 + (4 + 200) + 1 + 8 + 2
+        + 1 + (4 + MAX_TAGS * (4 + MAX_TAG_LEN)) + (4 + 12);
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum OrderStatus {
+    Pending,
+    Seed,
+    Growing,
+    Harvesting,
+    Processing,
+    Shipping,
+    Delivered,
+    Completed,
+    Cancelled,
+    Disputed,
+    Resolved,
+    PreOrdered,
+    BalanceDue,
+}
+
+impl OrderStatus {
+    pub fn is_in_progress(&self) -> bool {
+        matches!(
+            self,
+            OrderStatus::Seed
+                | OrderStatus::Growing
+                | OrderStatus::Harvesting
+                | OrderStatus::Processing
+                | OrderStatus::Shipping
+                | OrderStatus::Delivered
+        )
+    }
+
+    // Index into Order::stage_timestamps
+    pub fn stage_index(&self) -> usize {
+        (*self as usize) - (OrderStatus::Seed as usize)
+    }
+}
+
+#[account]
+pub struct Order {
+    pub bump: u8,
+    pub buyer: Pubkey,
+    pub farmer: Pubkey,
+    pub product: Pubkey,
+    pub order_id: u64,
+    pub quantity: u32,
+    pub unit_price: u64,
+    pub total_amount: u64,
+    pub currency_mint: Pubkey,
+    pub status: OrderStatus,
+    pub created_at: i64,
+    pub stage_timestamps: [i64; 6],
+    pub evidence_hash: Option<[u8; 32]>,
+    pub auto_release_at: i64,
+    pub is_preorder: bool,
+    pub paid_amount: u64,
+    pub harvest_deadline: i64,
+    pub balance_deadline: i64,
+    pub items: Vec<OrderItem>,
+}
+
+impl Order {
+    // Single-product orders carry no items; cart orders add OrderItem::LEN per item
+    pub const LEN: usize = 1 + 32 + 32 + 32 + 8 + 4 + 8 + 8 + 32 + 1 + 8 + (8 * 6) + (1 + 32) + 8 + 1 + 8 + 8 + 8 + 4;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct OrderItem {
+    pub product: Pubkey,
+    pub quantity: u32,
+    pub unit_price: u64,
+}
+
+impl OrderItem {
+    pub const LEN: usize = 32 + 4 + 8;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct CartLine {
+    pub quantity: u32,
+    // Guards against the farmer raising the price between quote and checkout
+    pub max_unit_price: u64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum DisputeKind {
+    Order,
+    Backing,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum DisputeStatus {
+    Open,
+    Resolved,
+}
+
+#[account]
+pub struct Dispute {
+    pub bump: u8,
+    pub subject: Pubkey,
+    pub kind: DisputeKind,
+    pub claimant: Pubkey,
+    pub respondent: Pubkey,
+    pub amount: u64,
+    pub reason_hash: [u8; 32],
+    pub status: DisputeStatus,
+    pub claimant_bps: u16,
+    pub opened_at: i64,
+    pub deadline: i64,
+    pub resolved_at: i64,
+}
+
+impl Dispute {
+    pub const LEN: usize = 1 + 32 + 1 + 32 + 32 + 8 + 32 + 1 + 2 + 8 + 8 + 8;
+}
+
+#[account]
+pub struct Subscription {
+    pub bump: u8,
+    pub buyer: Pubkey,
+    pub farmer: Pubkey,
+    pub product: Pubkey,
+    pub subscription_id: u64,
+    pub currency_mint: Pubkey,
+    pub quantity: u32,
+    pub price_per_period: u64,
+    pub period_length: i64,
+    pub total_periods: u32,
+    pub remaining_periods: u32,
+    pub next_settlement_at: i64,
+    pub created_at: i64,
     pub is_active: bool,
-    pub is_paused: bool,
-    pub version: u8,
 }
 
-impl Config {
-    pub const LEN: usize = 1 + 32 + 2 + 8 + 8 + 1 + 1 + 1;
+impl Subscription {
+    pub const LEN: usize = 1 + 32 + 32 + 32 + 8 + 32 + 4 + 8 + 8 + 4 + 4 + 8 + 8 + 1;
+    
+    // Elapsed periods that have not been settled yet
+    pub fn elapsed_periods(&self, now: i64) -> u32 {
+        if now < self.next_settlement_at || self.period_length <= 0 {
+            return 0;
+        }
+        let elapsed = (now - self.next_settlement_at) / self.period_length + 1;
+        elapsed.min(self.remaining_periods as i64) as u32
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum ReviewKind {
+    Order,
+    Backing,
+}
+
+#[account]
+pub struct Review {
+    pub bump: u8,
+    pub subject: Pubkey,
+    pub reviewer: Pubkey,
+    pub farmer: Pubkey,
+    pub kind: ReviewKind,
+    pub rating: u8,
+    pub content_hash: [u8; 32],
+    pub created_at: i64,
+}
+
+impl Review {
+    pub const LEN: usize = 1 + 32 + 32 + 32 + 1 + 1 + 32 + 8;
+}
+
+#[account]
+pub struct CustomerStats {
+    pub bump: u8,
+    pub wallet: Pubkey,
+    // Lamports backed in SOL campaigns, net of refunds
+    pub total_backed: u64,
+    pub backings_count: u32,
+    // Lamports spent on completed SOL orders
+    pub total_spent: u64,
+    pub orders_count: u32,
+}
+
+impl CustomerStats {
+    pub const LEN: usize = 1 + 32 + 8 + 4 + 8 + 4;
+    
+    pub fn lifetime_spend(&self) -> Result<u64> {
+        Ok(self.total_backed
+            .checked_add(self.total_spent)
+            .ok_or(ErrorCode::MathOverflow)?)
+    }
+}
+
+#[account]
+pub struct CampaignBackersPage {
+    pub bump: u8,
+    pub campaign: Pubkey,
+    pub page: u32,
+    // Grows by one key per backing, up to index::INDEX_PAGE_SIZE
+    pub backers: Vec<Pubkey>,
+}
+
+impl CampaignBackersPage {
+    pub const LEN: usize = 1 + 32 + 4 + 4;
+}
+
+#[account]
+pub struct BackerCampaignsPage {
+    pub bump: u8,
+    pub backer: Pubkey,
+    pub page: u32,
+    // Grows by one key per backing, up to index::INDEX_PAGE_SIZE
+    pub campaigns: Vec<Pubkey>,
+}
+
+impl BackerCampaignsPage {
+    pub const LEN: usize = 1 + 32 + 4 + 4;
+}
+
+// Head of the campaign or product listings for one category and geohash cell
+#[account]
+pub struct CategoryIndex {
+    pub bump: u8,
+    pub category: Category,
+    pub geohash_cell: String,
+    pub count: u64,
+}
+
+impl CategoryIndex {
+    pub const LEN: usize = 1 + 1 + (4 + GEOHASH_INDEX_PRECISION) + 8;
+}
+
+#[account]
+pub struct CategoryIndexPage {
+    pub bump: u8,
+    pub index: Pubkey,
+    pub page: u32,
+    // Grows by one key per listing, up to index::INDEX_PAGE_SIZE
+    pub entries: Vec<Pubkey>,
+}
+
+impl CategoryIndexPage {
+    pub const LEN: usize = 1 + 32 + 4 + 4;
+}
+
+// ==================== CONTEXT STRUCTS ====================
+
+#[derive(Accounts)]
+pub struct InitializeConfig<'info> {
+    #[account(
+        init,
+        seeds = [b"config"],
+        bump,
+        payer = authority,
+        space = 8 + Config::LEN
+    )]
+    pub config: Account<'info, Config>,
+    // Only the program's upgrade authority may claim the platform admin seat
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, crate::program::Workspace>,
+    #[account(constraint = program_data.upgrade_authority_address == Some(authority.key()) @ ErrorCode::Unauthorized)]
+    pub program_data: Account<'info, ProgramData>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(member: Pubkey, kind: RoleKind)]
+pub struct GrantRole<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        init,
+        seeds = [b"role", config.key().as_ref(), member.as_ref(), &[kind as u8]],
+        bump,
+        payer = authority,
+        space = 8 + Role::LEN
+    )]
+    pub role: Account<'info, Role>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeRole<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        mut,
+        seeds = [b"role", config.key().as_ref(), role.member.as_ref(), &[role.kind as u8]],
+        bump = role.bump,
+        close = authority,
+    )]
+    pub role: Account<'info, Role>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        seeds = [b"role", config.key().as_ref(), signer.key().as_ref(), &[role.kind as u8]],
+        bump = role.bump,
+    )]
+    pub role: Option<Account<'info, Role>>,
+    pub signer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetCampaignFee<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        seeds = [b"role", config.key().as_ref(), signer.key().as_ref(), &[role.kind as u8]],
+        bump = role.bump,
+    )]
+    pub role: Option<Account<'info, Role>>,
+    #[account(
+        mut,
+        seeds = [b"campaign", campaign.farmer.as_ref(), &campaign.campaign_id.to_le_bytes()],
+        bump = campaign.bump,
+    )]
+    pub campaign: Account<'info, Campaign>,
+    pub signer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(kind: AttestationKind)]
+pub struct AttestFarmer<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        seeds = [b"role", config.key().as_ref(), verifier.key().as_ref(), &[role.kind as u8]],
+        bump = role.bump,
+    )]
+    pub role: Option<Account<'info, Role>>,
+    #[account(
+        init_if_needed,
+        seeds = [b"attestation", config.key().as_ref(), farmer.key().as_ref(), &[kind as u8]],
+        bump,
+        payer = verifier,
+        space = 8 + Attestation::LEN
+    )]
+    pub attestation: Account<'info, Attestation>,
+    /// CHECK: Farmer wallet being attested
+    pub farmer: AccountInfo<'info>,
+    #[account(mut)]
+    pub verifier: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeAttestation<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        seeds = [b"role", config.key().as_ref(), verifier.key().as_ref(), &[role.kind as u8]],
+        bump = role.bump,
+    )]
+    pub role: Option<Account<'info, Role>>,
+    #[account(
+        mut,
+        seeds = [b"attestation", config.key().as_ref(), attestation.farmer.as_ref(), &[attestation.kind as u8]],
+        bump = attestation.bump,
+    )]
+    pub attestation: Account<'info, Attestation>,
+    pub verifier: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CreateFarmerProfile<'info> {
+    #[account(
+        init,
+        seeds = [b"farmer_profile", farmer.key().as_ref()],
+        bump,
+        payer = farmer,
+        space = 8 + FarmerProfile::LEN
+    )]
+    pub farmer_profile: Account<'info, FarmerProfile>,
+    #[account(mut)]
+    pub farmer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateFarmerProfile<'info> {
+    #[account(
+        mut,
+        seeds = [b"farmer_profile", farmer.key().as_ref()],
+        bump = farmer_profile.bump,
+    )]
+    pub farmer_profile: Account<'info, FarmerProfile>,
+    pub farmer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(
+    title: String,
+    description: String,
+    goal_amount: u64,
+    duration_days: u64,
+    requires_verification: bool,
+    category: Category,
+    tags: Vec<String>,
+    geohash: String,
+)]
+pub struct CreateCampaign<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        mut,
+        seeds = [b"farmer_profile", farmer.key().as_ref()],
+        bump = farmer_profile.bump,
+    )]
+    pub farmer_profile: Account<'info, FarmerProfile>,
+    #[account(
+        seeds = [b"attestation", config.key().as_ref(), farmer.key().as_ref(), &[AttestationKind::Verification as u8]],
+        bump = attestation.bump,
+    )]
+    pub attestation: Option<Account<'info, Attestation>>,
+    #[account(
+        seeds = [b"role", config.key().as_ref(), issuer_role.member.as_ref(), &[issuer_role.kind as u8]],
+        bump = issuer_role.bump,
+    )]
+    pub issuer_role: Option<Account<'info, Role>>,
+    #[account(
+        init,
+        seeds = [b"campaign", farmer.key().as_ref(), &farmer_profile.next_campaign_id.to_le_bytes()],
+        bump,
+        payer = farmer,
+        space = 8 + Campaign::LEN
+    )]
+    pub campaign: Account<'info, Campaign>,
+    /// CHECK: SOL vault PDA for the campaign
+    #[account(
+        mut,
+        seeds = [b"vault", farmer.key().as_ref(), &farmer_profile.next_campaign_id.to_le_bytes()],
+        bump,
+    )]
+    pub vault: AccountInfo<'info>,
+    pub currency_mint: Account<'info, Mint>,
+    #[account(
+        init_if_needed,
+        seeds = [b"campaign_category", &[category as u8][..], index::geohash_cell(&geohash)],
+        bump,
+        payer = farmer,
+        space = 8 + CategoryIndex::LEN
+    )]
+    pub category_index: Account<'info, CategoryIndex>,
+    #[account(
+        init_if_needed,
+        seeds = [b"category_page", category_index.key().as_ref(), &((category_index.count / index::INDEX_PAGE_SIZE as u64) as u32).to_le_bytes()],
+        bump,
+        payer = farmer,
+        space = 8 + CategoryIndexPage::LEN
+    )]
+    pub category_page: Account<'info, CategoryIndexPage>,
+    #[account(mut)]
+    pub farmer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(tier_id: u8)]
+pub struct CreateTier<'info> {
+    #[account(
+        mut,
+        seeds = [b"campaign", campaign.farmer.as_ref(), &campaign.campaign_id.to_le_bytes()],
+        bump = campaign.bump,
+    )]
+    pub campaign: Account<'info, Campaign>,
+    #[account(
+        init,
+        seeds = [b"tier", campaign.key().as_ref(), &[tier_id]],
+        bump,
+        payer = authority,
+        space = 8 + CampaignTier::LEN
+    )]
+    pub tier: Account<'info, CampaignTier>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(tier_id: u8)]
+pub struct BackCampaignSol<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        mut,
+        seeds = [b"campaign", campaign.farmer.as_ref(), &campaign.campaign_id.to_le_bytes()],
+        bump = campaign.bump,
+    )]
+    pub campaign: Account<'info, Campaign>,
+    #[account(
+        mut,
+        seeds = [b"tier", campaign.key().as_ref(), &[tier_id]],
+        bump = tier.bump,
+    )]
+    pub tier: Account<'info, CampaignTier>,
+    /// CHECK: SOL vault PDA
+    #[account(
+        mut,
+        seeds = [b"vault", campaign.farmer.as_ref(), &campaign.campaign_id.to_le_bytes()],
+        bump,
+    )]
+    pub vault: AccountInfo<'info>,
+    #[account(
+        init,
+        seeds = [b"backing", campaign.key().as_ref(), backer.key().as_ref()],
+        bump,
+        payer = backer,
+        space = 8 + Backing::LEN
+    )]
+    pub backing: Account<'info, Backing>,
+    #[account(
+        mut,
+        seeds = [b"referrer", campaign.key().as_ref(), referrer.wallet.as_ref()],
+        bump = referrer.bump,
+    )]
+    pub referrer: Option<Account<'info, Referrer>>,
+    #[account(
+        init_if_needed,
+        seeds = [b"customer_stats", backer.key().as_ref()],
+        bump,
+        payer = backer,
+        space = 8 + CustomerStats::LEN
+    )]
+    pub customer_stats: Account<'info, CustomerStats>,
+    #[account(
+        init_if_needed,
+        seeds = [b"campaign_backers", campaign.key().as_ref(), &((campaign.backers_count / index::INDEX_PAGE_SIZE as u64) as u32).to_le_bytes()],
+        bump,
+        payer = backer,
+        space = 8 + CampaignBackersPage::LEN
+    )]
+    pub campaign_backers: Account<'info, CampaignBackersPage>,
+    #[account(
+        init_if_needed,
+        seeds = [b"backer_campaigns", backer.key().as_ref(), &(customer_stats.backings_count / index::INDEX_PAGE_SIZE as u32).to_le_bytes()],
+        bump,
+        payer = backer,
+        space = 8 + BackerCampaignsPage::LEN
+    )]
+    pub backer_campaigns: Account<'info, BackerCampaignsPage>,
+    #[account(mut)]
+    pub backer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(tier_id: u8)]
+pub struct BackCampaignToken<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        mut,
+        seeds = [b"campaign", campaign.farmer.as_ref(), &campaign.campaign_id.to_le_bytes()],
+        bump = campaign.bump,
+    )]
+    pub campaign: Account<'info, Campaign>,
+    #[account(
+        mut,
+        seeds = [b"tier", campaign.key().as_ref(), &[tier_id]],
+        bump = tier.bump,
+    )]
+    pub tier: Account<'info, CampaignTier>,
+    #[account(
+        init_if_needed,
+        seeds = [b"vault_token", campaign.farmer.as_ref(), &campaign.campaign_id.to_le_bytes()],
+        bump,
+        payer = backer,
+        token::mint = currency_mint,
+        token::authority = vault_token,
+    )]
+    pub vault_token: Account<'info, TokenAccount>,
+    pub currency_mint: Account<'info, Mint>,
+    #[account(
+        mut,
+        constraint = backer_token.mint == currency_mint.key(),
+        constraint = backer_token.owner == backer.key(),
+    )]
+    pub backer_token: Account<'info, TokenAccount>,
+    #[account(
+        init,
+        seeds = [b"backing", campaign.key().as_ref(), backer.key().as_ref()],
+        bump,
+        payer = backer,
+        space = 8 + Backing::LEN
+    )]
+    pub backing: Account<'info, Backing>,
+    #[account(
+        mut,
+        seeds = [b"referrer", campaign.key().as_ref(), referrer.wallet.as_ref()],
+        bump = referrer.bump,
+    )]
+    pub referrer: Option<Account<'info, Referrer>>,
+    #[account(
+        init_if_needed,
+        seeds = [b"customer_stats", backer.key().as_ref()],
+        bump,
+        payer = backer,
+        space = 8 + CustomerStats::LEN
+    )]
+    pub customer_stats: Account<'info, CustomerStats>,
+    #[account(
+        init_if_needed,
+        seeds = [b"campaign_backers", campaign.key().as_ref(), &((campaign.backers_count / index::INDEX_PAGE_SIZE as u64) as u32).to_le_bytes()],
+        bump,
+        payer = backer,
+        space = 8 + CampaignBackersPage::LEN
+    )]
+    pub campaign_backers: Account<'info, CampaignBackersPage>,
+    #[account(
+        init_if_needed,
+        seeds = [b"backer_campaigns", backer.key().as_ref(), &(customer_stats.backings_count / index::INDEX_PAGE_SIZE as u32).to_le_bytes()],
+        bump,
+        payer = backer,
+        space = 8 + BackerCampaignsPage::LEN
+    )]
+    pub backer_campaigns: Account<'info, BackerCampaignsPage>,
+    #[account(mut)]
+    pub backer: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetAllowlistRoot<'info> {
+    #[account(
+        mut,
+        seeds = [b"campaign", campaign.farmer.as_ref(), &campaign.campaign_id.to_le_bytes()],
+        bump = campaign.bump,
+    )]
+    pub campaign: Account<'info, Campaign>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetReferralBps<'info> {
+    #[account(
+        mut,
+        seeds = [b"campaign", campaign.farmer.as_ref(), &campaign.campaign_id.to_le_bytes()],
+        bump = campaign.bump,
+    )]
+    pub campaign: Account<'info, Campaign>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RegisterReferrer<'info> {
+    #[account(
+        seeds = [b"campaign", campaign.farmer.as_ref(), &campaign.campaign_id.to_le_bytes()],
+        bump = campaign.bump,
+    )]
+    pub campaign: Account<'info, Campaign>,
+    #[account(
+        init,
+        seeds = [b"referrer", campaign.key().as_ref(), wallet.key().as_ref()],
+        bump,
+        payer = wallet,
+        space = 8 + Referrer::LEN
+    )]
+    pub referrer: Account<'info, Referrer>,
+    #[account(mut)]
+    pub wallet: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimReferralSol<'info> {
+    #[account(
+        mut,
+        seeds = [b"campaign", campaign.farmer.as_ref(), &campaign.campaign_id.to_le_bytes()],
+        bump = campaign.bump,
+    )]
+    pub campaign: Account<'info, Campaign>,
+    /// CHECK: SOL vault PDA
+    #[account(
+        mut,
+        seeds = [b"vault", campaign.farmer.as_ref(), &campaign.campaign_id.to_le_bytes()],
+        bump,
+    )]
+    pub vault: AccountInfo<'info>,
+    #[account(
+        mut,
+        seeds = [b"referrer", campaign.key().as_ref(), wallet.key().as_ref()],
+        bump = referrer.bump,
+    )]
+    pub referrer: Account<'info, Referrer>,
+    #[account(mut)]
+    pub wallet: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimReferralToken<'info> {
+    #[account(
+        mut,
+        seeds = [b"campaign", campaign.farmer.as_ref(), &campaign.campaign_id.to_le_bytes()],
+        bump = campaign.bump,
+    )]
+    pub campaign: Account<'info, Campaign>,
+    #[account(
+        mut,
+        seeds = [b"vault_token", campaign.farmer.as_ref(), &campaign.campaign_id.to_le_bytes()],
+        bump,
+        token::mint = currency_mint,
+        token::authority = vault_token,
+    )]
+    pub vault_token: Account<'info, TokenAccount>,
+    pub currency_mint: Account<'info, Mint>,
+    #[account(
+        mut,
+        constraint = wallet_token.mint == currency_mint.key(),
+        constraint = wallet_token.owner == wallet.key(),
+    )]
+    pub wallet_token: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        seeds = [b"referrer", campaign.key().as_ref(), wallet.key().as_ref()],
+        bump = referrer.bump,
+    )]
+    pub referrer: Account<'info, Referrer>,
+    pub wallet: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct FinalizeCampaign<'info> {
+    #[account(
+        mut,
+        seeds = [b"campaign", campaign.farmer.as_ref(), &campaign.campaign_id.to_le_bytes()],
+        bump = campaign.bump,
+    )]
+    pub campaign: Account<'info, Campaign>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawFundsSol<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        mut,
+        seeds = [b"farmer_profile", campaign.farmer.as_ref()],
+        bump = farmer_profile.bump,
+    )]
+    pub farmer_profile: Account<'info, FarmerProfile>,
+    #[account(
+        mut,
+        seeds = [b"campaign", campaign.farmer.as_ref(), &campaign.campaign_id.to_le_bytes()],
+        bump = campaign.bump,
+    )]
+    pub campaign: Account<'info, Campaign>,
+    /// CHECK: SOL vault PDA
+    #[account(
+        mut,
+        seeds = [b"vault", campaign.farmer.as_ref(), &campaign.campaign_id.to_le_bytes()],
+        bump,
+    )]
+    pub vault: AccountInfo<'info>,
+    /// CHECK: Treasury account for platform fees
+    #[account(
+        mut,
+        address = config.treasury @ ErrorCode::InvalidTreasury,
+    )]
+    pub treasury: AccountInfo<'info>,
+    /// CHECK: Destination for the lead farmer's proceeds
+    #[account(
+        mut,
+        address = campaign.payout_wallet @ ErrorCode::InvalidPayoutWallet,
+    )]
+    pub payout_wallet: AccountInfo<'info>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawFundsToken<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        mut,
+        seeds = [b"farmer_profile", campaign.farmer.as_ref()],
+        bump = farmer_profile.bump,
+    )]
+    pub farmer_profile: Account<'info, FarmerProfile>,
+    #[account(
+        mut,
+        seeds = [b"campaign", campaign.farmer.as_ref(), &campaign.campaign_id.to_le_bytes()],
+        bump = campaign.bump,
+    )]
+    pub campaign: Account<'info, Campaign>,
+    #[account(
+        mut,
+        seeds = [b"vault_token", campaign.farmer.as_ref(), &campaign.campaign_id.to_le_bytes()],
+        bump,
+        token::mint = currency_mint,
+        token::authority = vault_token,
+    )]
+    pub vault_token: Account<'info, TokenAccount>,
+    pub currency_mint: Account<'info, Mint>,
+    #[account(
+        mut,
+        constraint = payout_token.mint == currency_mint.key(),
+        constraint = payout_token.owner == campaign.payout_wallet @ ErrorCode::InvalidPayoutWallet,
+    )]
+    pub payout_token: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        constraint = treasury_token.mint == currency_mint.key(),
+        constraint = treasury_token.owner == config.treasury @ ErrorCode::InvalidTreasury,
+    )]
+    pub treasury_token: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimRefundSol<'info> {
+    #[account(
+        mut,
+        seeds = [b"campaign", campaign.farmer.as_ref(), &campaign.campaign_id.to_le_bytes()],
+        bump = campaign.bump,
+    )]
+    pub campaign: Account<'info, Campaign>,
+    /// CHECK: SOL vault PDA
+    #[account(
+        mut,
+        seeds = [b"vault", campaign.farmer.as_ref(), &campaign.campaign_id.to_le_bytes()],
+        bump,
+    )]
+    pub vault: AccountInfo<'info>,
+    #[account(
+        mut,
+        seeds = [b"backing", campaign.key().as_ref(), backer.key().as_ref()],
+        bump = backing.bump,
+    )]
+    pub backing: Account<'info, Backing>,
+    #[account(
+        mut,
+        seeds = [b"customer_stats", backer.key().as_ref()],
+        bump = customer_stats.bump,
+    )]
+    pub customer_stats: Account<'info, CustomerStats>,
+    #[account(mut)]
+    pub backer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimRefundToken<'info> {
+    #[account(
+        mut,
+        seeds = [b"campaign", campaign.farmer.as_ref(), &campaign.campaign_id.to_le_bytes()],
+        bump = campaign.bump,
+    )]
+    pub campaign: Account<'info, Campaign>,
+    #[account(
+        mut,
+        seeds = [b"vault_token", campaign.farmer.as_ref(), &campaign.campaign_id.to_le_bytes()],
+        bump,
+        token::mint = currency_mint,
+        token::authority = vault_token,
+    )]
+    pub vault_token: Account<'info, TokenAccount>,
+    pub currency_mint: Account<'info, Mint>,
+    #[account(
+        mut,
+        constraint = backer_token.mint == currency_mint.key(),
+        constraint = backer_token.owner == backer.key(),
+    )]
+    pub backer_token: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        seeds = [b"backing", campaign.key().as_ref(), backer.key().as_ref()],
+        bump = backing.bump,
+    )]
+    pub backing: Account<'info, Backing>,
+    #[account(mut)]
+    pub backer: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct CloseBacking<'info> {
+    /// CHECK: Campaign of the backing, checked in the handler; may already be closed
+    #[account(mut)]
+    pub campaign: AccountInfo<'info>,
+    #[account(
+        mut,
+        seeds = [b"backing", campaign.key().as_ref(), backer.key().as_ref()],
+        bump = backing.bump,
+        constraint = backing.campaign == campaign.key(),
+        close = backer,
+    )]
+    pub backing: Account<'info, Backing>,
+    /// CHECK: Dispute PDA of the backing; only exists once one was opened
+    #[account(
+        seeds = [b"dispute", backing.key().as_ref()],
+        bump,
+    )]
+    pub dispute: AccountInfo<'info>,
+    #[account(mut)]
+    pub backer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CloseTier<'info> {
+    #[account(
+        mut,
+        seeds = [b"campaign", campaign.farmer.as_ref(), &campaign.campaign_id.to_le_bytes()],
+        bump = campaign.bump,
+    )]
+    pub campaign: Account<'info, Campaign>,
+    #[account(
+        mut,
+        seeds = [b"tier", campaign.key().as_ref(), &[tier.tier_id]],
+        bump = tier.bump,
+        close = authority,
+    )]
+    pub tier: Account<'info, CampaignTier>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CloseCampaign<'info> {
+    #[account(
+        mut,
+        seeds = [b"campaign", campaign.farmer.as_ref(), &campaign.campaign_id.to_le_bytes()],
+        bump = campaign.bump,
+        close = authority,
+    )]
+    pub campaign: Account<'info, Campaign>,
+    /// CHECK: SOL vault PDA for the campaign
+    #[account(
+        mut,
+        seeds = [b"vault", campaign.farmer.as_ref(), &campaign.campaign_id.to_le_bytes()],
+        bump,
+    )]
+    pub vault: AccountInfo<'info>,
+    /// CHECK: Token vault PDA; only exists once the campaign took a token backing
+    #[account(
+        mut,
+        seeds = [b"vault_token", campaign.farmer.as_ref(), &campaign.campaign_id.to_le_bytes()],
+        bump,
+    )]
+    pub vault_token: AccountInfo<'info>,
+    #[account(mut)]
+    pub authority_token: Option<Account<'info, TokenAccount>>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub token_program: Option<Program<'info, Token>>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(
+    product_id: u64,
+    price: u64,
+    unit: String,
+    stock: u32,
+    growth_stage: GrowthStage,
+    harvest_date: i64,
+    metadata_uri: String,
+    category: Category,
+    tags: Vec<String>,
+    geohash: String,
+)]
+pub struct CreateProduct<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        seeds = [b"farmer_profile", farmer.key().as_ref()],
+        bump = farmer_profile.bump,
+    )]
+    pub farmer_profile: Account<'info, FarmerProfile>,
+    #[account(
+        init,
+        seeds = [b"product", farmer.key().as_ref(), &product_id.to_le_bytes()],
+        bump,
+        payer = farmer,
+        space = 8 + Product::LEN
+    )]
+    pub product: Account<'info, Product>,
+    pub currency_mint: Account<'info, Mint>,
+    #[account(
+        init_if_needed,
+        seeds = [b"product_category", &[category as u8][..], index::geohash_cell(&geohash)],
+        bump,
+        payer = farmer,
+        space = 8 + CategoryIndex::LEN
+    )]
+    pub category_index: Account<'info, CategoryIndex>,
+    #[account(
+        init_if_needed,
+        seeds = [b"category_page", category_index.key().as_ref(), &((category_index.count / index::INDEX_PAGE_SIZE as u64) as u32).to_le_bytes()],
+        bump,
+        payer = farmer,
+        space = 8 + CategoryIndexPage::LEN
+    )]
+    pub category_page: Account<'info, CategoryIndexPage>,
+    #[account(mut)]
+    pub farmer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateProduct<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        mut,
+        seeds = [b"product", farmer.key().as_ref(), &product.product_id.to_le_bytes()],
+        bump = product.bump,
+    )]
+    pub product: Account<'info, Product>,
+    pub farmer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(order_id: u64)]
+pub struct PlaceOrderSol<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        mut,
+        seeds = [b"product", product.farmer.as_ref(), &product.product_id.to_le_bytes()],
+        bump = product.bump,
+    )]
+    pub product: Account<'info, Product>,
+    #[account(
+        init,
+        seeds = [b"order", buyer.key().as_ref(), &order_id.to_le_bytes()],
+        bump,
+        payer = buyer,
+        space = 8 + Order::LEN
+    )]
+    pub order: Account<'info, Order>,
+    /// CHECK: SOL escrow PDA for the order
+    #[account(
+        mut,
+        seeds = [b"order_escrow", order.key().as_ref()],
+        bump,
+    )]
+    pub escrow: AccountInfo<'info>,
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(order_id: u64)]
+pub struct PlaceOrderToken<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        mut,
+        seeds = [b"product", product.farmer.as_ref(), &product.product_id.to_le_bytes()],
+        bump = product.bump,
+    )]
+    pub product: Account<'info, Product>,
+    #[account(
+        init,
+        seeds = [b"order", buyer.key().as_ref(), &order_id.to_le_bytes()],
+        bump,
+        payer = buyer,
+        space = 8 + Order::LEN
+    )]
+    pub order: Account<'info, Order>,
+    #[account(
+        init,
+        seeds = [b"order_escrow_token", order.key().as_ref()],
+        bump,
+        payer = buyer,
+        token::mint = currency_mint,
+        token::authority = escrow_token,
+    )]
+    pub escrow_token: Account<'info, TokenAccount>,
+    pub currency_mint: Account<'info, Mint>,
+    #[account(
+        mut,
+        constraint = buyer_token.mint == currency_mint.key(),
+        constraint = buyer_token.owner == buyer.key(),
+    )]
+    pub buyer_token: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateOrder<'info> {
+    #[account(
+        mut,
+        seeds = [b"order", order.buyer.as_ref(), &order.order_id.to_le_bytes()],
+        bump = order.bump,
+    )]
+    pub order: Account<'info, Order>,
+    pub farmer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CancelOrderSol<'info> {
+    #[account(
+        mut,
+        address = order.product,
+    )]
+    pub product: Option<Account<'info, Product>>,
+    #[account(
+        mut,
+        seeds = [b"order", buyer.key().as_ref(), &order.order_id.to_le_bytes()],
+        bump = order.bump,
+    )]
+    pub order: Account<'info, Order>,
+    /// CHECK: SOL escrow PDA for the order
+    #[account(
+        mut,
+        seeds = [b"order_escrow", order.key().as_ref()],
+        bump,
+    )]
+    pub escrow: AccountInfo<'info>,
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CancelOrderToken<'info> {
+    #[account(
+        mut,
+        address = order.product,
+    )]
+    pub product: Option<Account<'info, Product>>,
+    #[account(
+        mut,
+        seeds = [b"order", buyer.key().as_ref(), &order.order_id.to_le_bytes()],
+        bump = order.bump,
+    )]
+    pub order: Account<'info, Order>,
+    #[account(
+        mut,
+        seeds = [b"order_escrow_token", order.key().as_ref()],
+        bump,
+        token::mint = order.currency_mint,
+        token::authority = escrow_token,
+    )]
+    pub escrow_token: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        constraint = buyer_token.mint == order.currency_mint,
+        constraint = buyer_token.owner == buyer.key(),
+    )]
+    pub buyer_token: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct ConfirmDeliverySol<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        mut,
+        seeds = [b"order", order.buyer.as_ref(), &order.order_id.to_le_bytes()],
+        bump = order.bump,
+    )]
+    pub order: Account<'info, Order>,
+    /// CHECK: SOL escrow PDA for the order
+    #[account(
+        mut,
+        seeds = [b"order_escrow", order.key().as_ref()],
+        bump,
+    )]
+    pub escrow: AccountInfo<'info>,
+    /// CHECK: Farmer receiving the order payment
+    #[account(
+        mut,
+        address = order.farmer,
+    )]
+    pub farmer: AccountInfo<'info>,
+    /// CHECK: Treasury account for platform fees
+    #[account(
+        mut,
+        address = config.treasury @ ErrorCode::InvalidTreasury,
+    )]
+    pub treasury: AccountInfo<'info>,
+    // Created on first settlement; the caller covers rent when auto-releasing
+    #[account(
+        init_if_needed,
+        seeds = [b"customer_stats", order.buyer.as_ref()],
+        bump,
+        payer = caller,
+        space = 8 + CustomerStats::LEN
+    )]
+    pub customer_stats: Account<'info, CustomerStats>,
+    #[account(mut)]
+    pub caller: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ConfirmDeliveryToken<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        mut,
+        seeds = [b"order", order.buyer.as_ref(), &order.order_id.to_le_bytes()],
+        bump = order.bump,
+    )]
+    pub order: Account<'info, Order>,
+    #[account(
+        mut,
+        seeds = [b"order_escrow_token", order.key().as_ref()],
+        bump,
+        token::mint = order.currency_mint,
+        token::authority = escrow_token,
+    )]
+    pub escrow_token: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        constraint = farmer_token.mint == order.currency_mint,
+        constraint = farmer_token.owner == order.farmer,
+    )]
+    pub farmer_token: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        constraint = treasury_token.mint == order.currency_mint,
+        constraint = treasury_token.owner == config.treasury @ ErrorCode::InvalidTreasury,
+    )]
+    pub treasury_token: Account<'info, TokenAccount>,
+    /// CHECK: Buyer receiving the escrow account rent
+    #[account(
+        mut,
+        address = order.buyer,
+    )]
+    pub buyer: AccountInfo<'info>,
+    // Created on first settlement; the caller covers rent when auto-releasing
+    #[account(
+        init_if_needed,
+        seeds = [b"customer_stats", order.buyer.as_ref()],
+        bump,
+        payer = caller,
+        space = 8 + CustomerStats::LEN
+    )]
+    pub customer_stats: Account<'info, CustomerStats>,
+    #[account(mut)]
+    pub caller: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct OpenOrderDispute<'info> {
+    #[account(
+        mut,
+        seeds = [b"order", buyer.key().as_ref(), &order.order_id.to_le_bytes()],
+        bump = order.bump,
+    )]
+    pub order: Account<'info, Order>,
+    #[account(
+        init,
+        seeds = [b"dispute", order.key().as_ref()],
+        bump,
+        payer = buyer,
+        space = 8 + Dispute::LEN
+    )]
+    pub dispute: Account<'info, Dispute>,
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct OpenBackingDispute<'info> {
+    #[account(
+        mut,
+        seeds = [b"campaign", campaign.farmer.as_ref(), &campaign.campaign_id.to_le_bytes()],
+        bump = campaign.bump,
+    )]
+    pub campaign: Account<'info, Campaign>,
+    #[account(
+        seeds = [b"backing", campaign.key().as_ref(), backer.key().as_ref()],
+        bump = backing.bump,
+    )]
+    pub backing: Account<'info, Backing>,
+    #[account(
+        init,
+        seeds = [b"dispute", backing.key().as_ref()],
+        bump,
+        payer = backer,
+        space = 8 + Dispute::LEN
+    )]
+    pub dispute: Account<'info, Dispute>,
+    #[account(mut)]
+    pub backer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ResolveOrderDisputeSol<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        seeds = [b"role", config.key().as_ref(), caller.key().as_ref(), &[role.kind as u8]],
+        bump = role.bump,
+    )]
+    pub role: Option<Account<'info, Role>>,
+    #[account(
+        mut,
+        seeds = [b"dispute", dispute.subject.as_ref()],
+        bump = dispute.bump,
+    )]
+    pub dispute: Account<'info, Dispute>,
+    #[account(
+        mut,
+        address = dispute.subject,
+    )]
+    pub order: Account<'info, Order>,
+    /// CHECK: SOL escrow PDA for the order
+    #[account(
+        mut,
+        seeds = [b"order_escrow", order.key().as_ref()],
+        bump,
+    )]
+    pub escrow: AccountInfo<'info>,
+    /// CHECK: Buyer receiving the claimant share
+    #[account(
+        mut,
+        address = order.buyer,
+    )]
+    pub buyer: AccountInfo<'info>,
+    /// CHECK: Farmer receiving the respondent share
+    #[account(
+        mut,
+        address = order.farmer,
+    )]
+    pub farmer: AccountInfo<'info>,
+    /// CHECK: Treasury account for platform fees
+    #[account(
+        mut,
+        address = config.treasury @ ErrorCode::InvalidTreasury,
+    )]
+    pub treasury: AccountInfo<'info>,
+    pub caller: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ResolveOrderDisputeToken<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        seeds = [b"role", config.key().as_ref(), caller.key().as_ref(), &[role.kind as u8]],
+        bump = role.bump,
+    )]
+    pub role: Option<Account<'info, Role>>,
+    #[account(
+        mut,
+        seeds = [b"dispute", dispute.subject.as_ref()],
+        bump = dispute.bump,
+    )]
+    pub dispute: Account<'info, Dispute>,
+    #[account(
+        mut,
+        address = dispute.subject,
+    )]
+    pub order: Account<'info, Order>,
+    #[account(
+        mut,
+        seeds = [b"order_escrow_token", order.key().as_ref()],
+        bump,
+        token::mint = order.currency_mint,
+        token::authority = escrow_token,
+    )]
+    pub escrow_token: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        constraint = buyer_token.mint == order.currency_mint,
+        constraint = buyer_token.owner == order.buyer,
+    )]
+    pub buyer_token: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        constraint = farmer_token.mint == order.currency_mint,
+        constraint = farmer_token.owner == order.farmer,
+    )]
+    pub farmer_token: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        constraint = treasury_token.mint == order.currency_mint,
+        constraint = treasury_token.owner == config.treasury @ ErrorCode::InvalidTreasury,
+    )]
+    pub treasury_token: Account<'info, TokenAccount>,
+    /// CHECK: Buyer receiving the escrow account rent
+    #[account(
+        mut,
+        address = order.buyer,
+    )]
+    pub buyer: AccountInfo<'info>,
+    pub caller: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct ResolveBackingDisputeSol<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        seeds = [b"role", config.key().as_ref(), caller.key().as_ref(), &[role.kind as u8]],
+        bump = role.bump,
+    )]
+    pub role: Option<Account<'info, Role>>,
+    #[account(
+        mut,
+        seeds = [b"dispute", dispute.subject.as_ref()],
+        bump = dispute.bump,
+    )]
+    pub dispute: Account<'info, Dispute>,
+    #[account(
+        mut,
+        seeds = [b"campaign", campaign.farmer.as_ref(), &campaign.campaign_id.to_le_bytes()],
+        bump = campaign.bump,
+    )]
+    pub campaign: Account<'info, Campaign>,
+    /// CHECK: SOL vault PDA
+    #[account(
+        mut,
+        seeds = [b"vault", campaign.farmer.as_ref(), &campaign.campaign_id.to_le_bytes()],
+        bump,
+    )]
+    pub vault: AccountInfo<'info>,
+    #[account(
+        mut,
+        address = dispute.subject,
+        constraint = backing.campaign == campaign.key(),
+    )]
+    pub backing: Account<'info, Backing>,
+    /// CHECK: Backer receiving the claimant share
+    #[account(
+        mut,
+        address = backing.backer,
+    )]
+    pub backer: AccountInfo<'info>,
+    #[account(
+        mut,
+        seeds = [b"customer_stats", backing.backer.as_ref()],
+        bump = customer_stats.bump,
+    )]
+    pub customer_stats: Account<'info, CustomerStats>,
+    pub caller: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ResolveBackingDisputeToken<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        seeds = [b"role", config.key().as_ref(), caller.key().as_ref(), &[role.kind as u8]],
+        bump = role.bump,
+    )]
+    pub role: Option<Account<'info, Role>>,
+    #[account(
+        mut,
+        seeds = [b"dispute", dispute.subject.as_ref()],
+        bump = dispute.bump,
+    )]
+    pub dispute: Account<'info, Dispute>,
+    #[account(
+        mut,
+        seeds = [b"campaign", campaign.farmer.as_ref(), &campaign.campaign_id.to_le_bytes()],
+        bump = campaign.bump,
+    )]
+    pub campaign: Account<'info, Campaign>,
+    #[account(
+        mut,
+        seeds = [b"vault_token", campaign.farmer.as_ref(), &campaign.campaign_id.to_le_bytes()],
+        bump,
+        token::mint = campaign.currency_mint,
+        token::authority = vault_token,
+    )]
+    pub vault_token: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        address = dispute.subject,
+        constraint = backing.campaign == campaign.key(),
+    )]
+    pub backing: Account<'info, Backing>,
+    #[account(
+        mut,
+        constraint = backer_token.mint == campaign.currency_mint,
+        constraint = backer_token.owner == backing.backer,
+    )]
+    pub backer_token: Account<'info, TokenAccount>,
+    pub caller: Signer<'info>,
+    pub token_program: Program<'info, Token>,
 }
 
-#[account]
-pub struct Campaign {
-    pub bump: u8,
-    pub farmer: Pubkey,
-    pub campaign_id: u64,
-    pub title: String,
-    pub description: String,
-    pub goal_amount: u64,
-    pub raised_amount: u64,
-    pub currency_mint: Pubkey,
-    pub start_time: i64,
-    pub end_time: i64,
-    pub is_active: bool,
-    pub is_finalized: bool,
-    pub backers_count: u64,
-    pub tiers_count: u8,
+#[derive(Accounts)]
+pub struct PayBalanceSol<'info> {
+    #[account(
+        mut,
+        seeds = [b"order", buyer.key().as_ref(), &order.order_id.to_le_bytes()],
+        bump = order.bump,
+    )]
+    pub order: Account<'info, Order>,
+    /// CHECK: SOL escrow PDA for the order
+    #[account(
+        mut,
+        seeds = [b"order_escrow", order.key().as_ref()],
+        bump,
+    )]
+    pub escrow: AccountInfo<'info>,
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+    pub system_program: Program<'info, System>,
 }
 
-impl Campaign {
-    pub const LEN: usize = 1 + 32 + 8 + (4 + 64) + (4 + 256) + 8 + 8 + 32 + 8 + 8 + 1 + 1 + 8 + 1;
+#[derive(Accounts)]
+pub struct PayBalanceToken<'info> {
+    #[account(
+        mut,
+        seeds = [b"order", buyer.key().as_ref(), &order.order_id.to_le_bytes()],
+        bump = order.bump,
+    )]
+    pub order: Account<'info, Order>,
+    #[account(
+        mut,
+        seeds = [b"order_escrow_token", order.key().as_ref()],
+        bump,
+        token::mint = order.currency_mint,
+        token::authority = escrow_token,
+    )]
+    pub escrow_token: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        constraint = buyer_token.mint == order.currency_mint,
+        constraint = buyer_token.owner == buyer.key(),
+    )]
+    pub buyer_token: Account<'info, TokenAccount>,
+    pub buyer: Signer<'info>,
+    pub token_program: Program<'info, Token>,
 }
 
-#[account]
-pub struct CampaignTier {
-    pub bump: u8,
-    pub campaign: Pubkey,
-    pub tier_id: u8,
-    pub name: String,
-    pub min_amount: u64,
-    pub max_amount: u64,
-    pub benefits: String,
-    pub max_backers: u32,
-    pub current_backers: u32,
+#[derive(Accounts)]
+pub struct ReclaimDepositSol<'info> {
+    #[account(
+        mut,
+        seeds = [b"order", order.buyer.as_ref(), &order.order_id.to_le_bytes()],
+        bump = order.bump,
+    )]
+    pub order: Account<'info, Order>,
+    /// CHECK: SOL escrow PDA for the order
+    #[account(
+        mut,
+        seeds = [b"order_escrow", order.key().as_ref()],
+        bump,
+    )]
+    pub escrow: AccountInfo<'info>,
+    /// CHECK: Buyer receiving the deposit refund
+    #[account(
+        mut,
+        address = order.buyer,
+    )]
+    pub buyer: AccountInfo<'info>,
+    pub caller: Signer<'info>,
+    pub system_program: Program<'info, System>,
 }
 
-impl CampaignTier {
-    pub const LEN: usize = 1 + 32 + 1 + (4 + 32) + 8 + 8 + (4 + 256) + 4 + 4;
+#[derive(Accounts)]
+pub struct ReclaimDepositToken<'info> {
+    #[account(
+        mut,
+        seeds = [b"order", order.buyer.as_ref(), &order.order_id.to_le_bytes()],
+        bump = order.bump,
+    )]
+    pub order: Account<'info, Order>,
+    #[account(
+        mut,
+        seeds = [b"order_escrow_token", order.key().as_ref()],
+        bump,
+        token::mint = order.currency_mint,
+        token::authority = escrow_token,
+    )]
+    pub escrow_token: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        constraint = buyer_token.mint == order.currency_mint,
+        constraint = buyer_token.owner == order.buyer,
+    )]
+    pub buyer_token: Account<'info, TokenAccount>,
+    /// CHECK: Buyer receiving the escrow account rent
+    #[account(
+        mut,
+        address = order.buyer,
+    )]
+    pub buyer: AccountInfo<'info>,
+    pub caller: Signer<'info>,
+    pub token_program: Program<'info, Token>,
 }
 
-#[account]
-pub struct Backing {
-    pub bump: u8,
-    pub backer: Pubkey,
-    pub campaign: Pubkey,
-    pub tier_id: u8,
-    pub amount: u64,
-    pub backed_at: i64,
-    pub is_refunded: bool,
+#[derive(Accounts)]
+pub struct ForfeitDepositSol<'info> {
+    #[account(
+        mut,
+        seeds = [b"order", order.buyer.as_ref(), &order.order_id.to_le_bytes()],
+        bump = order.bump,
+    )]
+    pub order: Account<'info, Order>,
+    /// CHECK: SOL escrow PDA for the order
+    #[account(
+        mut,
+        seeds = [b"order_escrow", order.key().as_ref()],
+        bump,
+    )]
+    pub escrow: AccountInfo<'info>,
+    #[account(mut)]
+    pub farmer: Signer<'info>,
+    pub system_program: Program<'info, System>,
 }
 
-impl Backing {
-    pub const LEN: usize = 1 + 32 + 32 + 1 + 8 + 8 + 1;
+#[derive(Accounts)]
+pub struct ForfeitDepositToken<'info> {
+    #[account(
+        mut,
+        seeds = [b"order", order.buyer.as_ref(), &order.order_id.to_le_bytes()],
+        bump = order.bump,
+    )]
+    pub order: Account<'info, Order>,
+    #[account(
+        mut,
+        seeds = [b"order_escrow_token", order.key().as_ref()],
+        bump,
+        token::mint = order.currency_mint,
+        token::authority = escrow_token,
+    )]
+    pub escrow_token: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        constraint = farmer_token.mint == order.currency_mint,
+        constraint = farmer_token.owner == order.farmer,
+    )]
+    pub farmer_token: Account<'info, TokenAccount>,
+    /// CHECK: Buyer receiving the escrow account rent
+    #[account(
+        mut,
+        address = order.buyer,
+    )]
+    pub buyer: AccountInfo<'info>,
+    pub farmer: Signer<'info>,
+    pub token_program: Program<'info, Token>,
 }
 
-// ==================== CONTEXT STRUCTS ====================
-
 #[derive(Accounts)]
-pub struct InitializeConfig<'info> {
+#[instruction(subscription_id: u64)]
+pub struct SubscribeSol<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        seeds = [b"product", product.farmer.as_ref(), &product.product_id.to_le_bytes()],
+        bump = product.bump,
+    )]
+    pub product: Account<'info, Product>,
     #[account(
         init,
-        seeds = [b"config", authority.key().as_ref()],
+        seeds = [b"subscription", buyer.key().as_ref(), &subscription_id.to_le_bytes()],
         bump,
-        payer = authority,
-        space = 8 + Config::LEN
+        payer = buyer,
+        space = 8 + Subscription::LEN
     )]
-    pub config: Account<'info, Config>,
+    pub subscription: Account<'info, Subscription>,
+    /// CHECK: SOL escrow PDA for the subscription
+    #[account(
+        mut,
+        seeds = [b"subscription_escrow", subscription.key().as_ref()],
+        bump,
+    )]
+    pub escrow: AccountInfo<'info>,
     #[account(mut)]
-    pub authority: Signer<'info>,
+    pub buyer: Signer<'info>,
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-#[instruction(campaign_id: u64)]
-pub struct CreateCampaign<'info> {
+#[instruction(subscription_id: u64)]
+pub struct SubscribeToken<'info> {
     #[account(
-        mut,
-        seeds = [b"config", config.authority.as_ref()],
+        seeds = [b"config"],
         bump = config.bump,
     )]
     pub config: Account<'info, Config>,
+    #[account(
+        seeds = [b"product", product.farmer.as_ref(), &product.product_id.to_le_bytes()],
+        bump = product.bump,
+    )]
+    pub product: Account<'info, Product>,
     #[account(
         init,
-        seeds = [b"campaign", farmer.key().as_ref(), &campaign_id.to_le_bytes()],
+        seeds = [b"subscription", buyer.key().as_ref(), &subscription_id.to_le_bytes()],
         bump,
-        payer = farmer,
-        space = 8 + Campaign::LEN
+        payer = buyer,
+        space = 8 + Subscription::LEN
     )]
-    pub campaign: Account<'info, Campaign>,
-    /// CHECK: SOL vault PDA for the campaign
+    pub subscription: Account<'info, Subscription>,
     #[account(
-        mut,
-        seeds = [b"vault", farmer.key().as_ref(), &campaign_id.to_le_bytes()],
+        init,
+        seeds = [b"subscription_escrow_token", subscription.key().as_ref()],
         bump,
+        payer = buyer,
+        token::mint = currency_mint,
+        token::authority = escrow_token,
     )]
-    pub vault: AccountInfo<'info>,
+    pub escrow_token: Account<'info, TokenAccount>,
     pub currency_mint: Account<'info, Mint>,
+    #[account(
+        mut,
+        constraint = buyer_token.mint == currency_mint.key(),
+        constraint = buyer_token.owner == buyer.key(),
+    )]
+    pub buyer_token: Account<'info, TokenAccount>,
     #[account(mut)]
-    pub farmer: Signer<'info>,
+    pub buyer: Signer<'info>,
+    pub token_program: Program<'info, Token>,
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-#[instruction(tier_id: u8)]
-pub struct CreateTier<'info> {
+pub struct SettlePeriodSol<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
     #[account(
         mut,
-        seeds = [b"campaign", farmer.key().as_ref(), &campaign.campaign_id.to_le_bytes()],
-        bump = campaign.bump,
+        seeds = [b"subscription", subscription.buyer.as_ref(), &subscription.subscription_id.to_le_bytes()],
+        bump = subscription.bump,
     )]
-    pub campaign: Account<'info, Campaign>,
+    pub subscription: Account<'info, Subscription>,
+    /// CHECK: SOL escrow PDA for the subscription
     #[account(
-        init,
-        seeds = [b"tier", campaign.key().as_ref(), &[tier_id]],
+        mut,
+        seeds = [b"subscription_escrow", subscription.key().as_ref()],
+        bump,
+    )]
+    pub escrow: AccountInfo<'info>,
+    /// CHECK: Farmer receiving the period payment
+    #[account(
+        mut,
+        address = subscription.farmer,
+    )]
+    pub farmer: AccountInfo<'info>,
+    /// CHECK: Treasury account for platform fees
+    #[account(
+        mut,
+        address = config.treasury @ ErrorCode::InvalidTreasury,
+    )]
+    pub treasury: AccountInfo<'info>,
+    pub caller: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SettlePeriodToken<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        mut,
+        seeds = [b"subscription", subscription.buyer.as_ref(), &subscription.subscription_id.to_le_bytes()],
+        bump = subscription.bump,
+    )]
+    pub subscription: Account<'info, Subscription>,
+    #[account(
+        mut,
+        seeds = [b"subscription_escrow_token", subscription.key().as_ref()],
+        bump,
+        token::mint = subscription.currency_mint,
+        token::authority = escrow_token,
+    )]
+    pub escrow_token: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        constraint = farmer_token.mint == subscription.currency_mint,
+        constraint = farmer_token.owner == subscription.farmer,
+    )]
+    pub farmer_token: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        constraint = treasury_token.mint == subscription.currency_mint,
+        constraint = treasury_token.owner == config.treasury @ ErrorCode::InvalidTreasury,
+    )]
+    pub treasury_token: Account<'info, TokenAccount>,
+    pub caller: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct CancelSubscriptionSol<'info> {
+    #[account(
+        mut,
+        seeds = [b"subscription", buyer.key().as_ref(), &subscription.subscription_id.to_le_bytes()],
+        bump = subscription.bump,
+    )]
+    pub subscription: Account<'info, Subscription>,
+    /// CHECK: SOL escrow PDA for the subscription
+    #[account(
+        mut,
+        seeds = [b"subscription_escrow", subscription.key().as_ref()],
         bump,
-        payer = farmer,
-        space = 8 + CampaignTier::LEN
     )]
-    pub tier: Account<'info, CampaignTier>,
+    pub escrow: AccountInfo<'info>,
     #[account(mut)]
-    pub farmer: Signer<'info>,
+    pub buyer: Signer<'info>,
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-#[instruction(tier_id: u8)]
-pub struct BackCampaignSol<'info> {
+pub struct CancelSubscriptionToken<'info> {
     #[account(
         mut,
-        seeds = [b"campaign", campaign.farmer.as_ref(), &campaign.campaign_id.to_le_bytes()],
-        bump = campaign.bump,
+        seeds = [b"subscription", buyer.key().as_ref(), &subscription.subscription_id.to_le_bytes()],
+        bump = subscription.bump,
     )]
-    pub campaign: Account<'info, Campaign>,
+    pub subscription: Account<'info, Subscription>,
     #[account(
         mut,
-        seeds = [b"tier", campaign.key().as_ref(), &[tier_id]],
-        bump = tier.bump,
+        seeds = [b"subscription_escrow_token", subscription.key().as_ref()],
+        bump,
+        token::mint = subscription.currency_mint,
+        token::authority = escrow_token,
     )]
-    pub tier: Account<'info, CampaignTier>,
-    /// CHECK: SOL vault PDA
+    pub escrow_token: Account<'info, TokenAccount>,
     #[account(
         mut,
-        seeds = [b"vault", campaign.farmer.as_ref(), &campaign.campaign_id.to_le_bytes()],
-        bump,
+        constraint = buyer_token.mint == subscription.currency_mint,
+        constraint = buyer_token.owner == buyer.key(),
     )]
-    pub vault: AccountInfo<'info>,
+    pub buyer_token: Account<'info, TokenAccount>,
+    pub buyer: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct CheckoutSol<'info> {
     #[account(
-        init,
-        seeds = [b"backing", campaign.key().as_ref(), backer.key().as_ref()],
-        bump,
-        payer = backer,
-        space = 8 + Backing::LEN
+        seeds = [b"config"],
+        bump = config.bump,
     )]
-    pub backing: Account<'info, Backing>,
+    pub config: Account<'info, Config>,
     #[account(mut)]
-    pub backer: Signer<'info>,
+    pub buyer: Signer<'info>,
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-#[instruction(tier_id: u8)]
-pub struct BackCampaignToken<'info> {
-    #[account(
-        mut,
-        seeds = [b"campaign", campaign.farmer.as_ref(), &campaign.campaign_id.to_le_bytes()],
-        bump = campaign.bump,
-    )]
-    pub campaign: Account<'info, Campaign>,
+pub struct CheckoutToken<'info> {
     #[account(
-        mut,
-        seeds = [b"tier", campaign.key().as_ref(), &[tier_id]],
-        bump = tier.bump,
-    )]
-    pub tier: Account<'info, CampaignTier>,
-    #[account(
-        init_if_needed,
-        seeds = [b"vault_token", campaign.farmer.as_ref(), &campaign.campaign_id.to_le_bytes()],
-        bump,
-        payer = backer,
-        token::mint = currency_mint,
-        token::authority = vault_token,
+        seeds = [b"config"],
+        bump = config.bump,
     )]
-    pub vault_token: Account<'info, TokenAccount>,
+    pub config: Account<'info, Config>,
     pub currency_mint: Account<'info, Mint>,
     #[account(
         mut,
-        constraint = backer_token.mint == currency_mint.key(),
-        constraint = backer_token.owner == backer.key(),
-    )]
-    pub backer_token: Account<'info, TokenAccount>,
-    #[account(
-        init,
-        seeds = [b"backing", campaign.key().as_ref(), backer.key().as_ref()],
-        bump,
-        payer = backer,
-        space = 8 + Backing::LEN
+        constraint = buyer_token.mint == currency_mint.key(),
+        constraint = buyer_token.owner == buyer.key(),
     )]
-    pub backing: Account<'info, Backing>,
+    pub buyer_token: Account<'info, TokenAccount>,
     #[account(mut)]
-    pub backer: Signer<'info>,
+    pub buyer: Signer<'info>,
     pub token_program: Program<'info, Token>,
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct FinalizeCampaign<'info> {
+pub struct FulfillReward<'info> {
     #[account(
         mut,
         seeds = [b"campaign", campaign.farmer.as_ref(), &campaign.campaign_id.to_le_bytes()],
         bump = campaign.bump,
     )]
     pub campaign: Account<'info, Campaign>,
+    #[account(
+        mut,
+        seeds = [b"backing", campaign.key().as_ref(), backing.backer.as_ref()],
+        bump = backing.bump,
+    )]
+    pub backing: Account<'info, Backing>,
     pub authority: Signer<'info>,
 }
 
 #[derive(Accounts)]
-pub struct WithdrawFundsSol<'info> {
+pub struct ReleaseHoldbackSol<'info> {
     #[account(
         mut,
-        seeds = [b"config", config.authority.as_ref()],
-        bump = config.bump,
-    )]
-    pub config: Account<'info, Config>,
-    #[account(
-        seeds = [b"campaign", farmer.key().as_ref(), &campaign.campaign_id.to_le_bytes()],
+        seeds = [b"campaign", campaign.farmer.as_ref(), &campaign.campaign_id.to_le_bytes()],
         bump = campaign.bump,
     )]
     pub campaign: Account<'info, Campaign>,
     /// CHECK: SOL vault PDA
     #[account(
         mut,
-        seeds = [b"vault", farmer.key().as_ref(), &campaign.campaign_id.to_le_bytes()],
+        seeds = [b"vault", campaign.farmer.as_ref(), &campaign.campaign_id.to_le_bytes()],
         bump,
     )]
     pub vault: AccountInfo<'info>,
-    /// CHECK: Treasury account for platform fees
-    #[account(mut)]
-    pub treasury: AccountInfo<'info>,
-    #[account(mut)]
-    pub farmer: Signer<'info>,
+    /// CHECK: Destination for the lead farmer's proceeds
+    #[account(
+        mut,
+        address = campaign.payout_wallet @ ErrorCode::InvalidPayoutWallet,
+    )]
+    pub payout_wallet: AccountInfo<'info>,
+    pub authority: Signer<'info>,
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct WithdrawFundsToken<'info> {
+pub struct ReleaseHoldbackToken<'info> {
     #[account(
         mut,
-        seeds = [b"config", config.authority.as_ref()],
-        bump = config.bump,
-    )]
-    pub config: Account<'info, Config>,
-    #[account(
-        seeds = [b"campaign", farmer.key().as_ref(), &campaign.campaign_id.to_le_bytes()],
+        seeds = [b"campaign", campaign.farmer.as_ref(), &campaign.campaign_id.to_le_bytes()],
         bump = campaign.bump,
     )]
     pub campaign: Account<'info, Campaign>,
     #[account(
         mut,
-        seeds = [b"vault_token", farmer.key().as_ref(), &campaign.campaign_id.to_le_bytes()],
+        seeds = [b"vault_token", campaign.farmer.as_ref(), &campaign.campaign_id.to_le_bytes()],
         bump,
         token::mint = currency_mint,
         token::authority = vault_token,
@@ -747,77 +6569,345 @@ pub struct WithdrawFundsToken<'info> {
     pub currency_mint: Account<'info, Mint>,
     #[account(
         mut,
-        constraint = farmer_token.mint == currency_mint.key(),
-        constraint = farmer_token.owner == farmer.key(),
+        constraint = payout_token.mint == currency_mint.key(),
+        constraint = payout_token.owner == campaign.payout_wallet @ ErrorCode::InvalidPayoutWallet,
     )]
-    pub farmer_token: Account<'info, TokenAccount>,
-    #[account(
-        mut,
-        constraint = treasury_token.mint == currency_mint.key(),
-    )]
-    pub treasury_token: Account<'info, TokenAccount>,
-    #[account(mut)]
-    pub farmer: Signer<'info>,
+    pub payout_token: Account<'info, TokenAccount>,
+    pub authority: Signer<'info>,
     pub token_program: Program<'info, Token>,
-    pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct ClaimRefundSol<'info> {
+pub struct ReviewOrder<'info> {
     #[account(
-        seeds = [b"campaign", campaign.farmer.as_ref(), &campaign.campaign_id.to_le_bytes()],
-        bump = campaign.bump,
+        seeds = [b"order", reviewer.key().as_ref(), &order.order_id.to_le_bytes()],
+        bump = order.bump,
     )]
-    pub campaign: Account<'info, Campaign>,
-    /// CHECK: SOL vault PDA
+    pub order: Account<'info, Order>,
     #[account(
         mut,
-        seeds = [b"vault", campaign.farmer.as_ref(), &campaign.campaign_id.to_le_bytes()],
-        bump,
+        seeds = [b"farmer_profile", order.farmer.as_ref()],
+        bump = farmer_profile.bump,
     )]
-    pub vault: AccountInfo<'info>,
+    pub farmer_profile: Account<'info, FarmerProfile>,
     #[account(
-        mut,
-        seeds = [b"backing", campaign.key().as_ref(), backer.key().as_ref()],
-        bump = backing.bump,
+        init,
+        seeds = [b"review", order.key().as_ref(), reviewer.key().as_ref()],
+        bump,
+        payer = reviewer,
+        space = 8 + Review::LEN
     )]
-    pub backing: Account<'info, Backing>,
+    pub review: Account<'info, Review>,
     #[account(mut)]
-    pub backer: Signer<'info>,
+    pub reviewer: Signer<'info>,
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct ClaimRefundToken<'info> {
+pub struct ReviewBacking<'info> {
     #[account(
         seeds = [b"campaign", campaign.farmer.as_ref(), &campaign.campaign_id.to_le_bytes()],
         bump = campaign.bump,
     )]
     pub campaign: Account<'info, Campaign>,
+    #[account(
+        seeds = [b"backing", campaign.key().as_ref(), reviewer.key().as_ref()],
+        bump = backing.bump,
+    )]
+    pub backing: Account<'info, Backing>,
     #[account(
         mut,
-        seeds = [b"vault_token", campaign.farmer.as_ref(), &campaign.campaign_id.to_le_bytes()],
+        seeds = [b"farmer_profile", campaign.farmer.as_ref()],
+        bump = farmer_profile.bump,
+    )]
+    pub farmer_profile: Account<'info, FarmerProfile>,
+    #[account(
+        init,
+        seeds = [b"review", backing.key().as_ref(), reviewer.key().as_ref()],
         bump,
-        token::mint = currency_mint,
-        token::authority = vault_token,
+        payer = reviewer,
+        space = 8 + Review::LEN
     )]
-    pub vault_token: Account<'info, TokenAccount>,
-    pub currency_mint: Account<'info, Mint>,
+    pub review: Account<'info, Review>,
+    #[account(mut)]
+    pub reviewer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateCampaign<'info> {
     #[account(
         mut,
-        constraint = backer_token.mint == currency_mint.key(),
-        constraint = backer_token.owner == backer.key(),
+        seeds = [b"campaign", campaign.farmer.as_ref(), &campaign.campaign_id.to_le_bytes()],
+        bump = campaign.bump,
     )]
-    pub backer_token: Account<'info, TokenAccount>,
+    pub campaign: Account<'info, Campaign>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptCampaignAuthority<'info> {
     #[account(
         mut,
-        seeds = [b"backing", campaign.key().as_ref(), backer.key().as_ref()],
-        bump = backing.bump,
+        seeds = [b"campaign", campaign.farmer.as_ref(), &campaign.campaign_id.to_le_bytes()],
+        bump = campaign.bump,
     )]
-    pub backing: Account<'info, Backing>,
-    #[account(mut)]
-    pub backer: Signer<'info>,
-    pub token_program: Program<'info, Token>,
+    pub campaign: Account<'info, Campaign>,
+    pub new_authority: Signer<'info>,
+}
+
+// ==================== EVENTS ====================
+
+#[event]
+pub struct TierCreated {
+    pub campaign: Pubkey,
+    pub tier: Pubkey,
+    pub tier_id: u8,
+    pub min_amount: u64,
+    pub max_amount: u64,
+    pub max_units: u32,
+    pub available_from: i64,
+    pub available_until: i64,
+}
+
+#[event]
+pub struct CampaignBacked {
+    pub campaign: Pubkey,
+    pub backer: Pubkey,
+    pub tier_id: u8,
+    pub amount: u64,
+    pub quantity: u32,
+    pub referrer: Option<Pubkey>,
+    pub tier_backers: u32,
+    pub tier_units_sold: u32,
+    pub tier_available_until: i64,
+    pub backed_at: i64,
+}
+
+#[event]
+pub struct RoleGranted {
+    pub config: Pubkey,
+    pub member: Pubkey,
+    pub kind: RoleKind,
+}
+
+#[event]
+pub struct RoleRevoked {
+    pub config: Pubkey,
+    pub member: Pubkey,
+    pub kind: RoleKind,
+}
+
+#[event]
+pub struct FarmerAttested {
+    pub farmer: Pubkey,
+    pub kind: AttestationKind,
+    pub issuer: Pubkey,
+    pub expires_at: i64,
+    pub evidence_hash: [u8; 32],
+}
+
+#[event]
+pub struct AttestationRevoked {
+    pub farmer: Pubkey,
+    pub kind: AttestationKind,
+    pub revoked_by: Pubkey,
+}
+
+#[event]
+pub struct ProductListed {
+    pub product: Pubkey,
+    pub farmer: Pubkey,
+    pub price: u64,
+    pub currency_mint: Pubkey,
+    pub stock: u32,
+}
+
+#[event]
+pub struct ProductDelisted {
+    pub product: Pubkey,
+    pub farmer: Pubkey,
+}
+
+#[event]
+pub struct OrderPlaced {
+    pub order: Pubkey,
+    pub buyer: Pubkey,
+    pub farmer: Pubkey,
+    pub product: Pubkey,
+    pub quantity: u32,
+    pub total_amount: u64,
+}
+
+#[event]
+pub struct OrderAccepted {
+    pub order: Pubkey,
+    pub farmer: Pubkey,
+}
+
+#[event]
+pub struct OrderAdvanced {
+    pub order: Pubkey,
+    pub farmer: Pubkey,
+    pub status: OrderStatus,
+    pub evidence_hash: Option<[u8; 32]>,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct OrderCompleted {
+    pub order: Pubkey,
+    pub farmer: Pubkey,
+    pub farmer_amount: u64,
+    pub fee: u64,
+}
+
+#[event]
+pub struct OrderCancelled {
+    pub order: Pubkey,
+    pub buyer: Pubkey,
+}
+
+#[event]
+pub struct DisputeOpened {
+    pub dispute: Pubkey,
+    pub subject: Pubkey,
+    pub kind: DisputeKind,
+    pub claimant: Pubkey,
+    pub deadline: i64,
+}
+
+#[event]
+pub struct DisputeResolved {
+    pub dispute: Pubkey,
+    pub subject: Pubkey,
+    pub claimant_bps: u16,
+    pub claimant_amount: u64,
+    pub respondent_amount: u64,
+    pub defaulted: bool,
+}
+
+#[event]
+pub struct PreorderPlaced {
+    pub order: Pubkey,
+    pub buyer: Pubkey,
+    pub product: Pubkey,
+    pub quantity: u32,
+    pub deposit_amount: u64,
+    pub harvest_deadline: i64,
+}
+
+#[event]
+pub struct BalancePaymentOpened {
+    pub order: Pubkey,
+    pub farmer: Pubkey,
+    pub balance: u64,
+    pub balance_deadline: i64,
+}
+
+#[event]
+pub struct BalancePaid {
+    pub order: Pubkey,
+    pub buyer: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct DepositReclaimed {
+    pub order: Pubkey,
+    pub buyer: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct DepositForfeited {
+    pub order: Pubkey,
+    pub farmer: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct SubscriptionCreated {
+    pub subscription: Pubkey,
+    pub buyer: Pubkey,
+    pub farmer: Pubkey,
+    pub price_per_period: u64,
+    pub periods: u32,
+}
+
+#[event]
+pub struct PeriodSettled {
+    pub subscription: Pubkey,
+    pub farmer: Pubkey,
+    pub farmer_amount: u64,
+    pub fee: u64,
+    pub remaining_periods: u32,
+}
+
+#[event]
+pub struct SubscriptionCancelled {
+    pub subscription: Pubkey,
+    pub buyer: Pubkey,
+    pub refund_amount: u64,
+}
+
+#[event]
+pub struct CartOrderPlaced {
+    pub order: Pubkey,
+    pub buyer: Pubkey,
+    pub farmer: Pubkey,
+    pub item_count: u8,
+    pub total_amount: u64,
+}
+
+#[event]
+pub struct ReviewSubmitted {
+    pub review: Pubkey,
+    pub farmer: Pubkey,
+    pub reviewer: Pubkey,
+    pub kind: ReviewKind,
+    pub rating: u8,
+}
+
+#[event]
+pub struct CampaignFeeSet {
+    pub campaign: Pubkey,
+    pub fee_bps: u16,
+    pub set_by: Pubkey,
+}
+
+#[event]
+pub struct CoOwnersSet {
+    pub campaign: Pubkey,
+    pub co_owners: u8,
+    pub threshold: u8,
+}
+
+#[event]
+pub struct CampaignAuthorityProposed {
+    pub campaign: Pubkey,
+    pub authority: Pubkey,
+    pub new_authority: Pubkey,
+}
+
+#[event]
+pub struct CampaignAuthorityTransferred {
+    pub campaign: Pubkey,
+    pub previous_authority: Pubkey,
+    pub new_authority: Pubkey,
+}
+
+#[event]
+pub struct PayoutChangeRequested {
+    pub campaign: Pubkey,
+    pub payout_wallet: Pubkey,
+    pub effective_at: i64,
+}
+
+#[event]
+pub struct PayoutWalletChanged {
+    pub campaign: Pubkey,
+    pub payout_wallet: Pubkey,
 }
 
 // ==================== ERROR CODES ====================
@@ -878,4 +6968,150 @@ pub enum ErrorCode {
     AlreadyRefunded,
     #[msg("No funds to withdraw")]
     NoFundsToWithdraw,
+    #[msg("Funds already withdrawn")]
+    AlreadyWithdrawn,
+    #[msg("Backing is not refunded and campaign funds are not withdrawn")]
+    BackingNotSettled,
+    #[msg("Campaign funds are not settled")]
+    CampaignNotSettled,
+    #[msg("Campaign still has open tiers")]
+    OpenTiersRemain,
+    #[msg("Campaign still has open backings")]
+    OpenBackingsRemain,
+    #[msg("Invalid tier availability window")]
+    InvalidTierWindow,
+    #[msg("Tier is not available yet")]
+    TierNotYetAvailable,
+    #[msg("Tier is no longer available")]
+    TierExpired,
+    #[msg("Quantity must be at least one")]
+    InvalidQuantity,
+    #[msg("Backer is not on the campaign allow-list")]
+    NotAllowlisted,
+    #[msg("Allow-list cannot change once the campaign has backers")]
+    AllowlistLocked,
+    #[msg("Invalid referral percentage")]
+    InvalidReferralBps,
+    #[msg("Referral share cannot change once the campaign has backers")]
+    ReferralLocked,
+    #[msg("Backers cannot refer themselves")]
+    SelfReferral,
+    #[msg("Invalid referrer account")]
+    InvalidReferrer,
+    #[msg("Referral reward already paid")]
+    ReferralAlreadyPaid,
+    #[msg("Referral rewards are still unclaimed")]
+    ReferralsUnclaimed,
+    #[msg("Farm name must be 1-64 chars")]
+    FarmNameTooLong,
+    #[msg("Metadata URI too long (max 200 chars)")]
+    MetadataUriTooLong,
+    #[msg("Invalid geohash")]
+    InvalidGeohash,
+    #[msg("Invalid expiry")]
+    InvalidExpiry,
+    #[msg("A verification attestation is required")]
+    VerificationRequired,
+    #[msg("Attestation has been revoked")]
+    AttestationRevoked,
+    #[msg("Attestation has expired")]
+    AttestationExpired,
+    #[msg("Signer lacks the required role")]
+    MissingRole,
+    #[msg("Treasury does not match config")]
+    InvalidTreasury,
+    #[msg("Unit too long (max 16 chars)")]
+    UnitTooLong,
+    #[msg("Product is not listed")]
+    ProductNotListed,
+    #[msg("Insufficient stock")]
+    InsufficientStock,
+    #[msg("Order is not in the required status")]
+    InvalidOrderStatus,
+    #[msg("Orders can only advance to a later stage")]
+    InvalidOrderTransition,
+    #[msg("Dispute is already resolved")]
+    DisputeClosed,
+    #[msg("Basis points must not exceed 10000")]
+    InvalidBps,
+    #[msg("Campaign has open disputes")]
+    DisputesPending,
+    #[msg("Product does not accept pre-orders")]
+    PreordersDisabled,
+    #[msg("Harvest date has passed")]
+    HarvestDatePassed,
+    #[msg("Harvest window is still open")]
+    HarvestWindowOpen,
+    #[msg("Subscription needs at least one period of at least one day")]
+    InvalidSubscriptionTerms,
+    #[msg("Subscription is not active")]
+    SubscriptionInactive,
+    #[msg("Current period has not elapsed yet")]
+    PeriodNotElapsed,
+    #[msg("Cart lines do not match the accounts provided")]
+    InvalidCart,
+    #[msg("Cart lines must be grouped by farmer")]
+    CartNotGrouped,
+    #[msg("Product price is above the quoted maximum")]
+    PriceChanged,
+    #[msg("Rating must be between 1 and 5")]
+    InvalidRating,
+    #[msg("Reward has not been fulfilled")]
+    RewardNotFulfilled,
+    #[msg("Loyalty thresholds must be ascending")]
+    InvalidLoyaltyThresholds,
+    #[msg("Loyalty level too low for this tier")]
+    LoyaltyLevelTooLow,
+    #[msg("Fee splits must have positive shares totalling at most 10000 bps")]
+    InvalidFeeSplits,
+    #[msg("Fee recipient accounts missing")]
+    FeeRecipientsMissing,
+    #[msg("Fee recipient does not match the split table")]
+    InvalidFeeRecipient,
+    #[msg("Invalid co-owner list")]
+    InvalidCoOwners,
+    #[msg("Co-owners cannot change once the campaign has backers")]
+    CoOwnersLocked,
+    #[msg("Not enough co-owner signatures")]
+    CoOwnerApprovalRequired,
+    #[msg("Co-owner recipient accounts missing")]
+    CoOwnerRecipientsMissing,
+    #[msg("Co-owner recipient does not match the campaign")]
+    InvalidCoOwnerRecipient,
+    #[msg("Invalid campaign authority")]
+    InvalidAuthority,
+    #[msg("Invalid payout wallet")]
+    InvalidPayoutWallet,
+    #[msg("No payout wallet change is pending")]
+    NoPendingPayoutChange,
+    #[msg("Payout wallet change is still timelocked")]
+    PayoutChangeTimelocked,
+    #[msg("Too many tags")]
+    TooManyTags,
+    #[msg("Tags must be 1 to 16 characters")]
+    InvalidTag,
+    #[msg("Campaign vault still holds unclaimed refunds")]
+    RefundsOutstanding,
+    #[msg("Attestation belongs to another issuer")]
+    AttestationIssuerMismatch,
+    #[msg("Reward has already been fulfilled")]
+    RewardFulfilled,
+    #[msg("Campaign holdback has already been released")]
+    HoldbackReleased,
+    #[msg("Holdback is locked until rewards are delivered or the release period ends")]
+    HoldbackLocked,
+    #[msg("Campaign vault still holds the reward holdback")]
+    HoldbackOutstanding,
+    #[msg("Balance payment is overdue")]
+    BalanceOverdue,
+    #[msg("Balance payment window is still open")]
+    BalanceWindowOpen,
+    #[msg("Campaign fee cannot be raised once it has backers")]
+    FeeIncreaseLocked,
+    #[msg("No campaign authority transfer is pending")]
+    NoPendingAuthority,
+    #[msg("Token vault accounts are missing")]
+    TokenAccountsMissing,
+    #[msg("Loyalty gates are only available on SOL campaigns")]
+    LoyaltyGateRequiresSol,
 }
\ No newline at end of file
@@ -0,0 +1,122 @@
+// Inner nodes hash their children in sorted order, so a proof is just the sibling hashes
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hashv;
+
+const LEAF_PREFIX: &[u8] = &[0];
+const NODE_PREFIX: &[u8] = &[1];
+
+pub fn leaf_hash(member: &Pubkey) -> [u8; 32] {
+    hashv(&[LEAF_PREFIX, member.as_ref()]).to_bytes()
+}
+
+pub fn node_hash(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+    let (left, right) = if a <= b { (a, b) } else { (b, a) };
+    hashv(&[NODE_PREFIX, left, right]).to_bytes()
+}
+
+pub fn verify(root: &[u8; 32], member: &Pubkey, proof: &[[u8; 32]]) -> bool {
+    let computed = proof
+        .iter()
+        .fold(leaf_hash(member), |acc, sibling| node_hash(&acc, sibling));
+    computed == *root
+}
+
+// Client-side, for building allow-list roots and proofs
+pub struct MerkleTree {
+    // layers[0] holds the leaves, the last layer holds the root
+    layers: Vec<Vec<[u8; 32]>>,
+}
+
+impl MerkleTree {
+    pub fn new(members: &[Pubkey]) -> Self {
+        let mut leaves: Vec<[u8; 32]> = members.iter().map(leaf_hash).collect();
+        leaves.sort_unstable();
+        leaves.dedup();
+
+        let mut layers = vec![leaves];
+        while let Some(layer) = layers.last().filter(|layer| layer.len() > 1) {
+            let next = layer
+                .chunks(2)
+                .map(|pair| match pair {
+                    [a, b] => node_hash(a, b),
+                    // An odd node is promoted to the next layer unchanged
+                    [a] => *a,
+                    _ => unreachable!(),
+                })
+                .collect();
+            layers.push(next);
+        }
+
+        Self { layers }
+    }
+
+    // None for an empty member list
+    pub fn root(&self) -> Option<[u8; 32]> {
+        self.layers.last().and_then(|layer| layer.first().copied())
+    }
+
+    pub fn proof(&self, member: &Pubkey) -> Option<Vec<[u8; 32]>> {
+        let leaf = leaf_hash(member);
+        let mut index = self.layers[0].binary_search(&leaf).ok()?;
+        let mut proof = Vec::new();
+
+        for layer in &self.layers[..self.layers.len() - 1] {
+            let sibling = index ^ 1;
+            if let Some(hash) = layer.get(sibling) {
+                proof.push(*hash);
+            }
+            index /= 2;
+        }
+
+        Some(proof)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_tree(size: usize) {
+        let members: Vec<Pubkey> = (0..size).map(|_| Pubkey::new_unique()).collect();
+        let tree = MerkleTree::new(&members);
+        let root = tree.root().unwrap();
+
+        for member in &members {
+            let proof = tree.proof(member).unwrap();
+            assert!(verify(&root, member, &proof), "member of a {size}-leaf tree");
+        }
+
+        let outsider = Pubkey::new_unique();
+        assert!(tree.proof(&outsider).is_none());
+        for member in &members {
+            let proof = tree.proof(member).unwrap();
+            assert!(!verify(&root, &outsider, &proof), "outsider of a {size}-leaf tree");
+        }
+    }
+
+    #[test]
+    fn single_member() {
+        assert_tree(1);
+    }
+
+    #[test]
+    fn two_members() {
+        assert_tree(2);
+    }
+
+    #[test]
+    fn three_members() {
+        assert_tree(3);
+    }
+
+    #[test]
+    fn five_members() {
+        assert_tree(5);
+    }
+
+    #[test]
+    fn empty_tree_has_no_root() {
+        assert!(MerkleTree::new(&[]).root().is_none());
+    }
+}
@@ -0,0 +1,788 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, BN } from "@coral-xyz/anchor";
+import { Workspace } from "../target/types/workspace";
+import { expect } from "chai";
+import {
+  PublicKey,
+  SystemProgram,
+  Keypair,
+  LAMPORTS_PER_SOL,
+  Transaction,
+} from "@solana/web3.js";
+import {
+  TOKEN_PROGRAM_ID,
+  NATIVE_MINT,
+  MINT_SIZE,
+  ACCOUNT_SIZE,
+  MintLayout,
+  AccountLayout,
+  createInitializeMint2Instruction,
+  createInitializeAccount3Instruction,
+  createMintToInstruction,
+} from "@solana/spl-token";
+import { AddedAccount, Clock, ProgramTestContext, start } from "solana-bankrun";
+import { BankrunProvider } from "anchor-bankrun";
+import { readFileSync } from "fs";
+
+// Settlement paths only open once a campaign has ended, so these run on bankrun
+// where the clock can be moved forward
+const idl = JSON.parse(readFileSync("target/idl/workspace.json", "utf8"));
+const PROGRAM_ID = new PublicKey(idl.address);
+const BPF_LOADER_UPGRADEABLE = new PublicKey("BPFLoaderUpgradeab1e11111111111111111111111");
+const DAY = 86400;
+const CLOSE_GRACE_PERIOD = 90 * DAY;
+
+// Program and program data accounts of an upgradeable deploy of target/deploy/workspace.so
+const upgradeableProgram = (upgradeAuthority: PublicKey): AddedAccount[] => {
+  const [programData] = PublicKey.findProgramAddressSync(
+    [PROGRAM_ID.toBuffer()],
+    BPF_LOADER_UPGRADEABLE
+  );
+  const elf = readFileSync("target/deploy/workspace.so");
+
+  const programAccount = Buffer.alloc(36);
+  programAccount.writeUInt32LE(2, 0);
+  programData.toBuffer().copy(programAccount, 4);
+
+  const programDataAccount = Buffer.alloc(45 + elf.length);
+  programDataAccount.writeUInt32LE(3, 0);
+  programDataAccount.writeUInt8(1, 12);
+  upgradeAuthority.toBuffer().copy(programDataAccount, 13);
+  elf.copy(programDataAccount, 45);
+
+  return [
+    {
+      address: PROGRAM_ID,
+      info: {
+        lamports: LAMPORTS_PER_SOL,
+        data: programAccount,
+        owner: BPF_LOADER_UPGRADEABLE,
+        executable: true,
+      },
+    },
+    {
+      address: programData,
+      info: {
+        lamports: 100 * LAMPORTS_PER_SOL,
+        data: programDataAccount,
+        owner: BPF_LOADER_UPGRADEABLE,
+        executable: false,
+      },
+    },
+  ];
+};
+
+// The wrapped SOL mint SOL-priced campaigns and products are denominated in
+const nativeMint = (): AddedAccount => {
+  const data = Buffer.alloc(MINT_SIZE);
+  MintLayout.encode(
+    {
+      mintAuthorityOption: 0,
+      mintAuthority: PublicKey.default,
+      supply: BigInt(0),
+      decimals: 9,
+      isInitialized: true,
+      freezeAuthorityOption: 0,
+      freezeAuthority: PublicKey.default,
+    },
+    data
+  );
+  return {
+    address: NATIVE_MINT,
+    info: { lamports: LAMPORTS_PER_SOL, data, owner: TOKEN_PROGRAM_ID, executable: false },
+  };
+};
+
+const funded = (wallet: Keypair): AddedAccount => ({
+  address: wallet.publicKey,
+  info: { lamports: 1000 * LAMPORTS_PER_SOL, data: Buffer.alloc(0), owner: SystemProgram.programId, executable: false },
+});
+
+describe("Campaign Settlement", () => {
+  let context: ProgramTestContext;
+  let program: Program<Workspace>;
+
+  const admin = Keypair.generate();
+  const farmer = Keypair.generate();
+  const backer1 = Keypair.generate();
+  const backer2 = Keypair.generate();
+  const payoutWallet = Keypair.generate();
+  const referrerWallet = Keypair.generate();
+
+  const pda = (...seeds: Buffer[]): PublicKey =>
+    PublicKey.findProgramAddressSync(seeds, PROGRAM_ID)[0];
+  const u64 = (value: BN | number): Buffer => new BN(value).toArrayLike(Buffer, "le", 8);
+  const pageSeed = (count: number): Buffer =>
+    new BN(Math.floor(count / 32)).toArrayLike(Buffer, "le", 4);
+
+  const configPDA = pda(Buffer.from("config"));
+  const farmerProfilePDA = pda(Buffer.from("farmer_profile"), farmer.publicKey.toBuffer());
+  const customerStatsFor = (wallet: PublicKey) =>
+    pda(Buffer.from("customer_stats"), wallet.toBuffer());
+
+  const balanceOf = async (address: PublicKey): Promise<number> =>
+    Number(await context.banksClient.getBalance(address));
+  const exists = async (address: PublicKey): Promise<boolean> =>
+    (await context.banksClient.getAccount(address)) !== null;
+  const tokenBalanceOf = async (address: PublicKey): Promise<number> =>
+    Number(AccountLayout.decode((await context.banksClient.getAccount(address)).data).amount);
+
+  const send = async (signers: Keypair[], ...instructions: anchor.web3.TransactionInstruction[]) => {
+    const tx = new Transaction().add(...instructions);
+    tx.recentBlockhash = context.lastBlockhash;
+    tx.feePayer = signers[0].publicKey;
+    tx.sign(...signers);
+    await context.banksClient.processTransaction(tx);
+  };
+
+  const warpTo = async (unixTimestamp: number) => {
+    const clock = await context.banksClient.getClock();
+    context.warpToSlot(clock.slot + BigInt(1));
+    context.setClock(
+      new Clock(
+        clock.slot + BigInt(1),
+        clock.epochStartTimestamp,
+        clock.epoch,
+        clock.leaderScheduleEpoch,
+        BigInt(unixTimestamp)
+      )
+    );
+  };
+
+  const createCampaign = async (goal: BN, currencyMint: PublicKey) => {
+    const { nextCampaignId } = await program.account.farmerProfile.fetch(farmerProfilePDA);
+    const campaign = pda(Buffer.from("campaign"), farmer.publicKey.toBuffer(), u64(nextCampaignId));
+    const vault = pda(Buffer.from("vault"), farmer.publicKey.toBuffer(), u64(nextCampaignId));
+    const vaultToken = pda(Buffer.from("vault_token"), farmer.publicKey.toBuffer(), u64(nextCampaignId));
+
+    const categoryIndex = pda(Buffer.from("campaign_category"), Buffer.from([0]), Buffer.from("9q8y"));
+    const index = await program.account.categoryIndex.fetchNullable(categoryIndex);
+    const categoryPage = pda(
+      Buffer.from("category_page"),
+      categoryIndex.toBuffer(),
+      pageSeed(index ? index.count.toNumber() : 0)
+    );
+
+    await program.methods
+      .createCampaign("Settlement", "Campaign that runs to its end", goal, new BN(1), false, { vegetables: {} }, [], "9q8yy")
+      .accounts({
+        config: configPDA,
+        farmerProfile: farmerProfilePDA,
+        campaign,
+        vault,
+        currencyMint,
+        categoryIndex,
+        categoryPage,
+        farmer: farmer.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([farmer])
+      .rpc();
+
+    const tier = pda(Buffer.from("tier"), campaign.toBuffer(), Buffer.from([0]));
+    await program.methods
+      .createTier(0, "Basic Tier", new BN(LAMPORTS_PER_SOL), new BN(0), "Basic benefits", 0, new BN(0), new BN(0), { bronze: {} })
+      .accounts({
+        campaign,
+        tier,
+        authority: farmer.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([farmer])
+      .rpc();
+
+    return { campaign, vault, vaultToken, tier };
+  };
+
+  const indexPagesFor = async (campaign: PublicKey, backer: PublicKey) => {
+    const { backersCount } = await program.account.campaign.fetch(campaign);
+    const stats = await program.account.customerStats.fetchNullable(customerStatsFor(backer));
+    return {
+      campaignBackers: pda(Buffer.from("campaign_backers"), campaign.toBuffer(), pageSeed(backersCount.toNumber())),
+      backerCampaigns: pda(Buffer.from("backer_campaigns"), backer.toBuffer(), pageSeed(stats ? stats.backingsCount : 0)),
+    };
+  };
+
+  const backingFor = (campaign: PublicKey, backer: Keypair) =>
+    pda(Buffer.from("backing"), campaign.toBuffer(), backer.publicKey.toBuffer());
+
+  const backSol = async (
+    accounts: { campaign: PublicKey; vault: PublicKey; tier: PublicKey },
+    backer: Keypair,
+    amount: BN,
+    referrer: PublicKey | null = null
+  ) => {
+    await program.methods
+      .backCampaignSol(0, amount, 1, [])
+      .accounts({
+        config: configPDA,
+        campaign: accounts.campaign,
+        tier: accounts.tier,
+        vault: accounts.vault,
+        backing: backingFor(accounts.campaign, backer),
+        referrer,
+        customerStats: customerStatsFor(backer.publicKey),
+        ...(await indexPagesFor(accounts.campaign, backer.publicKey)),
+        backer: backer.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([backer])
+      .rpc();
+  };
+
+  const finalizeAfterEnd = async (campaign: PublicKey) => {
+    const { endTime } = await program.account.campaign.fetch(campaign);
+    await warpTo(endTime.toNumber() + 1);
+    await program.methods
+      .finalizeCampaign()
+      .accounts({ campaign, authority: farmer.publicKey })
+      .signers([farmer])
+      .rpc();
+  };
+
+  before(async () => {
+    context = await start(
+      [],
+      [
+        ...upgradeableProgram(admin.publicKey),
+        nativeMint(),
+        funded(admin),
+        funded(farmer),
+        funded(backer1),
+        funded(backer2),
+        funded(payoutWallet),
+        funded(referrerWallet),
+      ]
+    );
+    program = new Program<Workspace>(idl, new BankrunProvider(context));
+
+    await program.methods
+      .initializeConfig(250)
+      .accounts({
+        config: configPDA,
+        program: PROGRAM_ID,
+        programData: PublicKey.findProgramAddressSync([PROGRAM_ID.toBuffer()], BPF_LOADER_UPGRADEABLE)[0],
+        authority: admin.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([admin])
+      .rpc();
+
+    await program.methods
+      .createFarmerProfile("Settlement Farm", "", "9q8yy", false, payoutWallet.publicKey)
+      .accounts({
+        farmerProfile: farmerProfilePDA,
+        farmer: farmer.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([farmer])
+      .rpc();
+  });
+
+  describe("Failed SOL Campaign", () => {
+    let accounts: { campaign: PublicKey; vault: PublicKey; vaultToken: PublicKey; tier: PublicKey };
+
+    before(async () => {
+      accounts = await createCampaign(new BN(100 * LAMPORTS_PER_SOL), NATIVE_MINT);
+      await backSol(accounts, backer1, new BN(2 * LAMPORTS_PER_SOL));
+      await backSol(accounts, backer2, new BN(3 * LAMPORTS_PER_SOL));
+      await finalizeAfterEnd(accounts.campaign);
+    });
+
+    it("should refund both backers", async () => {
+      for (const backer of [backer1, backer2]) {
+        await program.methods
+          .claimRefundSol()
+          .accounts({
+            campaign: accounts.campaign,
+            vault: accounts.vault,
+            backing: backingFor(accounts.campaign, backer),
+            customerStats: customerStatsFor(backer.publicKey),
+            backer: backer.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([backer])
+          .rpc();
+      }
+
+      const campaign = await program.account.campaign.fetch(accounts.campaign);
+      expect(campaign.refundedAmount.toNumber()).to.equal(5 * LAMPORTS_PER_SOL);
+      // Only the rent reserve is left behind for close_campaign to sweep
+      const rent = await context.banksClient.getRent();
+      expect(await balanceOf(accounts.vault)).to.equal(Number(rent.minimumBalance(BigInt(0))));
+    });
+
+    it("should close a refunded backing and return its rent", async () => {
+      const backing = backingFor(accounts.campaign, backer1);
+      const rent = await balanceOf(backing);
+      const balanceBefore = await balanceOf(backer1.publicKey);
+
+      await program.methods
+        .closeBacking()
+        .accounts({ campaign: accounts.campaign, backing, backer: backer1.publicKey })
+        .signers([backer1])
+        .rpc();
+
+      expect(await exists(backing)).to.be.false;
+      expect(await balanceOf(backer1.publicKey)).to.equal(balanceBefore + rent);
+      const campaign = await program.account.campaign.fetch(accounts.campaign);
+      expect(campaign.openBackings).to.equal(1);
+    });
+
+    it("should close the tier", async () => {
+      await program.methods
+        .closeTier()
+        .accounts({ campaign: accounts.campaign, tier: accounts.tier, authority: farmer.publicKey })
+        .signers([farmer])
+        .rpc();
+
+      expect(await exists(accounts.tier)).to.be.false;
+    });
+
+    it("should fail to close the campaign while backings are open in the grace period", async () => {
+      try {
+        await program.methods
+          .closeCampaign()
+          .accounts({
+            campaign: accounts.campaign,
+            vault: accounts.vault,
+            vaultToken: accounts.vaultToken,
+            authorityToken: null,
+            authority: farmer.publicKey,
+            tokenProgram: null,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([farmer])
+          .rpc();
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("OpenBackingsRemain");
+      }
+    });
+
+    it("should close the campaign once the grace period is over", async () => {
+      const { endTime } = await program.account.campaign.fetch(accounts.campaign);
+      await warpTo(endTime.toNumber() + CLOSE_GRACE_PERIOD + 1);
+      const vaultReserve = await balanceOf(accounts.vault);
+      const campaignRent = await balanceOf(accounts.campaign);
+      const farmerBefore = await balanceOf(farmer.publicKey);
+
+      await program.methods
+        .closeCampaign()
+        .accounts({
+          campaign: accounts.campaign,
+          vault: accounts.vault,
+          vaultToken: accounts.vaultToken,
+          authorityToken: null,
+          authority: farmer.publicKey,
+          tokenProgram: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([farmer])
+        .rpc();
+
+      expect(await exists(accounts.campaign)).to.be.false;
+      expect(await exists(accounts.vault)).to.be.false;
+      expect(await balanceOf(farmer.publicKey)).to.equal(farmerBefore + vaultReserve + campaignRent);
+    });
+
+    it("should let a backer close a settled backing after the campaign is closed", async () => {
+      const backing = backingFor(accounts.campaign, backer2);
+
+      await program.methods
+        .closeBacking()
+        .accounts({ campaign: accounts.campaign, backing, backer: backer2.publicKey })
+        .signers([backer2])
+        .rpc();
+
+      expect(await exists(backing)).to.be.false;
+    });
+  });
+
+  describe("Disputed Backing", () => {
+    let accounts: { campaign: PublicKey; vault: PublicKey; vaultToken: PublicKey; tier: PublicKey };
+    let backing: PublicKey;
+    let dispute: PublicKey;
+
+    before(async () => {
+      accounts = await createCampaign(new BN(2 * LAMPORTS_PER_SOL), NATIVE_MINT);
+      await backSol(accounts, backer1, new BN(2 * LAMPORTS_PER_SOL));
+      await finalizeAfterEnd(accounts.campaign);
+      backing = backingFor(accounts.campaign, backer1);
+      dispute = pda(Buffer.from("dispute"), backing.toBuffer());
+
+      await program.methods
+        .openBackingDispute(Array.from(Buffer.alloc(32, 1)))
+        .accounts({
+          campaign: accounts.campaign,
+          backing,
+          dispute,
+          backer: backer1.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([backer1])
+        .rpc();
+    });
+
+    it("should fail to close a backing while its dispute is open", async () => {
+      try {
+        await program.methods
+          .closeBacking()
+          .accounts({ campaign: accounts.campaign, backing, dispute, backer: backer1.publicKey })
+          .signers([backer1])
+          .rpc();
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("DisputesPending");
+      }
+    });
+
+    it("should mark the backing refunded when the dispute pays it back", async () => {
+      const { deadline } = await program.account.dispute.fetch(dispute);
+      await warpTo(deadline.toNumber());
+      const backerBefore = await balanceOf(backer1.publicKey);
+
+      // Past the deadline the dispute defaults to the backer and anyone can settle it
+      await program.methods
+        .resolveBackingDisputeSol(0)
+        .accounts({
+          config: configPDA,
+          role: null,
+          dispute,
+          campaign: accounts.campaign,
+          vault: accounts.vault,
+          backing,
+          backer: backer1.publicKey,
+          customerStats: customerStatsFor(backer1.publicKey),
+          caller: backer2.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([backer2])
+        .rpc();
+
+      expect(await balanceOf(backer1.publicKey)).to.equal(backerBefore + 2 * LAMPORTS_PER_SOL);
+      const settled = await program.account.backing.fetch(backing);
+      expect(settled.refundedAmount.toNumber()).to.equal(2 * LAMPORTS_PER_SOL);
+      expect(settled.isRefunded).to.be.true;
+      const campaign = await program.account.campaign.fetch(accounts.campaign);
+      expect(campaign.refundedAmount.toNumber()).to.equal(2 * LAMPORTS_PER_SOL);
+      expect(campaign.openDisputes).to.equal(0);
+    });
+
+    it("should close the backing once the dispute is resolved", async () => {
+      await program.methods
+        .closeBacking()
+        .accounts({ campaign: accounts.campaign, backing, dispute, backer: backer1.publicKey })
+        .signers([backer1])
+        .rpc();
+
+      expect(await exists(backing)).to.be.false;
+    });
+  });
+
+  describe("Funded SOL Campaign", () => {
+    const coop = Keypair.generate();
+    const charity = Keypair.generate();
+    const partner = Keypair.generate();
+    let accounts: { campaign: PublicKey; vault: PublicKey; vaultToken: PublicKey; tier: PublicKey };
+    let referrer: PublicKey;
+
+    // 10 SOL raised at a 2.5% fee: 0.25 SOL fee, 0.3 SOL referral reserve, 9.45 SOL proceeds
+    const fee = 250_000_000;
+    const referralReserve = 300_000_000;
+    const proceeds = 9_450_000_000;
+    const holdback = 2_000_000_000;
+
+    before(async () => {
+      await program.methods
+        .setFeeSplits([
+          { wallet: coop.publicKey, shareBps: 3000 },
+          { wallet: charity.publicKey, shareBps: 1000 },
+        ])
+        .accounts({ config: configPDA, role: null, signer: admin.publicKey })
+        .signers([admin])
+        .rpc();
+
+      accounts = await createCampaign(new BN(10 * LAMPORTS_PER_SOL), NATIVE_MINT);
+      await program.methods
+        .setReferralBps(500)
+        .accounts({ campaign: accounts.campaign, authority: farmer.publicKey })
+        .signers([farmer])
+        .rpc();
+      await program.methods
+        .setCoOwners([{ wallet: partner.publicKey, shareBps: 2000 }], 0)
+        .accounts({ campaign: accounts.campaign, authority: farmer.publicKey })
+        .signers([farmer])
+        .rpc();
+
+      referrer = pda(Buffer.from("referrer"), accounts.campaign.toBuffer(), referrerWallet.publicKey.toBuffer());
+      await program.methods
+        .registerReferrer()
+        .accounts({
+          campaign: accounts.campaign,
+          referrer,
+          wallet: referrerWallet.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([referrerWallet])
+        .rpc();
+
+      await backSol(accounts, backer1, new BN(6 * LAMPORTS_PER_SOL), referrer);
+      await backSol(accounts, backer2, new BN(4 * LAMPORTS_PER_SOL));
+      await finalizeAfterEnd(accounts.campaign);
+    });
+
+    it("should pay every recipient its share on withdrawal", async () => {
+      const recipients = [admin, coop, charity, partner, payoutWallet].map((wallet) => wallet.publicKey);
+      const before = await Promise.all(recipients.map(balanceOf));
+      const vaultBefore = await balanceOf(accounts.vault);
+
+      await program.methods
+        .withdrawFundsSol()
+        .accounts({
+          config: configPDA,
+          farmerProfile: farmerProfilePDA,
+          campaign: accounts.campaign,
+          vault: accounts.vault,
+          treasury: admin.publicKey,
+          payoutWallet: payoutWallet.publicKey,
+          authority: farmer.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .remainingAccounts(
+          [coop, charity, partner].map((wallet) => ({ pubkey: wallet.publicKey, isSigner: false, isWritable: true }))
+        )
+        .signers([farmer])
+        .rpc();
+
+      const after = await Promise.all(recipients.map(balanceOf));
+      const received = after.map((balance, i) => balance - before[i]);
+      // Treasury keeps the 60% of the fee not split to partners
+      expect(received).to.deep.equal([
+        (fee * 6) / 10,
+        (fee * 3) / 10,
+        fee / 10,
+        proceeds / 5,
+        proceeds - proceeds / 5 - holdback,
+      ]);
+
+      // The referral reserve and holdback stay in the vault on top of its rent reserve
+      expect(vaultBefore - (await balanceOf(accounts.vault))).to.equal(
+        10 * LAMPORTS_PER_SOL - referralReserve - holdback
+      );
+      const campaign = await program.account.campaign.fetch(accounts.campaign);
+      expect(campaign.isWithdrawn).to.be.true;
+      expect(campaign.holdbackAmount.toNumber()).to.equal(holdback);
+    });
+
+    it("should pay the referral reserve to the referrer", async () => {
+      const balanceBefore = await balanceOf(referrerWallet.publicKey);
+
+      await program.methods
+        .claimReferralSol()
+        .accounts({
+          campaign: accounts.campaign,
+          vault: accounts.vault,
+          referrer,
+          wallet: referrerWallet.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([referrerWallet])
+        .rpc();
+
+      expect(await balanceOf(referrerWallet.publicKey)).to.equal(balanceBefore + referralReserve);
+    });
+
+    it("should release the holdback to the payout wallet once rewards are delivered", async () => {
+      for (const backer of [backer1, backer2]) {
+        await program.methods
+          .fulfillReward()
+          .accounts({
+            campaign: accounts.campaign,
+            backing: backingFor(accounts.campaign, backer),
+            authority: farmer.publicKey,
+          })
+          .signers([farmer])
+          .rpc();
+      }
+      const balanceBefore = await balanceOf(payoutWallet.publicKey);
+
+      await program.methods
+        .releaseHoldbackSol()
+        .accounts({
+          campaign: accounts.campaign,
+          vault: accounts.vault,
+          payoutWallet: payoutWallet.publicKey,
+          authority: farmer.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([farmer])
+        .rpc();
+
+      expect(await balanceOf(payoutWallet.publicKey)).to.equal(balanceBefore + holdback);
+      const rent = await context.banksClient.getRent();
+      expect(await balanceOf(accounts.vault)).to.equal(Number(rent.minimumBalance(BigInt(0))));
+    });
+
+    it("should close the settled backings, tier and campaign", async () => {
+      for (const backer of [backer1, backer2]) {
+        await program.methods
+          .closeBacking()
+          .accounts({
+            campaign: accounts.campaign,
+            backing: backingFor(accounts.campaign, backer),
+            backer: backer.publicKey,
+          })
+          .signers([backer])
+          .rpc();
+      }
+      await program.methods
+        .closeTier()
+        .accounts({ campaign: accounts.campaign, tier: accounts.tier, authority: farmer.publicKey })
+        .signers([farmer])
+        .rpc();
+      await program.methods
+        .closeCampaign()
+        .accounts({
+          campaign: accounts.campaign,
+          vault: accounts.vault,
+          vaultToken: accounts.vaultToken,
+          authorityToken: null,
+          authority: farmer.publicKey,
+          tokenProgram: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([farmer])
+        .rpc();
+
+      expect(await exists(accounts.campaign)).to.be.false;
+      expect(await exists(accounts.vault)).to.be.false;
+    });
+  });
+
+  describe("Failed Token Campaign", () => {
+    const mintAuthority = Keypair.generate();
+    const mint = Keypair.generate();
+    const backerToken = Keypair.generate();
+    let accounts: { campaign: PublicKey; vault: PublicKey; vaultToken: PublicKey; tier: PublicKey };
+
+    before(async () => {
+      const rent = await context.banksClient.getRent();
+      await send(
+        [backer1, mint, backerToken],
+        SystemProgram.createAccount({
+          fromPubkey: backer1.publicKey,
+          newAccountPubkey: mint.publicKey,
+          lamports: Number(rent.minimumBalance(BigInt(MINT_SIZE))),
+          space: MINT_SIZE,
+          programId: TOKEN_PROGRAM_ID,
+        }),
+        createInitializeMint2Instruction(mint.publicKey, 6, mintAuthority.publicKey, null),
+        SystemProgram.createAccount({
+          fromPubkey: backer1.publicKey,
+          newAccountPubkey: backerToken.publicKey,
+          lamports: Number(rent.minimumBalance(BigInt(ACCOUNT_SIZE))),
+          space: ACCOUNT_SIZE,
+          programId: TOKEN_PROGRAM_ID,
+        }),
+        createInitializeAccount3Instruction(backerToken.publicKey, mint.publicKey, backer1.publicKey)
+      );
+      await send(
+        [backer1, mintAuthority],
+        createMintToInstruction(mint.publicKey, backerToken.publicKey, mintAuthority.publicKey, 10_000_000_000)
+      );
+
+      accounts = await createCampaign(new BN(100 * LAMPORTS_PER_SOL), mint.publicKey);
+      await program.methods
+        .backCampaignToken(0, new BN(2 * LAMPORTS_PER_SOL), 1, [])
+        .accounts({
+          config: configPDA,
+          campaign: accounts.campaign,
+          tier: accounts.tier,
+          vaultToken: accounts.vaultToken,
+          currencyMint: mint.publicKey,
+          backerToken: backerToken.publicKey,
+          backing: backingFor(accounts.campaign, backer1),
+          referrer: null,
+          customerStats: customerStatsFor(backer1.publicKey),
+          ...(await indexPagesFor(accounts.campaign, backer1.publicKey)),
+          backer: backer1.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([backer1])
+        .rpc();
+    });
+
+    it("should fail to gate a tier on loyalty level in a token campaign", async () => {
+      try {
+        await program.methods
+          .createTier(1, "Gold Tier", new BN(LAMPORTS_PER_SOL), new BN(0), "Gold benefits", 0, new BN(0), new BN(0), { gold: {} })
+          .accounts({
+            campaign: accounts.campaign,
+            tier: pda(Buffer.from("tier"), accounts.campaign.toBuffer(), Buffer.from([1])),
+            authority: farmer.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([farmer])
+          .rpc();
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("LoyaltyGateRequiresSol");
+      }
+    });
+
+    it("should refund, close the backing and tier, and close the campaign with its token vault", async () => {
+      await finalizeAfterEnd(accounts.campaign);
+      const backing = backingFor(accounts.campaign, backer1);
+      await program.methods
+        .claimRefundToken()
+        .accounts({
+          campaign: accounts.campaign,
+          vaultToken: accounts.vaultToken,
+          currencyMint: mint.publicKey,
+          backerToken: backerToken.publicKey,
+          backing,
+          backer: backer1.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([backer1])
+        .rpc();
+      expect(await tokenBalanceOf(backerToken.publicKey)).to.equal(10_000_000_000);
+
+      await program.methods
+        .closeBacking()
+        .accounts({ campaign: accounts.campaign, backing, backer: backer1.publicKey })
+        .signers([backer1])
+        .rpc();
+      await program.methods
+        .closeTier()
+        .accounts({ campaign: accounts.campaign, tier: accounts.tier, authority: farmer.publicKey })
+        .signers([farmer])
+        .rpc();
+
+      const vaultRent = (await balanceOf(accounts.vaultToken)) + (await balanceOf(accounts.vault));
+      const campaignRent = await balanceOf(accounts.campaign);
+      const farmerBefore = await balanceOf(farmer.publicKey);
+
+      await program.methods
+        .closeCampaign()
+        .accounts({
+          campaign: accounts.campaign,
+          vault: accounts.vault,
+          vaultToken: accounts.vaultToken,
+          authorityToken: null,
+          authority: farmer.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([farmer])
+        .rpc();
+
+      expect(await exists(accounts.campaign)).to.be.false;
+      expect(await exists(accounts.vault)).to.be.false;
+      expect(await exists(accounts.vaultToken)).to.be.false;
+      expect(await balanceOf(farmer.publicKey)).to.equal(farmerBefore + vaultRent + campaignRent);
+    });
+  });
+});
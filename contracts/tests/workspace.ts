@@ -7,15 +7,27 @@ import {
   SystemProgram,
   Keypair,
   LAMPORTS_PER_SOL,
+  Transaction,
 } from "@solana/web3.js";
 import {
   TOKEN_PROGRAM_ID,
+  NATIVE_MINT,
   createMint,
   createAccount,
   mintTo,
   getAccount,
 } from "@solana/spl-token";
 import { BN } from "@coral-xyz/anchor";
+import { createHash } from "crypto";
+
+// Mirrors programs/workspace/src/merkle.rs
+const merkleLeaf = (member: PublicKey): Buffer =>
+  createHash("sha256").update(Buffer.from([0])).update(member.toBuffer()).digest();
+
+const merkleNode = (a: Buffer, b: Buffer): Buffer => {
+  const [left, right] = Buffer.compare(a, b) <= 0 ? [a, b] : [b, a];
+  return createHash("sha256").update(Buffer.from([1])).update(left).update(right).digest();
+};
 
 describe("Farm Crowdfunding Platform", () => {
   const provider = anchor.AnchorProvider.env();
@@ -29,19 +41,62 @@ describe("Farm Crowdfunding Platform", () => {
   let backer2: Keypair;
   let treasury: Keypair;
   let configPDA: PublicKey;
+  let programDataPDA: PublicKey;
+  let farmerProfilePDA: PublicKey;
   let campaignPDA: PublicKey;
   let vaultPDA: PublicKey;
   let tierPDA: PublicKey;
   let backingPDA: PublicKey;
   let currencyMint: PublicKey;
+  let productPDA: PublicKey;
 
-  const campaignId = new BN(1);
+  // Campaign ids are assigned per farmer, starting at 0
+  const campaignId = new BN(0);
   const tierId = 0;
   const feeBps = 250; // 2.5%
+  const productId = new BN(1);
+
+  const customerStatsFor = (wallet: PublicKey): PublicKey =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("customer_stats"), wallet.toBuffer()],
+      program.programId
+    )[0];
+
+  // Category index and page the next listing in a category and geohash cell is appended to
+  const listingIndexFor = async (seed: string, category: number, geohash: string) => {
+    const [categoryIndex] = PublicKey.findProgramAddressSync(
+      [Buffer.from(seed), Buffer.from([category]), Buffer.from(geohash.slice(0, 4))],
+      program.programId
+    );
+    const index = await program.account.categoryIndex.fetchNullable(categoryIndex);
+    const page = new BN(Math.floor((index ? index.count.toNumber() : 0) / 32));
+    const [categoryPage] = PublicKey.findProgramAddressSync(
+      [Buffer.from("category_page"), categoryIndex.toBuffer(), page.toArrayLike(Buffer, "le", 4)],
+      program.programId
+    );
+    return { categoryIndex, categoryPage };
+  };
+
+  // Index pages the next backing of `campaign` by `backer` is appended to
+  const indexPagesFor = async (campaign: PublicKey, backer: PublicKey) => {
+    const pageSeed = (count: number) => new BN(Math.floor(count / 32)).toArrayLike(Buffer, "le", 4);
+    const { backersCount } = await program.account.campaign.fetch(campaign);
+    const stats = await program.account.customerStats.fetchNullable(customerStatsFor(backer));
+    return {
+      campaignBackers: PublicKey.findProgramAddressSync(
+        [Buffer.from("campaign_backers"), campaign.toBuffer(), pageSeed(backersCount.toNumber())],
+        program.programId
+      )[0],
+      backerCampaigns: PublicKey.findProgramAddressSync(
+        [Buffer.from("backer_campaigns"), backer.toBuffer(), pageSeed(stats ? stats.backingsCount : 0)],
+        program.programId
+      )[0],
+    };
+  };
 
   before(async () => {
-    // Generate keypairs
-    authority = Keypair.generate();
+    // The platform admin must be the program's upgrade authority, the provider wallet on localnet
+    authority = (provider.wallet as anchor.Wallet).payer;
     farmer = Keypair.generate();
     backer1 = Keypair.generate();
     backer2 = Keypair.generate();
@@ -59,7 +114,17 @@ describe("Farm Crowdfunding Platform", () => {
 
     // Derive PDAs
     [configPDA] = PublicKey.findProgramAddressSync(
-      [Buffer.from("config"), authority.publicKey.toBuffer()],
+      [Buffer.from("config")],
+      program.programId
+    );
+
+    [programDataPDA] = PublicKey.findProgramAddressSync(
+      [program.programId.toBuffer()],
+      new PublicKey("BPFLoaderUpgradeab1e11111111111111111111111")
+    );
+
+    [farmerProfilePDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from("farmer_profile"), farmer.publicKey.toBuffer()],
       program.programId
     );
 
@@ -81,22 +146,56 @@ describe("Farm Crowdfunding Platform", () => {
       program.programId
     );
 
-    // Create a mock currency mint for testing
-    currencyMint = await createMint(
-      provider.connection,
-      authority,
-      authority.publicKey,
-      null,
-      6
-    );
+    // Every campaign and product in these tests is priced in SOL
+    currencyMint = NATIVE_MINT;
   });
 
   describe("Initialize Platform", () => {
+    it("should fail to initialize with invalid fee (>10000)", async () => {
+      try {
+        await program.methods
+          .initializeConfig(10001)
+          .accounts({
+            config: configPDA,
+            program: program.programId,
+            programData: programDataPDA,
+            authority: authority.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([authority])
+          .rpc();
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("InvalidFee");
+      }
+    });
+
+    it("should fail to initialize by a signer other than the upgrade authority", async () => {
+      try {
+        await program.methods
+          .initializeConfig(feeBps)
+          .accounts({
+            config: configPDA,
+            program: program.programId,
+            programData: programDataPDA,
+            authority: backer1.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([backer1])
+          .rpc();
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("Unauthorized");
+      }
+    });
+
     it("should initialize platform config successfully", async () => {
       await program.methods
         .initializeConfig(feeBps)
         .accounts({
           config: configPDA,
+          program: program.programId,
+          programData: programDataPDA,
           authority: authority.publicKey,
           systemProgram: SystemProgram.programId,
         })
@@ -111,36 +210,103 @@ describe("Farm Crowdfunding Platform", () => {
       expect(config.isActive).to.be.true;
       expect(config.isPaused).to.be.false;
       expect(config.version).to.equal(1);
+      expect(config.treasury.toString()).to.equal(authority.publicKey.toString());
     });
+  });
 
-    it("should fail to initialize with invalid fee (>10000)", async () => {
-      const newAuthority = Keypair.generate();
-      const sig = await provider.connection.requestAirdrop(
-        newAuthority.publicKey,
-        10 * LAMPORTS_PER_SOL
-      );
-      await provider.connection.confirmTransaction(sig);
-
-      const [newConfigPDA] = PublicKey.findProgramAddressSync(
-        [Buffer.from("config"), newAuthority.publicKey.toBuffer()],
+  describe("Farmer Profile", () => {
+    it("should fail to create campaign without a farmer profile", async () => {
+      const [newCampaignPDA] = PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("campaign"),
+          farmer.publicKey.toBuffer(),
+          campaignId.toArrayLike(Buffer, "le", 8),
+        ],
         program.programId
       );
 
       try {
         await program.methods
-          .initializeConfig(10001)
+          .createCampaign("No Profile", "desc", new BN(1000000), new BN(30), false, { vegetables: {} }, [], "9q8yy")
+          .accounts({
+            config: configPDA,
+            farmerProfile: farmerProfilePDA,
+            campaign: newCampaignPDA,
+            vault: vaultPDA,
+            currencyMint: currencyMint,
+            ...(await listingIndexFor("campaign_category", 0, "9q8yy")),
+            farmer: farmer.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([farmer])
+          .rpc();
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("AccountNotInitialized");
+      }
+    });
+
+    it("should fail to create a profile with an invalid geohash", async () => {
+      try {
+        await program.methods
+          .createFarmerProfile("Green Valley Farm", "", "9q8y!", true, farmer.publicKey)
           .accounts({
-            config: newConfigPDA,
-            authority: newAuthority.publicKey,
+            farmerProfile: farmerProfilePDA,
+            farmer: farmer.publicKey,
             systemProgram: SystemProgram.programId,
           })
-          .signers([newAuthority])
+          .signers([farmer])
           .rpc();
         expect.fail("Should have thrown error");
       } catch (error) {
-        expect(error.message).to.include("InvalidFee");
+        expect(error.message).to.include("InvalidGeohash");
       }
     });
+
+    it("should fail to create a profile without a payout wallet", async () => {
+      try {
+        await program.methods
+          .createFarmerProfile("Green Valley Farm", "", "9q8yy", true, PublicKey.default)
+          .accounts({
+            farmerProfile: farmerProfilePDA,
+            farmer: farmer.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([farmer])
+          .rpc();
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("InvalidPayoutWallet");
+      }
+    });
+
+    it("should create a farmer profile", async () => {
+      await program.methods
+        .createFarmerProfile(
+          "Green Valley Farm",
+          "https://example.com/farms/green-valley.json",
+          "9q8yy",
+          true,
+          farmer.publicKey
+        )
+        .accounts({
+          farmerProfile: farmerProfilePDA,
+          farmer: farmer.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([farmer])
+        .rpc();
+
+      const profile = await program.account.farmerProfile.fetch(farmerProfilePDA);
+      expect(profile.farmer.toString()).to.equal(farmer.publicKey.toString());
+      expect(profile.farmName).to.equal("Green Valley Farm");
+      expect(profile.geohash).to.equal("9q8yy");
+      expect(profile.isOrganic).to.be.true;
+      expect(profile.payoutWallet.toString()).to.equal(farmer.publicKey.toString());
+      expect(profile.campaignsCreated).to.equal(0);
+      expect(profile.campaignsFunded).to.equal(0);
+      expect(profile.totalRaised.toNumber()).to.equal(0);
+    });
   });
 
   describe("Create Campaign", () => {
@@ -151,12 +317,14 @@ describe("Farm Crowdfunding Platform", () => {
       const durationDays = new BN(30);
 
       await program.methods
-        .createCampaign(campaignId, title, description, goalAmount, durationDays)
+        .createCampaign(title, description, goalAmount, durationDays, false, { vegetables: {} }, [], "9q8yy")
         .accounts({
           config: configPDA,
           campaign: campaignPDA,
           vault: vaultPDA,
           currencyMint: currencyMint,
+          ...(await listingIndexFor("campaign_category", 0, "9q8yy")),
+          farmerProfile: farmerProfilePDA,
           farmer: farmer.publicKey,
           systemProgram: SystemProgram.programId,
         })
@@ -165,7 +333,7 @@ describe("Farm Crowdfunding Platform", () => {
 
       const campaign = await program.account.campaign.fetch(campaignPDA);
       expect(campaign.farmer.toString()).to.equal(farmer.publicKey.toString());
-      expect(campaign.campaignId.toNumber()).to.equal(1);
+      expect(campaign.campaignId.toNumber()).to.equal(0);
       expect(campaign.title).to.equal(title);
       expect(campaign.description).to.equal(description);
       expect(campaign.goalAmount.toNumber()).to.equal(10 * LAMPORTS_PER_SOL);
@@ -177,10 +345,14 @@ describe("Farm Crowdfunding Platform", () => {
 
       const config = await program.account.config.fetch(configPDA);
       expect(config.totalCampaigns.toNumber()).to.equal(1);
+
+      const profile = await program.account.farmerProfile.fetch(farmerProfilePDA);
+      expect(profile.campaignsCreated).to.equal(1);
+      expect(profile.nextCampaignId.toNumber()).to.equal(1);
     });
 
     it("should fail to create campaign with title too long", async () => {
-      const newCampaignId = new BN(999);
+      const newCampaignId = (await program.account.farmerProfile.fetch(farmerProfilePDA)).nextCampaignId;
       const longTitle = "A".repeat(65);
 
       const [newCampaignPDA] = PublicKey.findProgramAddressSync(
@@ -203,12 +375,14 @@ describe("Farm Crowdfunding Platform", () => {
 
       try {
         await program.methods
-          .createCampaign(newCampaignId, longTitle, "desc", new BN(1000000), new BN(30))
+          .createCampaign(longTitle, "desc", new BN(1000000), new BN(30), false, { vegetables: {} }, [], "9q8yy")
           .accounts({
             config: configPDA,
             campaign: newCampaignPDA,
             vault: newVaultPDA,
             currencyMint: currencyMint,
+            ...(await listingIndexFor("campaign_category", 0, "9q8yy")),
+            farmerProfile: farmerProfilePDA,
             farmer: farmer.publicKey,
             systemProgram: SystemProgram.programId,
           })
@@ -221,7 +395,7 @@ describe("Farm Crowdfunding Platform", () => {
     });
 
     it("should fail to create campaign with zero goal amount", async () => {
-      const newCampaignId = new BN(998);
+      const newCampaignId = (await program.account.farmerProfile.fetch(farmerProfilePDA)).nextCampaignId;
 
       const [newCampaignPDA] = PublicKey.findProgramAddressSync(
         [
@@ -243,12 +417,14 @@ describe("Farm Crowdfunding Platform", () => {
 
       try {
         await program.methods
-          .createCampaign(newCampaignId, "Test", "desc", new BN(0), new BN(30))
+          .createCampaign("Test", "desc", new BN(0), new BN(30), false, { vegetables: {} }, [], "9q8yy")
           .accounts({
             config: configPDA,
             campaign: newCampaignPDA,
             vault: newVaultPDA,
             currencyMint: currencyMint,
+            ...(await listingIndexFor("campaign_category", 0, "9q8yy")),
+            farmerProfile: farmerProfilePDA,
             farmer: farmer.publicKey,
             systemProgram: SystemProgram.programId,
           })
@@ -272,14 +448,14 @@ describe("Farm Crowdfunding Platform", () => {
       const minAmount = new BN(1 * LAMPORTS_PER_SOL);
       const maxAmount = new BN(5 * LAMPORTS_PER_SOL);
       const benefits = "Early access to farm produce, monthly newsletter";
-      const maxBackers = 100;
+      const maxUnits = 100;
 
       await program.methods
-        .createTier(tierId, tierName, minAmount, maxAmount, benefits, maxBackers)
+        .createTier(tierId, tierName, minAmount, maxAmount, benefits, maxUnits, new BN(0), new BN(0), { bronze: {} })
         .accounts({
           campaign: campaignPDA,
           tier: tierPDA,
-          farmer: farmer.publicKey,
+          authority: farmer.publicKey,
           systemProgram: SystemProgram.programId,
         })
         .signers([farmer])
@@ -292,7 +468,7 @@ describe("Farm Crowdfunding Platform", () => {
       expect(tier.minAmount.toNumber()).to.equal(1 * LAMPORTS_PER_SOL);
       expect(tier.maxAmount.toNumber()).to.equal(5 * LAMPORTS_PER_SOL);
       expect(tier.benefits).to.equal(benefits);
-      expect(tier.maxBackers).to.equal(maxBackers);
+      expect(tier.maxUnits).to.equal(maxUnits);
       expect(tier.currentBackers).to.equal(0);
 
       const campaign = await program.account.campaign.fetch(campaignPDA);
@@ -314,12 +490,15 @@ describe("Farm Crowdfunding Platform", () => {
             new BN(5 * LAMPORTS_PER_SOL),
             new BN(1 * LAMPORTS_PER_SOL), // max < min
             "Benefits",
-            100
+            100,
+            new BN(0), // Available immediately
+            new BN(0), // No expiry
+            { bronze: {} }
           )
           .accounts({
             campaign: campaignPDA,
             tier: newTierPDA,
-            farmer: farmer.publicKey,
+            authority: farmer.publicKey,
             systemProgram: SystemProgram.programId,
           })
           .signers([farmer])
@@ -345,19 +524,22 @@ describe("Farm Crowdfunding Platform", () => {
             new BN(1 * LAMPORTS_PER_SOL),
             new BN(5 * LAMPORTS_PER_SOL),
             "Benefits",
-            100
+            100,
+            new BN(0), // Available immediately
+            new BN(0), // No expiry
+            { bronze: {} }
           )
           .accounts({
             campaign: campaignPDA,
             tier: newTierPDA,
-            farmer: backer1.publicKey, // Not the farmer
+            authority: backer1.publicKey, // Not the farmer
             systemProgram: SystemProgram.programId,
           })
           .signers([backer1])
           .rpc();
         expect.fail("Should have thrown error");
       } catch (error) {
-        expect(error.message).to.include("constraint");
+        expect(error.message).to.include("Unauthorized");
       }
     });
   });
@@ -373,12 +555,15 @@ describe("Farm Crowdfunding Platform", () => {
       const vaultBalanceBefore = await provider.connection.getBalance(vaultPDA);
 
       await program.methods
-        .backCampaignSol(tierId, backAmount)
+        .backCampaignSol(tierId, backAmount, 1, [])
         .accounts({
+          config: configPDA,
           campaign: campaignPDA,
           tier: tierPDA,
           vault: vaultPDA,
           backing: backingPDA,
+          customerStats: customerStatsFor(backer1.publicKey),
+          ...(await indexPagesFor(campaignPDA, backer1.publicKey)),
           backer: backer1.publicKey,
           systemProgram: SystemProgram.programId,
         })
@@ -403,6 +588,24 @@ describe("Farm Crowdfunding Platform", () => {
       expect(tier.currentBackers).to.equal(1);
     });
 
+    it("should index the backing for enumeration", async () => {
+      const [campaignBackersPDA] = PublicKey.findProgramAddressSync(
+        [Buffer.from("campaign_backers"), campaignPDA.toBuffer(), new BN(0).toArrayLike(Buffer, "le", 4)],
+        program.programId
+      );
+      const [backerCampaignsPDA] = PublicKey.findProgramAddressSync(
+        [Buffer.from("backer_campaigns"), backer1.publicKey.toBuffer(), new BN(0).toArrayLike(Buffer, "le", 4)],
+        program.programId
+      );
+
+      const campaignBackers = await program.account.campaignBackersPage.fetch(campaignBackersPDA);
+      expect(campaignBackers.page).to.equal(0);
+      expect(campaignBackers.backers.map((key) => key.toString())).to.deep.equal([backer1.publicKey.toString()]);
+
+      const backerCampaigns = await program.account.backerCampaignsPage.fetch(backerCampaignsPDA);
+      expect(backerCampaigns.campaigns.map((key) => key.toString())).to.deep.equal([campaignPDA.toString()]);
+    });
+
     it("should fail to back with amount below minimum", async () => {
       const [newBackingPDA] = PublicKey.findProgramAddressSync(
         [Buffer.from("backing"), campaignPDA.toBuffer(), backer2.publicKey.toBuffer()],
@@ -411,12 +614,15 @@ describe("Farm Crowdfunding Platform", () => {
 
       try {
         await program.methods
-          .backCampaignSol(tierId, new BN(0.5 * LAMPORTS_PER_SOL)) // Below min of 1 SOL
+          .backCampaignSol(tierId, new BN(0.5 * LAMPORTS_PER_SOL), 1, []) // Below min of 1 SOL
           .accounts({
+            config: configPDA,
             campaign: campaignPDA,
             tier: tierPDA,
             vault: vaultPDA,
             backing: newBackingPDA,
+            customerStats: customerStatsFor(backer2.publicKey),
+            ...(await indexPagesFor(campaignPDA, backer2.publicKey)),
             backer: backer2.publicKey,
             systemProgram: SystemProgram.programId,
           })
@@ -436,12 +642,15 @@ describe("Farm Crowdfunding Platform", () => {
 
       try {
         await program.methods
-          .backCampaignSol(tierId, new BN(10 * LAMPORTS_PER_SOL)) // Above max of 5 SOL
+          .backCampaignSol(tierId, new BN(10 * LAMPORTS_PER_SOL), 1, []) // Above max of 5 SOL
           .accounts({
+            config: configPDA,
             campaign: campaignPDA,
             tier: tierPDA,
             vault: vaultPDA,
             backing: newBackingPDA,
+            customerStats: customerStatsFor(backer2.publicKey),
+            ...(await indexPagesFor(campaignPDA, backer2.publicKey)),
             backer: backer2.publicKey,
             systemProgram: SystemProgram.programId,
           })
@@ -462,12 +671,15 @@ describe("Farm Crowdfunding Platform", () => {
       const backAmount = new BN(3 * LAMPORTS_PER_SOL);
 
       await program.methods
-        .backCampaignSol(tierId, backAmount)
+        .backCampaignSol(tierId, backAmount, 1, [])
         .accounts({
+          config: configPDA,
           campaign: campaignPDA,
           tier: tierPDA,
           vault: vaultPDA,
           backing: backer2BackingPDA,
+          customerStats: customerStatsFor(backer2.publicKey),
+          ...(await indexPagesFor(campaignPDA, backer2.publicKey)),
           backer: backer2.publicKey,
           systemProgram: SystemProgram.programId,
         })
@@ -506,9 +718,10 @@ describe("Farm Crowdfunding Platform", () => {
     let failedVaultPDA: PublicKey;
     let failedTierPDA: PublicKey;
     let failedBackingPDA: PublicKey;
-    const failedCampaignId = new BN(2);
+    let failedCampaignId: BN;
 
     before(async () => {
+      failedCampaignId = (await program.account.farmerProfile.fetch(farmerProfilePDA)).nextCampaignId;
       [failedCampaignPDA] = PublicKey.findProgramAddressSync(
         [
           Buffer.from("campaign"),
@@ -535,12 +748,14 @@ describe("Farm Crowdfunding Platform", () => {
       const durationDays = new BN(1); // Short duration
 
       await program.methods
-        .createCampaign(failedCampaignId, title, description, goalAmount, durationDays)
+        .createCampaign(title, description, goalAmount, durationDays, false, { vegetables: {} }, [], "9q8yy")
         .accounts({
           config: configPDA,
           campaign: failedCampaignPDA,
           vault: failedVaultPDA,
           currencyMint: currencyMint,
+          ...(await listingIndexFor("campaign_category", 0, "9q8yy")),
+          farmerProfile: farmerProfilePDA,
           farmer: farmer.publicKey,
           systemProgram: SystemProgram.programId,
         })
@@ -564,12 +779,15 @@ describe("Farm Crowdfunding Platform", () => {
           new BN(1 * LAMPORTS_PER_SOL),
           new BN(0), // Unlimited max
           "Basic benefits",
-          0 // Unlimited backers
+          0, // Unlimited backers
+          new BN(0), // Available immediately
+          new BN(0), // No expiry
+          { bronze: {} }
         )
         .accounts({
           campaign: failedCampaignPDA,
           tier: failedTierPDA,
-          farmer: farmer.publicKey,
+          authority: farmer.publicKey,
           systemProgram: SystemProgram.programId,
         })
         .signers([farmer])
@@ -583,12 +801,15 @@ describe("Farm Crowdfunding Platform", () => {
       );
 
       await program.methods
-        .backCampaignSol(0, new BN(5 * LAMPORTS_PER_SOL))
+        .backCampaignSol(0, new BN(5 * LAMPORTS_PER_SOL), 1, [])
         .accounts({
+          config: configPDA,
           campaign: failedCampaignPDA,
           tier: failedTierPDA,
           vault: failedVaultPDA,
           backing: failedBackingPDA,
+          customerStats: customerStatsFor(backer1.publicKey),
+          ...(await indexPagesFor(failedCampaignPDA, backer1.publicKey)),
           backer: backer1.publicKey,
           systemProgram: SystemProgram.programId,
         })
@@ -615,12 +836,15 @@ describe("Farm Crowdfunding Platform", () => {
           new BN(5 * LAMPORTS_PER_SOL),
           new BN(0), // Unlimited
           "Premium benefits, farm visits, exclusive produce",
-          50
+          50,
+          new BN(0), // Available immediately
+          new BN(0), // No expiry
+          { bronze: {} }
         )
         .accounts({
           campaign: campaignPDA,
           tier: unlimitedTierPDA,
-          farmer: farmer.publicKey,
+          authority: farmer.publicKey,
           systemProgram: SystemProgram.programId,
         })
         .signers([farmer])
@@ -645,129 +869,2954 @@ describe("Farm Crowdfunding Platform", () => {
           new BN(0.1 * LAMPORTS_PER_SOL),
           new BN(1 * LAMPORTS_PER_SOL),
           "Community newsletter access",
-          0 // Unlimited backers
+          0, // Unlimited backers
+          new BN(0), // Available immediately
+          new BN(0), // No expiry
+          { bronze: {} }
         )
         .accounts({
           campaign: campaignPDA,
           tier: unlimitedBackersTierPDA,
-          farmer: farmer.publicKey,
+          authority: farmer.publicKey,
           systemProgram: SystemProgram.programId,
         })
         .signers([farmer])
         .rpc();
 
       const tier = await program.account.campaignTier.fetch(unlimitedBackersTierPDA);
-      expect(tier.maxBackers).to.equal(0);
+      expect(tier.maxUnits).to.equal(0);
     });
   });
 
-  describe("Edge Cases", () => {
-    it("should fail to create campaign with duration > 365 days", async () => {
-      const newCampaignId = new BN(997);
-      const [newCampaignPDA] = PublicKey.findProgramAddressSync(
-        [
-          Buffer.from("campaign"),
-          farmer.publicKey.toBuffer(),
-          newCampaignId.toArrayLike(Buffer, "le", 8),
-        ],
-        program.programId
+  describe("Multi-Unit Backing", () => {
+    const communityTierId = 2;
+    let communityTierPDA: PublicKey;
+    let shareBacker: Keypair;
+    let shareBackingPDA: PublicKey;
+
+    before(async () => {
+      shareBacker = Keypair.generate();
+      const sig = await provider.connection.requestAirdrop(
+        shareBacker.publicKey,
+        10 * LAMPORTS_PER_SOL
       );
+      await provider.connection.confirmTransaction(sig);
 
-      const [newVaultPDA] = PublicKey.findProgramAddressSync(
-        [
-          Buffer.from("vault"),
-          farmer.publicKey.toBuffer(),
-          newCampaignId.toArrayLike(Buffer, "le", 8),
-        ],
+      [communityTierPDA] = PublicKey.findProgramAddressSync(
+        [Buffer.from("tier"), campaignPDA.toBuffer(), Buffer.from([communityTierId])],
+        program.programId
+      );
+      [shareBackingPDA] = PublicKey.findProgramAddressSync(
+        [Buffer.from("backing"), campaignPDA.toBuffer(), shareBacker.publicKey.toBuffer()],
         program.programId
       );
+    });
 
+    it("should fail when amount does not cover every unit", async () => {
       try {
         await program.methods
-          .createCampaign(
-            newCampaignId,
-            "Long Campaign",
-            "Description",
-            new BN(1 * LAMPORTS_PER_SOL),
-            new BN(366) // > 365 days
-          )
+          .backCampaignSol(communityTierId, new BN(0.2 * LAMPORTS_PER_SOL), 3, []) // Needs 0.3 SOL
           .accounts({
             config: configPDA,
-            campaign: newCampaignPDA,
-            vault: newVaultPDA,
-            currencyMint: currencyMint,
-            farmer: farmer.publicKey,
+            campaign: campaignPDA,
+            tier: communityTierPDA,
+            vault: vaultPDA,
+            backing: shareBackingPDA,
+            customerStats: customerStatsFor(shareBacker.publicKey),
+            ...(await indexPagesFor(campaignPDA, shareBacker.publicKey)),
+            backer: shareBacker.publicKey,
             systemProgram: SystemProgram.programId,
           })
-          .signers([farmer])
+          .signers([shareBacker])
           .rpc();
         expect.fail("Should have thrown error");
       } catch (error) {
-        expect(error.message).to.include("InvalidDuration");
+        expect(error.message).to.include("AmountBelowMinimum");
       }
     });
 
-    it("should fail to create tier with zero min amount", async () => {
-      const zeroMinTierId = 97;
-      const [zeroMinTierPDA] = PublicKey.findProgramAddressSync(
-        [Buffer.from("tier"), campaignPDA.toBuffer(), Buffer.from([zeroMinTierId])],
+    it("should back several units in one backing", async () => {
+      await program.methods
+        .backCampaignSol(communityTierId, new BN(0.5 * LAMPORTS_PER_SOL), 3, [])
+        .accounts({
+          config: configPDA,
+          campaign: campaignPDA,
+          tier: communityTierPDA,
+          vault: vaultPDA,
+          backing: shareBackingPDA,
+          customerStats: customerStatsFor(shareBacker.publicKey),
+          ...(await indexPagesFor(campaignPDA, shareBacker.publicKey)),
+          backer: shareBacker.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([shareBacker])
+        .rpc();
+
+      const backing = await program.account.backing.fetch(shareBackingPDA);
+      expect(backing.quantity).to.equal(3);
+
+      const tier = await program.account.campaignTier.fetch(communityTierPDA);
+      expect(tier.currentBackers).to.equal(1);
+      expect(tier.unitsSold).to.equal(3);
+    });
+  });
+
+  describe("Allow-Listed Campaigns", () => {
+    let privateCampaignId: BN;
+    let privateCampaignPDA: PublicKey;
+    let privateVaultPDA: PublicKey;
+    let privateTierPDA: PublicKey;
+
+    before(async () => {
+      privateCampaignId = (await program.account.farmerProfile.fetch(farmerProfilePDA)).nextCampaignId;
+      [privateCampaignPDA] = PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("campaign"),
+          farmer.publicKey.toBuffer(),
+          privateCampaignId.toArrayLike(Buffer, "le", 8),
+        ],
+        program.programId
+      );
+      [privateVaultPDA] = PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("vault"),
+          farmer.publicKey.toBuffer(),
+          privateCampaignId.toArrayLike(Buffer, "le", 8),
+        ],
+        program.programId
+      );
+      [privateTierPDA] = PublicKey.findProgramAddressSync(
+        [Buffer.from("tier"), privateCampaignPDA.toBuffer(), Buffer.from([0])],
         program.programId
       );
 
+      await program.methods
+        .createCampaign("Co-op Members Only", "Members fund the new barn", new BN(10 * LAMPORTS_PER_SOL), new BN(30), false, { vegetables: {} }, [], "9q8yy")
+        .accounts({
+          config: configPDA,
+          campaign: privateCampaignPDA,
+          vault: privateVaultPDA,
+          currencyMint: currencyMint,
+          ...(await listingIndexFor("campaign_category", 0, "9q8yy")),
+          farmerProfile: farmerProfilePDA,
+          farmer: farmer.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([farmer])
+        .rpc();
+
+      await program.methods
+        .createTier(0, "Member Share", new BN(1 * LAMPORTS_PER_SOL), new BN(0), "Barn share", 0, new BN(0), new BN(0), { bronze: {} })
+        .accounts({
+          campaign: privateCampaignPDA,
+          tier: privateTierPDA,
+          authority: farmer.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([farmer])
+        .rpc();
+    });
+
+    it("should fail to change the allow-list once a campaign has backers", async () => {
       try {
         await program.methods
-          .createTier(
-            zeroMinTierId,
-            "Zero Min Tier",
-            new BN(0), // Zero min amount
-            new BN(5 * LAMPORTS_PER_SOL),
-            "Benefits",
-            100
-          )
+          .setAllowlistRoot(Array.from(merkleLeaf(backer1.publicKey)))
           .accounts({
             campaign: campaignPDA,
-            tier: zeroMinTierPDA,
-            farmer: farmer.publicKey,
-            systemProgram: SystemProgram.programId,
+            authority: farmer.publicKey,
           })
           .signers([farmer])
           .rpc();
         expect.fail("Should have thrown error");
       } catch (error) {
-        expect(error.message).to.include("InvalidAmount");
+        expect(error.message).to.include("AllowlistLocked");
       }
     });
 
-    it("should fail to create tier with name too long", async () => {
-      const longNameTierId = 96;
-      const [longNameTierPDA] = PublicKey.findProgramAddressSync(
-        [Buffer.from("tier"), campaignPDA.toBuffer(), Buffer.from([longNameTierId])],
+    it("should set the allow-list root while in draft", async () => {
+      const root = merkleNode(merkleLeaf(backer1.publicKey), merkleLeaf(authority.publicKey));
+
+      await program.methods
+        .setAllowlistRoot(Array.from(root))
+        .accounts({
+          campaign: privateCampaignPDA,
+          authority: farmer.publicKey,
+        })
+        .signers([farmer])
+        .rpc();
+
+      const campaign = await program.account.campaign.fetch(privateCampaignPDA);
+      expect(Buffer.from(campaign.allowlistRoot).equals(root)).to.be.true;
+    });
+
+    it("should reject a backer without a valid proof", async () => {
+      const [backingPDA] = PublicKey.findProgramAddressSync(
+        [Buffer.from("backing"), privateCampaignPDA.toBuffer(), backer2.publicKey.toBuffer()],
         program.programId
       );
 
       try {
         await program.methods
-          .createTier(
-            longNameTierId,
-            "A".repeat(33), // > 32 chars
-            new BN(1 * LAMPORTS_PER_SOL),
-            new BN(5 * LAMPORTS_PER_SOL),
-            "Benefits",
-            100
-          )
+          .backCampaignSol(0, new BN(1 * LAMPORTS_PER_SOL), 1, [Array.from(merkleLeaf(authority.publicKey))])
           .accounts({
-            campaign: campaignPDA,
-            tier: longNameTierPDA,
-            farmer: farmer.publicKey,
+            config: configPDA,
+            campaign: privateCampaignPDA,
+            tier: privateTierPDA,
+            vault: privateVaultPDA,
+            backing: backingPDA,
+            customerStats: customerStatsFor(backer2.publicKey),
+            ...(await indexPagesFor(privateCampaignPDA, backer2.publicKey)),
+            backer: backer2.publicKey,
             systemProgram: SystemProgram.programId,
           })
-          .signers([farmer])
+          .signers([backer2])
           .rpc();
         expect.fail("Should have thrown error");
       } catch (error) {
-        expect(error.message).to.include("NameTooLong");
+        expect(error.message).to.include("NotAllowlisted");
       }
     });
+
+    it("should accept an allow-listed backer with a proof", async () => {
+      const [backingPDA] = PublicKey.findProgramAddressSync(
+        [Buffer.from("backing"), privateCampaignPDA.toBuffer(), backer1.publicKey.toBuffer()],
+        program.programId
+      );
+
+      await program.methods
+        .backCampaignSol(0, new BN(1 * LAMPORTS_PER_SOL), 1, [Array.from(merkleLeaf(authority.publicKey))])
+        .accounts({
+          config: configPDA,
+          campaign: privateCampaignPDA,
+          tier: privateTierPDA,
+          vault: privateVaultPDA,
+          backing: backingPDA,
+          customerStats: customerStatsFor(backer1.publicKey),
+          ...(await indexPagesFor(privateCampaignPDA, backer1.publicKey)),
+          backer: backer1.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([backer1])
+        .rpc();
+
+      const campaign = await program.account.campaign.fetch(privateCampaignPDA);
+      expect(campaign.backersCount.toNumber()).to.equal(1);
+    });
+  });
+
+  describe("Referrals", () => {
+    let referralCampaignId: BN;
+    let referralCampaignPDA: PublicKey;
+    let referralVaultPDA: PublicKey;
+    let referralTierPDA: PublicKey;
+    let referrerPDA: PublicKey;
+
+    before(async () => {
+      referralCampaignId = (await program.account.farmerProfile.fetch(farmerProfilePDA)).nextCampaignId;
+      [referralCampaignPDA] = PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("campaign"),
+          farmer.publicKey.toBuffer(),
+          referralCampaignId.toArrayLike(Buffer, "le", 8),
+        ],
+        program.programId
+      );
+      [referralVaultPDA] = PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("vault"),
+          farmer.publicKey.toBuffer(),
+          referralCampaignId.toArrayLike(Buffer, "le", 8),
+        ],
+        program.programId
+      );
+      [referralTierPDA] = PublicKey.findProgramAddressSync(
+        [Buffer.from("tier"), referralCampaignPDA.toBuffer(), Buffer.from([0])],
+        program.programId
+      );
+      [referrerPDA] = PublicKey.findProgramAddressSync(
+        [Buffer.from("referrer"), referralCampaignPDA.toBuffer(), backer1.publicKey.toBuffer()],
+        program.programId
+      );
+
+      await program.methods
+        .createCampaign("Neighbourhood Orchard", "Plant 200 apple trees", new BN(10 * LAMPORTS_PER_SOL), new BN(30), false, { vegetables: {} }, [], "9q8yy")
+        .accounts({
+          config: configPDA,
+          campaign: referralCampaignPDA,
+          vault: referralVaultPDA,
+          currencyMint: currencyMint,
+          ...(await listingIndexFor("campaign_category", 0, "9q8yy")),
+          farmerProfile: farmerProfilePDA,
+          farmer: farmer.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([farmer])
+        .rpc();
+
+      await program.methods
+        .createTier(0, "Tree Sponsor", new BN(1 * LAMPORTS_PER_SOL), new BN(0), "One tree", 0, new BN(0), new BN(0), { bronze: {} })
+        .accounts({
+          campaign: referralCampaignPDA,
+          tier: referralTierPDA,
+          authority: farmer.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([farmer])
+        .rpc();
+    });
+
+    it("should reject a referral share that leaves no room for the fee", async () => {
+      try {
+        await program.methods
+          .setReferralBps(10000)
+          .accounts({
+            campaign: referralCampaignPDA,
+            authority: farmer.publicKey,
+          })
+          .signers([farmer])
+          .rpc();
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("InvalidReferralBps");
+      }
+    });
+
+    it("should set the referral share before any backing", async () => {
+      await program.methods
+        .setReferralBps(500)
+        .accounts({
+          campaign: referralCampaignPDA,
+          authority: farmer.publicKey,
+        })
+        .signers([farmer])
+        .rpc();
+
+      const campaign = await program.account.campaign.fetch(referralCampaignPDA);
+      expect(campaign.referralBps).to.equal(500);
+    });
+
+    it("should register a referrer", async () => {
+      await program.methods
+        .registerReferrer()
+        .accounts({
+          campaign: referralCampaignPDA,
+          referrer: referrerPDA,
+          wallet: backer1.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([backer1])
+        .rpc();
+
+      const referrer = await program.account.referrer.fetch(referrerPDA);
+      expect(referrer.wallet.toString()).to.equal(backer1.publicKey.toString());
+      expect(referrer.isPaid).to.be.false;
+    });
+
+    it("should fail when a backer refers themselves", async () => {
+      const [backingPDA] = PublicKey.findProgramAddressSync(
+        [Buffer.from("backing"), referralCampaignPDA.toBuffer(), backer1.publicKey.toBuffer()],
+        program.programId
+      );
+
+      try {
+        await program.methods
+          .backCampaignSol(0, new BN(1 * LAMPORTS_PER_SOL), 1, [])
+          .accounts({
+            config: configPDA,
+            campaign: referralCampaignPDA,
+            tier: referralTierPDA,
+            vault: referralVaultPDA,
+            backing: backingPDA,
+            referrer: referrerPDA,
+            customerStats: customerStatsFor(backer1.publicKey),
+            ...(await indexPagesFor(referralCampaignPDA, backer1.publicKey)),
+            backer: backer1.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([backer1])
+          .rpc();
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("SelfReferral");
+      }
+    });
+
+    it("should record referred volume when backing with a referrer", async () => {
+      const [backingPDA] = PublicKey.findProgramAddressSync(
+        [Buffer.from("backing"), referralCampaignPDA.toBuffer(), backer2.publicKey.toBuffer()],
+        program.programId
+      );
+
+      await program.methods
+        .backCampaignSol(0, new BN(2 * LAMPORTS_PER_SOL), 1, [])
+        .accounts({
+          config: configPDA,
+          campaign: referralCampaignPDA,
+          tier: referralTierPDA,
+          vault: referralVaultPDA,
+          backing: backingPDA,
+          referrer: referrerPDA,
+          customerStats: customerStatsFor(backer2.publicKey),
+          ...(await indexPagesFor(referralCampaignPDA, backer2.publicKey)),
+          backer: backer2.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([backer2])
+        .rpc();
+
+      const backing = await program.account.backing.fetch(backingPDA);
+      expect(backing.referrer.toString()).to.equal(backer1.publicKey.toString());
+
+      const referrer = await program.account.referrer.fetch(referrerPDA);
+      expect(referrer.referredVolume.toNumber()).to.equal(2 * LAMPORTS_PER_SOL);
+      expect(referrer.referredCount).to.equal(1);
+
+      const campaign = await program.account.campaign.fetch(referralCampaignPDA);
+      expect(campaign.referredVolume.toNumber()).to.equal(2 * LAMPORTS_PER_SOL);
+    });
+
+    it("should lock the referral share once the campaign has backers", async () => {
+      try {
+        await program.methods
+          .setReferralBps(1000)
+          .accounts({
+            campaign: referralCampaignPDA,
+            authority: farmer.publicKey,
+          })
+          .signers([farmer])
+          .rpc();
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("ReferralLocked");
+      }
+    });
+
+    it("should not pay a referral reward before the funds are withdrawn", async () => {
+      try {
+        await program.methods
+          .claimReferralSol()
+          .accounts({
+            campaign: referralCampaignPDA,
+            vault: referralVaultPDA,
+            referrer: referrerPDA,
+            wallet: backer1.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([backer1])
+          .rpc();
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("CampaignNotSettled");
+      }
+    });
+  });
+
+  describe("Farmer Verification", () => {
+    let verifier: Keypair;
+    let verifierRolePDA: PublicKey;
+    let attestationPDA: PublicKey;
+    let verifiedCampaignId: BN;
+    const verificationKind = { verification: {} };
+
+    before(async () => {
+      verifiedCampaignId = (await program.account.farmerProfile.fetch(farmerProfilePDA)).nextCampaignId;
+      verifier = Keypair.generate();
+      const sig = await provider.connection.requestAirdrop(
+        verifier.publicKey,
+        10 * LAMPORTS_PER_SOL
+      );
+      await provider.connection.confirmTransaction(sig);
+
+      [attestationPDA] = PublicKey.findProgramAddressSync(
+        [Buffer.from("attestation"), configPDA.toBuffer(), farmer.publicKey.toBuffer(), Buffer.from([0])],
+        program.programId
+      );
+      [verifierRolePDA] = PublicKey.findProgramAddressSync(
+        [Buffer.from("role"), configPDA.toBuffer(), verifier.publicKey.toBuffer(), Buffer.from([3])],
+        program.programId
+      );
+    });
+
+    it("should fail to attest by a non-verifier", async () => {
+      try {
+        await program.methods
+          .attestFarmer(verificationKind, new BN(0), Array(32).fill(0))
+          .accounts({
+            config: configPDA,
+            role: null,
+            attestation: attestationPDA,
+            farmer: farmer.publicKey,
+            verifier: verifier.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([verifier])
+          .rpc();
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("MissingRole");
+      }
+    });
+
+    it("should grant the verifier role", async () => {
+      await program.methods
+        .grantRole(verifier.publicKey, { verifier: {} })
+        .accounts({
+          config: configPDA,
+          role: verifierRolePDA,
+          authority: authority.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([authority])
+        .rpc();
+
+      const role = await program.account.role.fetch(verifierRolePDA);
+      expect(role.member.toString()).to.equal(verifier.publicKey.toString());
+      expect(role.kind).to.deep.equal({ verifier: {} });
+    });
+
+    it("should attest a farmer", async () => {
+      const expiresAt = Math.floor(Date.now() / 1000) + 365 * 86400;
+      const evidenceHash = Array(32).fill(7);
+
+      await program.methods
+        .attestFarmer(verificationKind, new BN(expiresAt), evidenceHash)
+        .accounts({
+          config: configPDA,
+          role: verifierRolePDA,
+          attestation: attestationPDA,
+          farmer: farmer.publicKey,
+          verifier: verifier.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([verifier])
+        .rpc();
+
+      const attestation = await program.account.attestation.fetch(attestationPDA);
+      expect(attestation.farmer.toString()).to.equal(farmer.publicKey.toString());
+      expect(attestation.issuer.toString()).to.equal(verifier.publicKey.toString());
+      expect(attestation.expiresAt.toNumber()).to.equal(expiresAt);
+      expect(attestation.isRevoked).to.be.false;
+    });
+
+    it("should not let another verifier overwrite a live attestation", async () => {
+      const otherVerifier = Keypair.generate();
+      const sig = await provider.connection.requestAirdrop(
+        otherVerifier.publicKey,
+        LAMPORTS_PER_SOL
+      );
+      await provider.connection.confirmTransaction(sig);
+      const [otherRolePDA] = PublicKey.findProgramAddressSync(
+        [Buffer.from("role"), configPDA.toBuffer(), otherVerifier.publicKey.toBuffer(), Buffer.from([3])],
+        program.programId
+      );
+
+      await program.methods
+        .grantRole(otherVerifier.publicKey, { verifier: {} })
+        .accounts({
+          config: configPDA,
+          role: otherRolePDA,
+          authority: authority.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([authority])
+        .rpc();
+
+      try {
+        await program.methods
+          .attestFarmer(verificationKind, new BN(0), Array(32).fill(0))
+          .accounts({
+            config: configPDA,
+            role: otherRolePDA,
+            attestation: attestationPDA,
+            farmer: farmer.publicKey,
+            verifier: otherVerifier.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([otherVerifier])
+          .rpc();
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("AttestationIssuerMismatch");
+      }
+    });
+
+    it("should create a campaign that requires verification", async () => {
+      const [verifiedCampaignPDA] = PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("campaign"),
+          farmer.publicKey.toBuffer(),
+          verifiedCampaignId.toArrayLike(Buffer, "le", 8),
+        ],
+        program.programId
+      );
+      const [verifiedVaultPDA] = PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("vault"),
+          farmer.publicKey.toBuffer(),
+          verifiedCampaignId.toArrayLike(Buffer, "le", 8),
+        ],
+        program.programId
+      );
+
+      await program.methods
+        .createCampaign("Verified Dairy", "Certified raw milk", new BN(10 * LAMPORTS_PER_SOL), new BN(30), true, { vegetables: {} }, [], "9q8yy")
+        .accounts({
+          config: configPDA,
+          farmerProfile: farmerProfilePDA,
+          attestation: attestationPDA,
+          issuerRole: verifierRolePDA,
+          campaign: verifiedCampaignPDA,
+          vault: verifiedVaultPDA,
+          currencyMint: currencyMint,
+          ...(await listingIndexFor("campaign_category", 0, "9q8yy")),
+          farmer: farmer.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([farmer])
+        .rpc();
+
+      const campaign = await program.account.campaign.fetch(verifiedCampaignPDA);
+      expect(campaign.isVerified).to.be.true;
+    });
+
+    it("should revoke an attestation", async () => {
+      await program.methods
+        .revokeAttestation()
+        .accounts({
+          config: configPDA,
+          role: verifierRolePDA,
+          attestation: attestationPDA,
+          verifier: verifier.publicKey,
+        })
+        .signers([verifier])
+        .rpc();
+
+      const attestation = await program.account.attestation.fetch(attestationPDA);
+      expect(attestation.isRevoked).to.be.true;
+    });
+  });
+
+  describe("Time-Limited Tiers", () => {
+    const earlyBirdTierId = 3;
+    let earlyBirdTierPDA: PublicKey;
+
+    it("should fail to create tier with window ending before it starts", async () => {
+      const badWindowTierId = 95;
+      const [badWindowTierPDA] = PublicKey.findProgramAddressSync(
+        [Buffer.from("tier"), campaignPDA.toBuffer(), Buffer.from([badWindowTierId])],
+        program.programId
+      );
+      const now = Math.floor(Date.now() / 1000);
+
+      try {
+        await program.methods
+          .createTier(
+            badWindowTierId,
+            "Bad Window",
+            new BN(1 * LAMPORTS_PER_SOL),
+            new BN(0),
+            "Benefits",
+            0,
+            new BN(now + 3600),
+            new BN(now),
+            { bronze: {} }
+          )
+          .accounts({
+            campaign: campaignPDA,
+            tier: badWindowTierPDA,
+            authority: farmer.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([farmer])
+          .rpc();
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("InvalidTierWindow");
+      }
+    });
+
+    it("should create a tier that opens in the future", async () => {
+      [earlyBirdTierPDA] = PublicKey.findProgramAddressSync(
+        [Buffer.from("tier"), campaignPDA.toBuffer(), Buffer.from([earlyBirdTierId])],
+        program.programId
+      );
+      const availableFrom = Math.floor(Date.now() / 1000) + 86400;
+      const availableUntil = availableFrom + 48 * 3600;
+
+      await program.methods
+        .createTier(
+          earlyBirdTierId,
+          "Late Bloomer",
+          new BN(1 * LAMPORTS_PER_SOL),
+          new BN(0),
+          "Opens tomorrow for 48 hours",
+          0,
+          new BN(availableFrom),
+          new BN(availableUntil),
+          { bronze: {} }
+        )
+        .accounts({
+          campaign: campaignPDA,
+          tier: earlyBirdTierPDA,
+          authority: farmer.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([farmer])
+        .rpc();
+
+      const tier = await program.account.campaignTier.fetch(earlyBirdTierPDA);
+      expect(tier.availableFrom.toNumber()).to.equal(availableFrom);
+      expect(tier.availableUntil.toNumber()).to.equal(availableUntil);
+    });
+
+    it("should fail to back a tier before it opens", async () => {
+      const lateBacker = Keypair.generate();
+      const sig = await provider.connection.requestAirdrop(
+        lateBacker.publicKey,
+        10 * LAMPORTS_PER_SOL
+      );
+      await provider.connection.confirmTransaction(sig);
+
+      const [lateBackingPDA] = PublicKey.findProgramAddressSync(
+        [Buffer.from("backing"), campaignPDA.toBuffer(), lateBacker.publicKey.toBuffer()],
+        program.programId
+      );
+
+      try {
+        await program.methods
+          .backCampaignSol(earlyBirdTierId, new BN(1 * LAMPORTS_PER_SOL), 1, [])
+          .accounts({
+            config: configPDA,
+            campaign: campaignPDA,
+            tier: earlyBirdTierPDA,
+            vault: vaultPDA,
+            backing: lateBackingPDA,
+            customerStats: customerStatsFor(lateBacker.publicKey),
+            ...(await indexPagesFor(campaignPDA, lateBacker.publicKey)),
+            backer: lateBacker.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([lateBacker])
+          .rpc();
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("TierNotYetAvailable");
+      }
+    });
+  });
+
+  describe("Edge Cases", () => {
+    it("should fail to create campaign with duration > 365 days", async () => {
+      const newCampaignId = (await program.account.farmerProfile.fetch(farmerProfilePDA)).nextCampaignId;
+      const [newCampaignPDA] = PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("campaign"),
+          farmer.publicKey.toBuffer(),
+          newCampaignId.toArrayLike(Buffer, "le", 8),
+        ],
+        program.programId
+      );
+
+      const [newVaultPDA] = PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("vault"),
+          farmer.publicKey.toBuffer(),
+          newCampaignId.toArrayLike(Buffer, "le", 8),
+        ],
+        program.programId
+      );
+
+      try {
+        await program.methods
+          .createCampaign(
+            "Long Campaign",
+            "Description",
+            new BN(1 * LAMPORTS_PER_SOL),
+            new BN(366), // > 365 days
+            false,
+            { vegetables: {} },
+            [],
+            "9q8yy"
+          )
+          .accounts({
+            config: configPDA,
+            campaign: newCampaignPDA,
+            vault: newVaultPDA,
+            currencyMint: currencyMint,
+            ...(await listingIndexFor("campaign_category", 0, "9q8yy")),
+            farmerProfile: farmerProfilePDA,
+            farmer: farmer.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([farmer])
+          .rpc();
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("InvalidDuration");
+      }
+    });
+
+    it("should fail to create tier with zero min amount", async () => {
+      const zeroMinTierId = 97;
+      const [zeroMinTierPDA] = PublicKey.findProgramAddressSync(
+        [Buffer.from("tier"), campaignPDA.toBuffer(), Buffer.from([zeroMinTierId])],
+        program.programId
+      );
+
+      try {
+        await program.methods
+          .createTier(
+            zeroMinTierId,
+            "Zero Min Tier",
+            new BN(0), // Zero min amount
+            new BN(5 * LAMPORTS_PER_SOL),
+            "Benefits",
+            100,
+            new BN(0), // Available immediately
+            new BN(0), // No expiry
+            { bronze: {} }
+          )
+          .accounts({
+            campaign: campaignPDA,
+            tier: zeroMinTierPDA,
+            authority: farmer.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([farmer])
+          .rpc();
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("InvalidAmount");
+      }
+    });
+
+    it("should fail to create tier with name too long", async () => {
+      const longNameTierId = 96;
+      const [longNameTierPDA] = PublicKey.findProgramAddressSync(
+        [Buffer.from("tier"), campaignPDA.toBuffer(), Buffer.from([longNameTierId])],
+        program.programId
+      );
+
+      try {
+        await program.methods
+          .createTier(
+            longNameTierId,
+            "A".repeat(33), // > 32 chars
+            new BN(1 * LAMPORTS_PER_SOL),
+            new BN(5 * LAMPORTS_PER_SOL),
+            "Benefits",
+            100,
+            new BN(0), // Available immediately
+            new BN(0), // No expiry
+            { bronze: {} }
+          )
+          .accounts({
+            campaign: campaignPDA,
+            tier: longNameTierPDA,
+            authority: farmer.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([farmer])
+          .rpc();
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("NameTooLong");
+      }
+    });
+  });
+
+  describe("Close Accounts", () => {
+    it("should fail to close a backing that is not settled", async () => {
+      try {
+        await program.methods
+          .closeBacking()
+          .accounts({
+            campaign: campaignPDA,
+            backing: backingPDA,
+            backer: backer1.publicKey,
+          })
+          .signers([backer1])
+          .rpc();
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("BackingNotSettled");
+      }
+    });
+
+    it("should fail to close a tier before the campaign is finalized", async () => {
+      try {
+        await program.methods
+          .closeTier()
+          .accounts({
+            campaign: campaignPDA,
+            tier: tierPDA,
+            authority: farmer.publicKey,
+          })
+          .signers([farmer])
+          .rpc();
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("CampaignNotFinalized");
+      }
+    });
+
+    it("should fail to close a campaign before it is finalized", async () => {
+      try {
+        await program.methods
+          .closeCampaign()
+          .accounts({
+            campaign: campaignPDA,
+            authority: farmer.publicKey,
+          })
+          .signers([farmer])
+          .rpc();
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("CampaignNotFinalized");
+      }
+    });
+
+    it("should track open backings on the campaign", async () => {
+      const campaign = await program.account.campaign.fetch(campaignPDA);
+      expect(campaign.openBackings.toNumber()).to.equal(3);
+      expect(campaign.isWithdrawn).to.be.false;
+    });
+  });
+
+  describe("Marketplace Products", () => {
+    const delistedProductId = new BN(2);
+    let delistedProductPDA: PublicKey;
+
+    before(async () => {
+      [productPDA] = PublicKey.findProgramAddressSync(
+        [Buffer.from("product"), farmer.publicKey.toBuffer(), productId.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+      [delistedProductPDA] = PublicKey.findProgramAddressSync(
+        [Buffer.from("product"), farmer.publicKey.toBuffer(), delistedProductId.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+    });
+
+    it("should create a product listing", async () => {
+      const harvestDate = Math.floor(Date.now() / 1000) + 30 * 86400;
+
+      await program.methods
+        .createProduct(
+          productId,
+          new BN(0.1 * LAMPORTS_PER_SOL),
+          "lb",
+          45,
+          { ready: {} },
+          new BN(harvestDate),
+          "https://example.com/products/heirloom-tomatoes.json",
+          { vegetables: {} },
+          ["heirloom", "organic"],
+          "9q8yy"
+        )
+        .accounts({
+          config: configPDA,
+          farmerProfile: farmerProfilePDA,
+          product: productPDA,
+          currencyMint: currencyMint,
+          ...(await listingIndexFor("product_category", 0, "9q8yy")),
+          farmer: farmer.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([farmer])
+        .rpc();
+
+      const product = await program.account.product.fetch(productPDA);
+      expect(product.farmer.toString()).to.equal(farmer.publicKey.toString());
+      expect(product.price.toNumber()).to.equal(0.1 * LAMPORTS_PER_SOL);
+      expect(product.unit).to.equal("lb");
+      expect(product.stock).to.equal(45);
+      expect(product.growthStage).to.deep.equal({ ready: {} });
+      expect(product.isListed).to.be.true;
+    });
+
+    it("should update and restock a product", async () => {
+      await program.methods
+        .updateProduct(
+          new BN(0.12 * LAMPORTS_PER_SOL),
+          { ready: {} },
+          new BN(0),
+          "https://example.com/products/heirloom-tomatoes-v2.json"
+        )
+        .accounts({
+          config: configPDA,
+          product: productPDA,
+          farmer: farmer.publicKey,
+        })
+        .signers([farmer])
+        .rpc();
+
+      await program.methods
+        .restock(5)
+        .accounts({
+          config: configPDA,
+          product: productPDA,
+          farmer: farmer.publicKey,
+        })
+        .signers([farmer])
+        .rpc();
+
+      const product = await program.account.product.fetch(productPDA);
+      expect(product.price.toNumber()).to.equal(0.12 * LAMPORTS_PER_SOL);
+      expect(product.stock).to.equal(50);
+    });
+
+    it("should fail to update a product by non-farmer", async () => {
+      try {
+        await program.methods
+          .restock(5)
+          .accounts({
+            config: configPDA,
+            product: productPDA,
+            farmer: backer1.publicKey,
+          })
+          .signers([backer1])
+          .rpc();
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("ConstraintSeeds");
+      }
+    });
+
+    it("should delist a product and block restocking", async () => {
+      await program.methods
+        .createProduct(delistedProductId, new BN(1000000), "jar", 10, { ready: {} }, new BN(0), "", { vegetables: {} }, [], "9q8yy")
+        .accounts({
+          config: configPDA,
+          farmerProfile: farmerProfilePDA,
+          product: delistedProductPDA,
+          currencyMint: currencyMint,
+          ...(await listingIndexFor("product_category", 0, "9q8yy")),
+          farmer: farmer.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([farmer])
+        .rpc();
+
+      await program.methods
+        .delist()
+        .accounts({
+          config: configPDA,
+          product: delistedProductPDA,
+          farmer: farmer.publicKey,
+        })
+        .signers([farmer])
+        .rpc();
+
+      const product = await program.account.product.fetch(delistedProductPDA);
+      expect(product.isListed).to.be.false;
+
+      try {
+        await program.methods
+          .restock(1)
+          .accounts({
+            config: configPDA,
+            product: delistedProductPDA,
+            farmer: farmer.publicKey,
+          })
+          .signers([farmer])
+          .rpc();
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("ProductNotListed");
+      }
+    });
+  });
+
+  describe("Marketplace Orders", () => {
+    const orderId = new BN(1);
+    const cancelledOrderId = new BN(2);
+    let orderPDA: PublicKey;
+    let escrowPDA: PublicKey;
+    let cancelledOrderPDA: PublicKey;
+    let cancelledEscrowPDA: PublicKey;
+
+    before(async () => {
+      [orderPDA] = PublicKey.findProgramAddressSync(
+        [Buffer.from("order"), backer1.publicKey.toBuffer(), orderId.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+      [escrowPDA] = PublicKey.findProgramAddressSync(
+        [Buffer.from("order_escrow"), orderPDA.toBuffer()],
+        program.programId
+      );
+      [cancelledOrderPDA] = PublicKey.findProgramAddressSync(
+        [Buffer.from("order"), backer1.publicKey.toBuffer(), cancelledOrderId.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+      [cancelledEscrowPDA] = PublicKey.findProgramAddressSync(
+        [Buffer.from("order_escrow"), cancelledOrderPDA.toBuffer()],
+        program.programId
+      );
+    });
+
+    it("should place an order and escrow the payment", async () => {
+      await program.methods
+        .placeOrderSol(orderId, 3)
+        .accounts({
+          config: configPDA,
+          product: productPDA,
+          order: orderPDA,
+          escrow: escrowPDA,
+          buyer: backer1.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([backer1])
+        .rpc();
+
+      const order = await program.account.order.fetch(orderPDA);
+      expect(order.quantity).to.equal(3);
+      expect(order.totalAmount.toNumber()).to.equal(0.36 * LAMPORTS_PER_SOL);
+      expect(order.status).to.deep.equal({ pending: {} });
+
+      const escrowBalance = await provider.connection.getBalance(escrowPDA);
+      expect(escrowBalance).to.equal(0.36 * LAMPORTS_PER_SOL);
+
+      const product = await program.account.product.fetch(productPDA);
+      expect(product.stock).to.equal(47);
+    });
+
+    it("should fail to pay in SOL for a product priced in another token", async () => {
+      const tokenProductId = new BN(4);
+      const [tokenProductPDA] = PublicKey.findProgramAddressSync(
+        [Buffer.from("product"), farmer.publicKey.toBuffer(), tokenProductId.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+      const tokenMint = await createMint(provider.connection, authority, authority.publicKey, null, 6);
+
+      await program.methods
+        .createProduct(tokenProductId, new BN(1000000), "lb", 10, { ready: {} }, new BN(0), "", { vegetables: {} }, [], "9q8yy")
+        .accounts({
+          config: configPDA,
+          farmerProfile: farmerProfilePDA,
+          product: tokenProductPDA,
+          currencyMint: tokenMint,
+          ...(await listingIndexFor("product_category", 0, "9q8yy")),
+          farmer: farmer.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([farmer])
+        .rpc();
+
+      const tokenOrderId = new BN(4);
+      const [tokenOrderPDA] = PublicKey.findProgramAddressSync(
+        [Buffer.from("order"), backer1.publicKey.toBuffer(), tokenOrderId.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+      const [tokenEscrowPDA] = PublicKey.findProgramAddressSync(
+        [Buffer.from("order_escrow"), tokenOrderPDA.toBuffer()],
+        program.programId
+      );
+
+      try {
+        await program.methods
+          .placeOrderSol(tokenOrderId, 1)
+          .accounts({
+            config: configPDA,
+            product: tokenProductPDA,
+            order: tokenOrderPDA,
+            escrow: tokenEscrowPDA,
+            buyer: backer1.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([backer1])
+          .rpc();
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("InvalidMint");
+      }
+    });
+
+    it("should cancel a pending order and restock the product", async () => {
+      await program.methods
+        .placeOrderSol(cancelledOrderId, 2)
+        .accounts({
+          config: configPDA,
+          product: productPDA,
+          order: cancelledOrderPDA,
+          escrow: cancelledEscrowPDA,
+          buyer: backer1.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([backer1])
+        .rpc();
+
+      await program.methods
+        .cancelOrderSol()
+        .accounts({
+          product: productPDA,
+          order: cancelledOrderPDA,
+          escrow: cancelledEscrowPDA,
+          buyer: backer1.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([backer1])
+        .rpc();
+
+      const order = await program.account.order.fetch(cancelledOrderPDA);
+      expect(order.status).to.deep.equal({ cancelled: {} });
+
+      const escrowBalance = await provider.connection.getBalance(cancelledEscrowPDA);
+      expect(escrowBalance).to.equal(0);
+
+      const product = await program.account.product.fetch(productPDA);
+      expect(product.stock).to.equal(47);
+    });
+
+    it("should fail to accept an order by non-farmer", async () => {
+      try {
+        await program.methods
+          .acceptOrder()
+          .accounts({
+            order: orderPDA,
+            farmer: backer2.publicKey,
+          })
+          .signers([backer2])
+          .rpc();
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("Unauthorized");
+      }
+    });
+
+    it("should accept an order and block cancellation", async () => {
+      await program.methods
+        .acceptOrder()
+        .accounts({
+          order: orderPDA,
+          farmer: farmer.publicKey,
+        })
+        .signers([farmer])
+        .rpc();
+
+      const order = await program.account.order.fetch(orderPDA);
+      expect(order.status).to.deep.equal({ seed: {} });
+      expect(order.stageTimestamps[0].toNumber()).to.be.greaterThan(0);
+      expect(order.autoReleaseAt.toNumber()).to.equal(0);
+
+      try {
+        await program.methods
+          .cancelOrderSol()
+          .accounts({
+            product: productPDA,
+            order: orderPDA,
+            escrow: escrowPDA,
+            buyer: backer1.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([backer1])
+          .rpc();
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("InvalidOrderStatus");
+      }
+    });
+
+    it("should advance an order through its stages with evidence", async () => {
+      const evidenceHash = Array.from(Buffer.alloc(32, 7));
+
+      await program.methods
+        .advanceOrder({ harvesting: {} }, evidenceHash)
+        .accounts({
+          order: orderPDA,
+          farmer: farmer.publicKey,
+        })
+        .signers([farmer])
+        .rpc();
+
+      let order = await program.account.order.fetch(orderPDA);
+      expect(order.status).to.deep.equal({ harvesting: {} });
+      expect(order.stageTimestamps[2].toNumber()).to.be.greaterThan(0);
+      expect(order.stageTimestamps[1].toNumber()).to.equal(0);
+      expect(order.evidenceHash).to.deep.equal(evidenceHash);
+
+      await program.methods
+        .advanceOrder({ delivered: {} }, null)
+        .accounts({
+          order: orderPDA,
+          farmer: farmer.publicKey,
+        })
+        .signers([farmer])
+        .rpc();
+
+      order = await program.account.order.fetch(orderPDA);
+      expect(order.status).to.deep.equal({ delivered: {} });
+      expect(order.evidenceHash).to.deep.equal(evidenceHash);
+      expect(order.autoReleaseAt.toNumber()).to.equal(order.stageTimestamps[5].toNumber() + 14 * 86400);
+    });
+
+    it("should fail to move an order back to an earlier stage", async () => {
+      try {
+        await program.methods
+          .advanceOrder({ shipping: {} }, null)
+          .accounts({
+            order: orderPDA,
+            farmer: farmer.publicKey,
+          })
+          .signers([farmer])
+          .rpc();
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("InvalidOrderTransition");
+      }
+    });
+
+    it("should fail to release escrow before the timeout unless the buyer confirms", async () => {
+      const config = await program.account.config.fetch(configPDA);
+
+      try {
+        await program.methods
+          .confirmDeliverySol()
+          .accounts({
+            config: configPDA,
+            order: orderPDA,
+            escrow: escrowPDA,
+            farmer: farmer.publicKey,
+            treasury: config.treasury,
+            customerStats: customerStatsFor(backer1.publicKey),
+            caller: farmer.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([farmer])
+          .rpc();
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("Unauthorized");
+      }
+    });
+
+    it("should pay the farmer minus fees when the buyer confirms delivery", async () => {
+      const config = await program.account.config.fetch(configPDA);
+      const farmerBalanceBefore = await provider.connection.getBalance(farmer.publicKey);
+
+      await program.methods
+        .confirmDeliverySol()
+        .accounts({
+          config: configPDA,
+          order: orderPDA,
+          escrow: escrowPDA,
+          farmer: farmer.publicKey,
+          treasury: config.treasury,
+          customerStats: customerStatsFor(backer1.publicKey),
+          caller: backer1.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([backer1])
+        .rpc();
+
+      const order = await program.account.order.fetch(orderPDA);
+      expect(order.status).to.deep.equal({ completed: {} });
+
+      const total = 0.36 * LAMPORTS_PER_SOL;
+      const fee = Math.floor((total * config.feeBps) / 10000);
+      const farmerBalanceAfter = await provider.connection.getBalance(farmer.publicKey);
+      expect(farmerBalanceAfter - farmerBalanceBefore).to.equal(total - fee);
+    });
+  });
+
+  describe("Disputes", () => {
+    const orderId = new BN(3);
+    const arbiter = Keypair.generate();
+    let orderPDA: PublicKey;
+    let escrowPDA: PublicKey;
+    let disputePDA: PublicKey;
+    let arbiterRolePDA: PublicKey;
+
+    before(async () => {
+      const sig = await provider.connection.requestAirdrop(arbiter.publicKey, 10 * LAMPORTS_PER_SOL);
+      await provider.connection.confirmTransaction(sig);
+
+      [orderPDA] = PublicKey.findProgramAddressSync(
+        [Buffer.from("order"), backer2.publicKey.toBuffer(), orderId.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+      [escrowPDA] = PublicKey.findProgramAddressSync(
+        [Buffer.from("order_escrow"), orderPDA.toBuffer()],
+        program.programId
+      );
+      [disputePDA] = PublicKey.findProgramAddressSync(
+        [Buffer.from("dispute"), orderPDA.toBuffer()],
+        program.programId
+      );
+      [arbiterRolePDA] = PublicKey.findProgramAddressSync(
+        [Buffer.from("role"), configPDA.toBuffer(), arbiter.publicKey.toBuffer(), Buffer.from([5])],
+        program.programId
+      );
+
+      await program.methods
+        .grantRole(arbiter.publicKey, { arbiter: {} })
+        .accounts({
+          config: configPDA,
+          role: arbiterRolePDA,
+          authority: authority.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([authority])
+        .rpc();
+
+      await program.methods
+        .placeOrderSol(orderId, 1)
+        .accounts({
+          config: configPDA,
+          product: productPDA,
+          order: orderPDA,
+          escrow: escrowPDA,
+          buyer: backer2.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([backer2])
+        .rpc();
+
+      await program.methods
+        .acceptOrder()
+        .accounts({
+          order: orderPDA,
+          farmer: farmer.publicKey,
+        })
+        .signers([farmer])
+        .rpc();
+    });
+
+    it("should open a dispute on an escrowed order", async () => {
+      const reasonHash = Array.from(Buffer.alloc(32, 9));
+
+      await program.methods
+        .openOrderDispute(reasonHash)
+        .accounts({
+          order: orderPDA,
+          dispute: disputePDA,
+          buyer: backer2.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([backer2])
+        .rpc();
+
+      const dispute = await program.account.dispute.fetch(disputePDA);
+      expect(dispute.kind).to.deep.equal({ order: {} });
+      expect(dispute.claimant.toString()).to.equal(backer2.publicKey.toString());
+      expect(dispute.amount.toNumber()).to.equal(0.12 * LAMPORTS_PER_SOL);
+      expect(dispute.deadline.toNumber()).to.equal(dispute.openedAt.toNumber() + 7 * 86400);
+
+      const order = await program.account.order.fetch(orderPDA);
+      expect(order.status).to.deep.equal({ disputed: {} });
+    });
+
+    it("should fail to advance a disputed order", async () => {
+      try {
+        await program.methods
+          .advanceOrder({ shipping: {} }, null)
+          .accounts({
+            order: orderPDA,
+            farmer: farmer.publicKey,
+          })
+          .signers([farmer])
+          .rpc();
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("InvalidOrderStatus");
+      }
+    });
+
+    it("should fail to resolve a dispute without the arbiter role", async () => {
+      const config = await program.account.config.fetch(configPDA);
+
+      try {
+        await program.methods
+          .resolveOrderDisputeSol(10000)
+          .accounts({
+            config: configPDA,
+            role: null,
+            dispute: disputePDA,
+            order: orderPDA,
+            escrow: escrowPDA,
+            buyer: backer2.publicKey,
+            farmer: farmer.publicKey,
+            treasury: config.treasury,
+            caller: backer2.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([backer2])
+          .rpc();
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("MissingRole");
+      }
+    });
+
+    it("should split the escrow between buyer and farmer", async () => {
+      const config = await program.account.config.fetch(configPDA);
+      const buyerBalanceBefore = await provider.connection.getBalance(backer2.publicKey);
+      const farmerBalanceBefore = await provider.connection.getBalance(farmer.publicKey);
+
+      await program.methods
+        .resolveOrderDisputeSol(5000)
+        .accounts({
+          config: configPDA,
+          role: arbiterRolePDA,
+          dispute: disputePDA,
+          order: orderPDA,
+          escrow: escrowPDA,
+          buyer: backer2.publicKey,
+          farmer: farmer.publicKey,
+          treasury: config.treasury,
+          caller: arbiter.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([arbiter])
+        .rpc();
+
+      const half = 0.06 * LAMPORTS_PER_SOL;
+      const fee = Math.floor((half * config.feeBps) / 10000);
+      const buyerBalanceAfter = await provider.connection.getBalance(backer2.publicKey);
+      const farmerBalanceAfter = await provider.connection.getBalance(farmer.publicKey);
+      expect(buyerBalanceAfter - buyerBalanceBefore).to.equal(half);
+      expect(farmerBalanceAfter - farmerBalanceBefore).to.equal(half - fee);
+
+      const dispute = await program.account.dispute.fetch(disputePDA);
+      expect(dispute.status).to.deep.equal({ resolved: {} });
+      expect(dispute.claimantBps).to.equal(5000);
+
+      const order = await program.account.order.fetch(orderPDA);
+      expect(order.status).to.deep.equal({ resolved: {} });
+    });
+
+    it("should fail to dispute a backing before the campaign is finalized", async () => {
+      const [backingDisputePDA] = PublicKey.findProgramAddressSync(
+        [Buffer.from("dispute"), backingPDA.toBuffer()],
+        program.programId
+      );
+
+      try {
+        await program.methods
+          .openBackingDispute(Array.from(Buffer.alloc(32, 1)))
+          .accounts({
+            campaign: campaignPDA,
+            backing: backingPDA,
+            dispute: backingDisputePDA,
+            backer: backer1.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([backer1])
+          .rpc();
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("CampaignNotFinalized");
+      }
+    });
+
+    it("should not release the reward holdback before withdrawal", async () => {
+      try {
+        await program.methods
+          .releaseHoldbackSol()
+          .accounts({
+            campaign: campaignPDA,
+            vault: vaultPDA,
+            payoutWallet: farmer.publicKey,
+            authority: farmer.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([farmer])
+          .rpc();
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("CampaignNotSettled");
+      }
+    });
+  });
+
+  describe("Pre-Orders", () => {
+    const preorderProductId = new BN(3);
+    const orderId = new BN(10);
+    const price = 0.5 * LAMPORTS_PER_SOL;
+    let preorderProductPDA: PublicKey;
+    let orderPDA: PublicKey;
+    let escrowPDA: PublicKey;
+
+    before(async () => {
+      [preorderProductPDA] = PublicKey.findProgramAddressSync(
+        [Buffer.from("product"), farmer.publicKey.toBuffer(), preorderProductId.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+      [orderPDA] = PublicKey.findProgramAddressSync(
+        [Buffer.from("order"), backer1.publicKey.toBuffer(), orderId.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+      [escrowPDA] = PublicKey.findProgramAddressSync(
+        [Buffer.from("order_escrow"), orderPDA.toBuffer()],
+        program.programId
+      );
+
+      const harvestDate = Math.floor(Date.now() / 1000) + 60 * 86400;
+      await program.methods
+        .createProduct(preorderProductId, new BN(price), "box", 0, { growing: {} }, new BN(harvestDate), "", { vegetables: {} }, [], "9q8yy")
+        .accounts({
+          config: configPDA,
+          farmerProfile: farmerProfilePDA,
+          product: preorderProductPDA,
+          currencyMint: currencyMint,
+          ...(await listingIndexFor("product_category", 0, "9q8yy")),
+          farmer: farmer.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([farmer])
+        .rpc();
+    });
+
+    it("should fail to pre-order a product without a deposit configured", async () => {
+      try {
+        await program.methods
+          .placePreorderSol(orderId, 2)
+          .accounts({
+            config: configPDA,
+            product: preorderProductPDA,
+            order: orderPDA,
+            escrow: escrowPDA,
+            buyer: backer1.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([backer1])
+          .rpc();
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("PreordersDisabled");
+      }
+    });
+
+    it("should place a pre-order with a deposit", async () => {
+      await program.methods
+        .setPreorderDeposit(2000)
+        .accounts({
+          config: configPDA,
+          product: preorderProductPDA,
+          farmer: farmer.publicKey,
+        })
+        .signers([farmer])
+        .rpc();
+
+      await program.methods
+        .placePreorderSol(orderId, 2)
+        .accounts({
+          config: configPDA,
+          product: preorderProductPDA,
+          order: orderPDA,
+          escrow: escrowPDA,
+          buyer: backer1.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([backer1])
+        .rpc();
+
+      const order = await program.account.order.fetch(orderPDA);
+      expect(order.status).to.deep.equal({ preOrdered: {} });
+      expect(order.isPreorder).to.be.true;
+      expect(order.totalAmount.toNumber()).to.equal(2 * price);
+      expect(order.paidAmount.toNumber()).to.equal(0.2 * 2 * price);
+
+      const escrowBalance = await provider.connection.getBalance(escrowPDA);
+      expect(escrowBalance).to.equal(0.2 * 2 * price);
+
+      const product = await program.account.product.fetch(preorderProductPDA);
+      expect(product.stock).to.equal(0);
+    });
+
+    it("should fail to reclaim a deposit while the harvest window is open", async () => {
+      try {
+        await program.methods
+          .reclaimDepositSol()
+          .accounts({
+            order: orderPDA,
+            escrow: escrowPDA,
+            buyer: backer1.publicKey,
+            caller: backer2.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([backer2])
+          .rpc();
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("HarvestWindowOpen");
+      }
+    });
+
+    it("should collect the balance once the farmer opens payment", async () => {
+      await program.methods
+        .openBalancePayment()
+        .accounts({
+          order: orderPDA,
+          farmer: farmer.publicKey,
+        })
+        .signers([farmer])
+        .rpc();
+
+      let order = await program.account.order.fetch(orderPDA);
+      expect(order.status).to.deep.equal({ balanceDue: {} });
+      expect(order.balanceDeadline.toNumber()).to.be.greaterThan(0);
+
+      try {
+        await program.methods
+          .forfeitDepositSol()
+          .accounts({
+            order: orderPDA,
+            escrow: escrowPDA,
+            farmer: farmer.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([farmer])
+          .rpc();
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("BalanceWindowOpen");
+      }
+
+      await program.methods
+        .payBalanceSol()
+        .accounts({
+          order: orderPDA,
+          escrow: escrowPDA,
+          buyer: backer1.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([backer1])
+        .rpc();
+
+      order = await program.account.order.fetch(orderPDA);
+      expect(order.status).to.deep.equal({ harvesting: {} });
+      expect(order.paidAmount.toNumber()).to.equal(2 * price);
+
+      const escrowBalance = await provider.connection.getBalance(escrowPDA);
+      expect(escrowBalance).to.equal(2 * price);
+    });
+  });
+
+  describe("CSA Subscriptions", () => {
+    const subscriptionId = new BN(1);
+    let subscriptionPDA: PublicKey;
+    let escrowPDA: PublicKey;
+
+    before(async () => {
+      [subscriptionPDA] = PublicKey.findProgramAddressSync(
+        [Buffer.from("subscription"), backer2.publicKey.toBuffer(), subscriptionId.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+      [escrowPDA] = PublicKey.findProgramAddressSync(
+        [Buffer.from("subscription_escrow"), subscriptionPDA.toBuffer()],
+        program.programId
+      );
+    });
+
+    it("should fund a weekly subscription up front", async () => {
+      await program.methods
+        .subscribeSol(subscriptionId, 2, 7, 4)
+        .accounts({
+          config: configPDA,
+          product: productPDA,
+          subscription: subscriptionPDA,
+          escrow: escrowPDA,
+          buyer: backer2.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([backer2])
+        .rpc();
+
+      const subscription = await program.account.subscription.fetch(subscriptionPDA);
+      expect(subscription.pricePerPeriod.toNumber()).to.equal(0.24 * LAMPORTS_PER_SOL);
+      expect(subscription.periodLength.toNumber()).to.equal(7 * 86400);
+      expect(subscription.remainingPeriods).to.equal(4);
+      expect(subscription.isActive).to.be.true;
+
+      const escrowBalance = await provider.connection.getBalance(escrowPDA);
+      expect(escrowBalance).to.equal(4 * 0.24 * LAMPORTS_PER_SOL);
+    });
+
+    it("should fail to settle a period before it has elapsed", async () => {
+      const config = await program.account.config.fetch(configPDA);
+
+      try {
+        await program.methods
+          .settlePeriodSol()
+          .accounts({
+            config: configPDA,
+            subscription: subscriptionPDA,
+            escrow: escrowPDA,
+            farmer: farmer.publicKey,
+            treasury: config.treasury,
+            caller: backer1.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([backer1])
+          .rpc();
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("PeriodNotElapsed");
+      }
+    });
+
+    it("should refund unused periods on cancellation", async () => {
+      const buyerBalanceBefore = await provider.connection.getBalance(backer2.publicKey);
+
+      await program.methods
+        .cancelSubscriptionSol()
+        .accounts({
+          subscription: subscriptionPDA,
+          escrow: escrowPDA,
+          buyer: backer2.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([backer2])
+        .rpc();
+
+      const subscription = await program.account.subscription.fetch(subscriptionPDA);
+      expect(subscription.isActive).to.be.false;
+      expect(subscription.remainingPeriods).to.equal(0);
+
+      const escrowBalance = await provider.connection.getBalance(escrowPDA);
+      expect(escrowBalance).to.equal(0);
+
+      const buyerBalanceAfter = await provider.connection.getBalance(backer2.publicKey);
+      expect(buyerBalanceAfter).to.be.greaterThan(buyerBalanceBefore);
+    });
+  });
+
+  describe("Cart Checkout", () => {
+    const orchard = Keypair.generate();
+    const firstOrderId = new BN(100);
+    const price = 0.01 * LAMPORTS_PER_SOL;
+    const linesPerFarm = 6;
+    let farmProducts: PublicKey[] = [];
+    let orchardProducts: PublicKey[] = [];
+    let orderPDAs: PublicKey[] = [];
+    let escrowPDAs: PublicKey[] = [];
+
+    const productFor = (owner: PublicKey, id: number): PublicKey =>
+      PublicKey.findProgramAddressSync(
+        [Buffer.from("product"), owner.toBuffer(), new BN(id).toArrayLike(Buffer, "le", 8)],
+        program.programId
+      )[0];
+
+    const listProducts = async (owner: Keypair, profile: PublicKey): Promise<PublicKey[]> => {
+      const products: PublicKey[] = [];
+      for (let i = 0; i < linesPerFarm; i++) {
+        const product = productFor(owner.publicKey, 20 + i);
+        await program.methods
+          .createProduct(new BN(20 + i), new BN(price), "lb", 10, { ready: {} }, new BN(0), "", { vegetables: {} }, [], "9q8yy")
+          .accounts({
+            config: configPDA,
+            farmerProfile: profile,
+            product,
+            currencyMint: currencyMint,
+            ...(await listingIndexFor("product_category", 0, "9q8yy")),
+            farmer: owner.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([owner])
+          .rpc();
+        products.push(product);
+      }
+      return products;
+    };
+
+    before(async () => {
+      const sig = await provider.connection.requestAirdrop(orchard.publicKey, 10 * LAMPORTS_PER_SOL);
+      await provider.connection.confirmTransaction(sig);
+
+      const [orchardProfilePDA] = PublicKey.findProgramAddressSync(
+        [Buffer.from("farmer_profile"), orchard.publicKey.toBuffer()],
+        program.programId
+      );
+      await program.methods
+        .createFarmerProfile("Hillside Orchard", "", "9q9p1", false, orchard.publicKey)
+        .accounts({
+          farmerProfile: orchardProfilePDA,
+          farmer: orchard.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([orchard])
+        .rpc();
+
+      farmProducts = await listProducts(farmer, farmerProfilePDA);
+      orchardProducts = await listProducts(orchard, orchardProfilePDA);
+
+      for (let i = 0; i < 2; i++) {
+        const [orderPDA] = PublicKey.findProgramAddressSync(
+          [Buffer.from("order"), backer2.publicKey.toBuffer(), firstOrderId.addn(i).toArrayLike(Buffer, "le", 8)],
+          program.programId
+        );
+        const [escrowPDA] = PublicKey.findProgramAddressSync(
+          [Buffer.from("order_escrow"), orderPDA.toBuffer()],
+          program.programId
+        );
+        orderPDAs.push(orderPDA);
+        escrowPDAs.push(escrowPDA);
+      }
+    });
+
+    const cartAccounts = () => [
+      ...[...farmProducts, ...orchardProducts].map((pubkey) => ({ pubkey, isWritable: true, isSigner: false })),
+      ...orderPDAs.flatMap((order, i) => [
+        { pubkey: order, isWritable: true, isSigner: false },
+        { pubkey: escrowPDAs[i], isWritable: true, isSigner: false },
+      ]),
+    ];
+
+    it("should fail the whole checkout if any line is invalid", async () => {
+      const lines = Array.from({ length: 2 * linesPerFarm }, (_, i) => ({
+        quantity: 1,
+        maxUnitPrice: new BN(i === 7 ? price - 1 : price),
+      }));
+
+      try {
+        await program.methods
+          .checkoutSol(firstOrderId, lines)
+          .accounts({
+            config: configPDA,
+            buyer: backer2.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .remainingAccounts(cartAccounts())
+          .signers([backer2])
+          .rpc();
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("PriceChanged");
+      }
+
+      const product = await program.account.product.fetch(farmProducts[0]);
+      expect(product.stock).to.equal(10);
+    });
+
+    it("should check out 12 lines from two farms within the compute budget", async () => {
+      const lines = Array.from({ length: 2 * linesPerFarm }, () => ({
+        quantity: 2,
+        maxUnitPrice: new BN(price),
+      }));
+
+      // A pre-funded order address must not block checkout
+      await provider.sendAndConfirm(
+        new Transaction().add(
+          SystemProgram.transfer({
+            fromPubkey: provider.wallet.publicKey,
+            toPubkey: orderPDAs[0],
+            lamports: 1_000_000,
+          })
+        )
+      );
+
+      const signature = await program.methods
+        .checkoutSol(firstOrderId, lines)
+        .accounts({
+          config: configPDA,
+          buyer: backer2.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .remainingAccounts(cartAccounts())
+        .signers([backer2])
+        .rpc({ commitment: "confirmed" });
+
+      const tx = await provider.connection.getTransaction(signature, {
+        commitment: "confirmed",
+        maxSupportedTransactionVersion: 0,
+      });
+      const unitsConsumed = tx.meta.computeUnitsConsumed;
+      // Must fit the default per-transaction budget without a compute budget instruction
+      expect(unitsConsumed).to.be.at.most(200_000);
+
+      for (let i = 0; i < 2; i++) {
+        const order = await program.account.order.fetch(orderPDAs[i]);
+        expect(order.items.length).to.equal(linesPerFarm);
+        expect(order.quantity).to.equal(2 * linesPerFarm);
+        expect(order.totalAmount.toNumber()).to.equal(2 * linesPerFarm * price);
+        expect(order.status).to.deep.equal({ pending: {} });
+
+        const escrowBalance = await provider.connection.getBalance(escrowPDAs[i]);
+        expect(escrowBalance).to.equal(2 * linesPerFarm * price);
+      }
+
+      const farmOrder = await program.account.order.fetch(orderPDAs[0]);
+      expect(farmOrder.farmer.toString()).to.equal(farmer.publicKey.toString());
+      const orchardOrder = await program.account.order.fetch(orderPDAs[1]);
+      expect(orchardOrder.farmer.toString()).to.equal(orchard.publicKey.toString());
+
+      const product = await program.account.product.fetch(orchardProducts[5]);
+      expect(product.stock).to.equal(8);
+    });
+
+    it("should restock every product when a cart order is cancelled", async () => {
+      await program.methods
+        .cancelOrderSol()
+        .accounts({
+          product: null,
+          order: orderPDAs[1],
+          escrow: escrowPDAs[1],
+          buyer: backer2.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .remainingAccounts(orchardProducts.map((pubkey) => ({ pubkey, isWritable: true, isSigner: false })))
+        .signers([backer2])
+        .rpc();
+
+      const order = await program.account.order.fetch(orderPDAs[1]);
+      expect(order.status).to.deep.equal({ cancelled: {} });
+
+      for (const pubkey of orchardProducts) {
+        const product = await program.account.product.fetch(pubkey);
+        expect(product.stock).to.equal(10);
+      }
+    });
+  });
+
+  describe("Reviews", () => {
+    const orderPDAFor = (id: number): PublicKey =>
+      PublicKey.findProgramAddressSync(
+        [Buffer.from("order"), backer1.publicKey.toBuffer(), new BN(id).toArrayLike(Buffer, "le", 8)],
+        program.programId
+      )[0];
+    const reviewPDAFor = (subject: PublicKey, reviewer: PublicKey): PublicKey =>
+      PublicKey.findProgramAddressSync(
+        [Buffer.from("review"), subject.toBuffer(), reviewer.toBuffer()],
+        program.programId
+      )[0];
+
+    it("should fail to review with a rating outside 1-5", async () => {
+      const orderPDA = orderPDAFor(1);
+
+      try {
+        await program.methods
+          .reviewOrder(6, Array.from(Buffer.alloc(32, 2)))
+          .accounts({
+            order: orderPDA,
+            farmerProfile: farmerProfilePDA,
+            review: reviewPDAFor(orderPDA, backer1.publicKey),
+            reviewer: backer1.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([backer1])
+          .rpc();
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("InvalidRating");
+      }
+    });
+
+    it("should review a completed order and update the farmer rating", async () => {
+      const orderPDA = orderPDAFor(1);
+      const reviewPDA = reviewPDAFor(orderPDA, backer1.publicKey);
+      const profileBefore = await program.account.farmerProfile.fetch(farmerProfilePDA);
+
+      await program.methods
+        .reviewOrder(5, Array.from(Buffer.alloc(32, 2)))
+        .accounts({
+          order: orderPDA,
+          farmerProfile: farmerProfilePDA,
+          review: reviewPDA,
+          reviewer: backer1.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([backer1])
+        .rpc();
+
+      const review = await program.account.review.fetch(reviewPDA);
+      expect(review.rating).to.equal(5);
+      expect(review.kind).to.deep.equal({ order: {} });
+      expect(review.farmer.toString()).to.equal(farmer.publicKey.toString());
+
+      const profile = await program.account.farmerProfile.fetch(farmerProfilePDA);
+      expect(profile.ratingSum.toNumber()).to.equal(profileBefore.ratingSum.toNumber() + 5);
+      expect(profile.ratingCount).to.equal(profileBefore.ratingCount + 1);
+    });
+
+    it("should fail to review an order that was not completed", async () => {
+      const orderPDA = orderPDAFor(2);
+
+      try {
+        await program.methods
+          .reviewOrder(1, Array.from(Buffer.alloc(32, 3)))
+          .accounts({
+            order: orderPDA,
+            farmerProfile: farmerProfilePDA,
+            review: reviewPDAFor(orderPDA, backer1.publicKey),
+            reviewer: backer1.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([backer1])
+          .rpc();
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("InvalidOrderStatus");
+      }
+    });
+
+    it("should fail to review a backing whose reward is unfulfilled", async () => {
+      try {
+        await program.methods
+          .reviewBacking(4, Array.from(Buffer.alloc(32, 4)))
+          .accounts({
+            campaign: campaignPDA,
+            backing: backingPDA,
+            farmerProfile: farmerProfilePDA,
+            review: reviewPDAFor(backingPDA, backer1.publicKey),
+            reviewer: backer1.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([backer1])
+          .rpc();
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("RewardNotFulfilled");
+      }
+    });
+
+    it("should fail to fulfill a reward before the campaign is finalized", async () => {
+      try {
+        await program.methods
+          .fulfillReward()
+          .accounts({
+            campaign: campaignPDA,
+            backing: backingPDA,
+            authority: farmer.publicKey,
+          })
+          .signers([farmer])
+          .rpc();
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("CampaignNotFinalized");
+      }
+    });
+  });
+
+  describe("Loyalty Levels", () => {
+    const exclusiveTierId = 9;
+    const newcomer = Keypair.generate();
+    let exclusiveTierPDA: PublicKey;
+
+    before(async () => {
+      const sig = await provider.connection.requestAirdrop(newcomer.publicKey, 10 * LAMPORTS_PER_SOL);
+      await provider.connection.confirmTransaction(sig);
+
+      [exclusiveTierPDA] = PublicKey.findProgramAddressSync(
+        [Buffer.from("tier"), campaignPDA.toBuffer(), Buffer.from([exclusiveTierId])],
+        program.programId
+      );
+    });
+
+    it("should track lifetime spend for backers and buyers", async () => {
+      const stats = await program.account.customerStats.fetch(customerStatsFor(backer1.publicKey));
+      expect(stats.wallet.toString()).to.equal(backer1.publicKey.toString());
+      expect(stats.backingsCount).to.be.greaterThan(0);
+      expect(stats.totalBacked.toNumber()).to.be.greaterThan(0);
+      expect(stats.ordersCount).to.equal(1);
+      expect(stats.totalSpent.toNumber()).to.equal(0.36 * LAMPORTS_PER_SOL);
+    });
+
+    it("should fail to set descending loyalty thresholds", async () => {
+      try {
+        await program.methods
+          .setLoyaltyThresholds([new BN(10), new BN(5), new BN(20)])
+          .accounts({
+            config: configPDA,
+            role: null,
+            signer: authority.publicKey,
+          })
+          .signers([authority])
+          .rpc();
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("InvalidLoyaltyThresholds");
+      }
+    });
+
+    it("should gate an exclusive tier on loyalty level", async () => {
+      await program.methods
+        .setLoyaltyThresholds([
+          new BN(1 * LAMPORTS_PER_SOL),
+          new BN(2 * LAMPORTS_PER_SOL),
+          new BN(10 * LAMPORTS_PER_SOL),
+        ])
+        .accounts({
+          config: configPDA,
+          role: null,
+          signer: authority.publicKey,
+        })
+        .signers([authority])
+        .rpc();
+
+      await program.methods
+        .createTier(
+          exclusiveTierId,
+          "Gold Members",
+          new BN(0.1 * LAMPORTS_PER_SOL),
+          new BN(0),
+          "Members-only harvest dinner",
+          0,
+          new BN(0),
+          new BN(0),
+          { gold: {} }
+        )
+        .accounts({
+          campaign: campaignPDA,
+          tier: exclusiveTierPDA,
+          authority: farmer.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([farmer])
+        .rpc();
+
+      const tier = await program.account.campaignTier.fetch(exclusiveTierPDA);
+      expect(tier.minLoyaltyLevel).to.deep.equal({ gold: {} });
+
+      const [newcomerBackingPDA] = PublicKey.findProgramAddressSync(
+        [Buffer.from("backing"), campaignPDA.toBuffer(), newcomer.publicKey.toBuffer()],
+        program.programId
+      );
+
+      try {
+        await program.methods
+          .backCampaignSol(exclusiveTierId, new BN(0.1 * LAMPORTS_PER_SOL), 1, [])
+          .accounts({
+            config: configPDA,
+            campaign: campaignPDA,
+            tier: exclusiveTierPDA,
+            vault: vaultPDA,
+            backing: newcomerBackingPDA,
+            customerStats: customerStatsFor(newcomer.publicKey),
+            ...(await indexPagesFor(campaignPDA, newcomer.publicKey)),
+            backer: newcomer.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([newcomer])
+          .rpc();
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("LoyaltyLevelTooLow");
+      }
+    });
+  });
+
+  describe("Campaign Fees", () => {
+    it("should lock the platform fee into the campaign at creation", async () => {
+      const campaign = await program.account.campaign.fetch(campaignPDA);
+      expect(campaign.feeBps).to.equal(feeBps);
+    });
+
+    it("should fail to override a campaign fee without the fee manager role", async () => {
+      try {
+        await program.methods
+          .setCampaignFee(0)
+          .accounts({
+            config: configPDA,
+            role: null,
+            campaign: campaignPDA,
+            signer: farmer.publicKey,
+          })
+          .signers([farmer])
+          .rpc();
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("MissingRole");
+      }
+    });
+
+    it("should waive the fee for a campaign without touching the global fee", async () => {
+      await program.methods
+        .setCampaignFee(0)
+        .accounts({
+          config: configPDA,
+          role: null,
+          campaign: campaignPDA,
+          signer: authority.publicKey,
+        })
+        .signers([authority])
+        .rpc();
+
+      const campaign = await program.account.campaign.fetch(campaignPDA);
+      expect(campaign.feeBps).to.equal(0);
+
+      const config = await program.account.config.fetch(configPDA);
+      expect(config.feeBps).to.equal(feeBps);
+    });
+
+    it("should fail to raise the fee of a campaign with backers", async () => {
+      try {
+        await program.methods
+          .setCampaignFee(feeBps)
+          .accounts({
+            config: configPDA,
+            role: null,
+            campaign: campaignPDA,
+            signer: authority.publicKey,
+          })
+          .signers([authority])
+          .rpc();
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("FeeIncreaseLocked");
+      }
+    });
+
+    it("should fail to set fee splits above 10000 bps", async () => {
+      try {
+        await program.methods
+          .setFeeSplits([
+            { wallet: backer1.publicKey, shareBps: 6000 },
+            { wallet: backer2.publicKey, shareBps: 5000 },
+          ])
+          .accounts({
+            config: configPDA,
+            role: null,
+            signer: authority.publicKey,
+          })
+          .signers([authority])
+          .rpc();
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("InvalidFeeSplits");
+      }
+    });
+
+    it("should record fee splits for partners", async () => {
+      const coop = Keypair.generate();
+      const charity = Keypair.generate();
+
+      await program.methods
+        .setFeeSplits([
+          { wallet: coop.publicKey, shareBps: 3000 },
+          { wallet: charity.publicKey, shareBps: 1000 },
+        ])
+        .accounts({
+          config: configPDA,
+          role: null,
+          signer: authority.publicKey,
+        })
+        .signers([authority])
+        .rpc();
+
+      const config = await program.account.config.fetch(configPDA);
+      expect(config.feeSplits.length).to.equal(2);
+      expect(config.feeSplits[0].wallet.toString()).to.equal(coop.publicKey.toString());
+      expect(config.feeSplits[1].shareBps).to.equal(1000);
+    });
+
+    it("should configure volume discounts", async () => {
+      await program.methods
+        .setFeeDiscounts(
+          [new BN(100 * LAMPORTS_PER_SOL), new BN(1000 * LAMPORTS_PER_SOL), new BN(10000 * LAMPORTS_PER_SOL)],
+          [50, 100, 200]
+        )
+        .accounts({
+          config: configPDA,
+          role: null,
+          signer: authority.publicKey,
+        })
+        .signers([authority])
+        .rpc();
+
+      const config = await program.account.config.fetch(configPDA);
+      expect(config.feeDiscountThresholds[0].toNumber()).to.equal(100 * LAMPORTS_PER_SOL);
+      expect(config.feeDiscountBps).to.deep.equal([50, 100, 200]);
+    });
+  });
+
+  describe("Co-Owned Campaigns", () => {
+    let coopCampaignId: BN;
+    const partnerA = Keypair.generate();
+    const partnerB = Keypair.generate();
+    let coopCampaignPDA: PublicKey;
+    let coopVaultPDA: PublicKey;
+
+    before(async () => {
+      coopCampaignId = (await program.account.farmerProfile.fetch(farmerProfilePDA)).nextCampaignId;
+      [coopCampaignPDA] = PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("campaign"),
+          farmer.publicKey.toBuffer(),
+          coopCampaignId.toArrayLike(Buffer, "le", 8),
+        ],
+        program.programId
+      );
+      [coopVaultPDA] = PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("vault"),
+          farmer.publicKey.toBuffer(),
+          coopCampaignId.toArrayLike(Buffer, "le", 8),
+        ],
+        program.programId
+      );
+
+      await program.methods
+        .createCampaign("Valley Co-op Mill", "Shared grain mill", new BN(10 * LAMPORTS_PER_SOL), new BN(30), false, { vegetables: {} }, [], "9q8yy")
+        .accounts({
+          config: configPDA,
+          campaign: coopCampaignPDA,
+          vault: coopVaultPDA,
+          currencyMint: currencyMint,
+          ...(await listingIndexFor("campaign_category", 0, "9q8yy")),
+          farmerProfile: farmerProfilePDA,
+          farmer: farmer.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([farmer])
+        .rpc();
+    });
+
+    it("should fail when co-owner shares exceed 10000 bps", async () => {
+      try {
+        await program.methods
+          .setCoOwners(
+            [
+              { wallet: partnerA.publicKey, shareBps: 6000 },
+              { wallet: partnerB.publicKey, shareBps: 5000 },
+            ],
+            1
+          )
+          .accounts({
+            campaign: coopCampaignPDA,
+            authority: farmer.publicKey,
+          })
+          .signers([farmer])
+          .rpc();
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("InvalidCoOwners");
+      }
+    });
+
+    it("should add co-owners with revenue shares", async () => {
+      await program.methods
+        .setCoOwners(
+          [
+            { wallet: partnerA.publicKey, shareBps: 3000 },
+            { wallet: partnerB.publicKey, shareBps: 2000 },
+          ],
+          2
+        )
+        .accounts({
+          campaign: coopCampaignPDA,
+          authority: farmer.publicKey,
+        })
+        .signers([farmer])
+        .rpc();
+
+      const campaign = await program.account.campaign.fetch(coopCampaignPDA);
+      expect(campaign.coOwners.length).to.equal(2);
+      expect(campaign.coOwners[0].wallet.toString()).to.equal(partnerA.publicKey.toString());
+      expect(campaign.coOwnerThreshold).to.equal(2);
+    });
+
+    it("should fail to update the campaign without enough co-owner signatures", async () => {
+      try {
+        await program.methods
+          .updateCampaign("Valley Co-op Mill", "Stone-ground flour for the valley")
+          .accounts({
+            campaign: coopCampaignPDA,
+            authority: farmer.publicKey,
+          })
+          .remainingAccounts([
+            { pubkey: partnerA.publicKey, isWritable: false, isSigner: true },
+          ])
+          .signers([farmer, partnerA])
+          .rpc();
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("CoOwnerApprovalRequired");
+      }
+    });
+
+    it("should update the campaign with co-owner approval", async () => {
+      await program.methods
+        .updateCampaign("Valley Co-op Mill", "Stone-ground flour for the valley")
+        .accounts({
+          campaign: coopCampaignPDA,
+          authority: farmer.publicKey,
+        })
+        .remainingAccounts([
+          { pubkey: partnerA.publicKey, isWritable: false, isSigner: true },
+          { pubkey: partnerB.publicKey, isWritable: false, isSigner: true },
+        ])
+        .signers([farmer, partnerA, partnerB])
+        .rpc();
+
+      const campaign = await program.account.campaign.fetch(coopCampaignPDA);
+      expect(campaign.description).to.equal("Stone-ground flour for the valley");
+    });
+
+    it("should require co-owner approval to create a tier", async () => {
+      const [coopTierPDA] = PublicKey.findProgramAddressSync(
+        [Buffer.from("tier"), coopCampaignPDA.toBuffer(), Buffer.from([0])],
+        program.programId
+      );
+
+      try {
+        await program.methods
+          .createTier(0, "Flour Share", new BN(1 * LAMPORTS_PER_SOL), new BN(0), "Monthly flour", 0, new BN(0), new BN(0), { bronze: {} })
+          .accounts({
+            campaign: coopCampaignPDA,
+            tier: coopTierPDA,
+            authority: farmer.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([farmer])
+          .rpc();
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("CoOwnerApprovalRequired");
+      }
+
+      await program.methods
+        .createTier(0, "Flour Share", new BN(1 * LAMPORTS_PER_SOL), new BN(0), "Monthly flour", 0, new BN(0), new BN(0), { bronze: {} })
+        .accounts({
+          campaign: coopCampaignPDA,
+          tier: coopTierPDA,
+          authority: farmer.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .remainingAccounts([
+          { pubkey: partnerA.publicKey, isWritable: false, isSigner: true },
+          { pubkey: partnerB.publicKey, isWritable: false, isSigner: true },
+        ])
+        .signers([farmer, partnerA, partnerB])
+        .rpc();
+
+      const campaign = await program.account.campaign.fetch(coopCampaignPDA);
+      expect(campaign.tiersCount).to.equal(1);
+    });
+  });
+
+  describe("Campaign Authority", () => {
+    let handoverCampaignId: BN;
+    const multisig = Keypair.generate();
+    let handoverCampaignPDA: PublicKey;
+    let handoverVaultPDA: PublicKey;
+
+    before(async () => {
+      handoverCampaignId = (await program.account.farmerProfile.fetch(farmerProfilePDA)).nextCampaignId;
+      const sig = await provider.connection.requestAirdrop(
+        multisig.publicKey,
+        10 * LAMPORTS_PER_SOL
+      );
+      await provider.connection.confirmTransaction(sig);
+
+      [handoverCampaignPDA] = PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("campaign"),
+          farmer.publicKey.toBuffer(),
+          handoverCampaignId.toArrayLike(Buffer, "le", 8),
+        ],
+        program.programId
+      );
+      [handoverVaultPDA] = PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("vault"),
+          farmer.publicKey.toBuffer(),
+          handoverCampaignId.toArrayLike(Buffer, "le", 8),
+        ],
+        program.programId
+      );
+
+      await program.methods
+        .createCampaign("Estate Vineyard", "Replant the south slope", new BN(10 * LAMPORTS_PER_SOL), new BN(30), false, { vegetables: {} }, [], "9q8yy")
+        .accounts({
+          config: configPDA,
+          campaign: handoverCampaignPDA,
+          vault: handoverVaultPDA,
+          currencyMint: currencyMint,
+          ...(await listingIndexFor("campaign_category", 0, "9q8yy")),
+          farmerProfile: farmerProfilePDA,
+          farmer: farmer.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([farmer])
+        .rpc();
+    });
+
+    it("should start with the farmer as campaign authority", async () => {
+      const campaign = await program.account.campaign.fetch(handoverCampaignPDA);
+      expect(campaign.authority.toString()).to.equal(farmer.publicKey.toString());
+    });
+
+    it("should fail to propose an authority from a non-authority", async () => {
+      try {
+        await program.methods
+          .proposeCampaignAuthority(backer1.publicKey)
+          .accounts({
+            campaign: handoverCampaignPDA,
+            authority: backer1.publicKey,
+          })
+          .signers([backer1])
+          .rpc();
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("Unauthorized");
+      }
+    });
+
+    it("should keep the authority until the proposed key accepts", async () => {
+      await program.methods
+        .proposeCampaignAuthority(multisig.publicKey)
+        .accounts({
+          campaign: handoverCampaignPDA,
+          authority: farmer.publicKey,
+        })
+        .signers([farmer])
+        .rpc();
+
+      const campaign = await program.account.campaign.fetch(handoverCampaignPDA);
+      expect(campaign.authority.toString()).to.equal(farmer.publicKey.toString());
+      expect(campaign.pendingAuthority.toString()).to.equal(multisig.publicKey.toString());
+    });
+
+    it("should fail to accept the authority with another key", async () => {
+      try {
+        await program.methods
+          .acceptCampaignAuthority()
+          .accounts({
+            campaign: handoverCampaignPDA,
+            newAuthority: backer1.publicKey,
+          })
+          .signers([backer1])
+          .rpc();
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("Unauthorized");
+      }
+    });
+
+    it("should hand the campaign to a multisig", async () => {
+      await program.methods
+        .acceptCampaignAuthority()
+        .accounts({
+          campaign: handoverCampaignPDA,
+          newAuthority: multisig.publicKey,
+        })
+        .signers([multisig])
+        .rpc();
+
+      const campaign = await program.account.campaign.fetch(handoverCampaignPDA);
+      expect(campaign.authority.toString()).to.equal(multisig.publicKey.toString());
+      expect(campaign.pendingAuthority).to.be.null;
+      // PDAs stay seeded by the original farmer
+      expect(campaign.farmer.toString()).to.equal(farmer.publicKey.toString());
+    });
+
+    it("should reject the previous key for management instructions", async () => {
+      const [tierPDA] = PublicKey.findProgramAddressSync(
+        [Buffer.from("tier"), handoverCampaignPDA.toBuffer(), Buffer.from([0])],
+        program.programId
+      );
+
+      try {
+        await program.methods
+          .createTier(0, "Vine Sponsor", new BN(1 * LAMPORTS_PER_SOL), new BN(0), "One vine", 0, new BN(0), new BN(0), { bronze: {} })
+          .accounts({
+            campaign: handoverCampaignPDA,
+            tier: tierPDA,
+            authority: farmer.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([farmer])
+          .rpc();
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("Unauthorized");
+      }
+    });
+
+    it("should let the new authority manage the campaign", async () => {
+      const [tierPDA] = PublicKey.findProgramAddressSync(
+        [Buffer.from("tier"), handoverCampaignPDA.toBuffer(), Buffer.from([0])],
+        program.programId
+      );
+
+      await program.methods
+        .createTier(0, "Vine Sponsor", new BN(1 * LAMPORTS_PER_SOL), new BN(0), "One vine", 0, new BN(0), new BN(0), { bronze: {} })
+        .accounts({
+          campaign: handoverCampaignPDA,
+          tier: tierPDA,
+          authority: multisig.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([multisig])
+        .rpc();
+
+      const tier = await program.account.campaignTier.fetch(tierPDA);
+      expect(tier.campaign.toString()).to.equal(handoverCampaignPDA.toString());
+    });
+
+    it("should default the payout wallet to the farmer profile's", async () => {
+      const campaign = await program.account.campaign.fetch(handoverCampaignPDA);
+      const profile = await program.account.farmerProfile.fetch(farmerProfilePDA);
+      expect(campaign.payoutWallet.toString()).to.equal(profile.payoutWallet.toString());
+      expect(campaign.pendingPayoutWallet).to.be.null;
+    });
+
+    it("should timelock a payout wallet change", async () => {
+      const coldWallet = Keypair.generate();
+
+      await program.methods
+        .requestPayoutChange(coldWallet.publicKey)
+        .accounts({
+          campaign: handoverCampaignPDA,
+          authority: multisig.publicKey,
+        })
+        .signers([multisig])
+        .rpc();
+
+      const campaign = await program.account.campaign.fetch(handoverCampaignPDA);
+      expect(campaign.pendingPayoutWallet.toString()).to.equal(coldWallet.publicKey.toString());
+      expect(campaign.payoutChangeAt.toNumber()).to.be.greaterThan(0);
+
+      try {
+        await program.methods
+          .applyPayoutChange()
+          .accounts({
+            campaign: handoverCampaignPDA,
+            authority: multisig.publicKey,
+          })
+          .signers([multisig])
+          .rpc();
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("PayoutChangeTimelocked");
+      }
+    });
+  });
+
+  describe("Discovery", () => {
+    let dairyCampaignId: BN;
+    let dairyCampaignPDA: PublicKey;
+    let dairyVaultPDA: PublicKey;
+
+    before(async () => {
+      dairyCampaignId = (await program.account.farmerProfile.fetch(farmerProfilePDA)).nextCampaignId;
+      [dairyCampaignPDA] = PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("campaign"),
+          farmer.publicKey.toBuffer(),
+          dairyCampaignId.toArrayLike(Buffer, "le", 8),
+        ],
+        program.programId
+      );
+      [dairyVaultPDA] = PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("vault"),
+          farmer.publicKey.toBuffer(),
+          dairyCampaignId.toArrayLike(Buffer, "le", 8),
+        ],
+        program.programId
+      );
+    });
+
+    it("should fail to create a campaign with a geohash shorter than the index cell", async () => {
+      try {
+        await program.methods
+          .createCampaign("Goat Cheese Cave", "Age cheese on site", new BN(10 * LAMPORTS_PER_SOL), new BN(30), false, { dairy: {} }, [], "9q8")
+          .accounts({
+            config: configPDA,
+            campaign: dairyCampaignPDA,
+            vault: dairyVaultPDA,
+            currencyMint: currencyMint,
+            ...(await listingIndexFor("campaign_category", 2, "9q8")),
+            farmerProfile: farmerProfilePDA,
+            farmer: farmer.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([farmer])
+          .rpc();
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("InvalidGeohash");
+      }
+    });
+
+    it("should fail to create a campaign with too many tags", async () => {
+      try {
+        await program.methods
+          .createCampaign(
+            "Goat Cheese Cave",
+            "Age cheese on site",
+            new BN(10 * LAMPORTS_PER_SOL),
+            new BN(30),
+            false,
+            { dairy: {} },
+            ["goat", "cheese", "raw", "aged", "local"],
+            "9q8yz"
+          )
+          .accounts({
+            config: configPDA,
+            campaign: dairyCampaignPDA,
+            vault: dairyVaultPDA,
+            currencyMint: currencyMint,
+            ...(await listingIndexFor("campaign_category", 2, "9q8yz")),
+            farmerProfile: farmerProfilePDA,
+            farmer: farmer.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([farmer])
+          .rpc();
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("TooManyTags");
+      }
+    });
+
+    it("should list a dairy campaign under its category and geohash cell", async () => {
+      const { categoryIndex, categoryPage } = await listingIndexFor("campaign_category", 2, "9q8yz");
+
+      await program.methods
+        .createCampaign("Goat Cheese Cave", "Age cheese on site", new BN(10 * LAMPORTS_PER_SOL), new BN(30), false, { dairy: {} }, ["goat", "cheese"], "9q8yz")
+        .accounts({
+          config: configPDA,
+          campaign: dairyCampaignPDA,
+          vault: dairyVaultPDA,
+          currencyMint: currencyMint,
+          categoryIndex,
+          categoryPage,
+          farmerProfile: farmerProfilePDA,
+          farmer: farmer.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([farmer])
+        .rpc();
+
+      const campaign = await program.account.campaign.fetch(dairyCampaignPDA);
+      expect(campaign.category).to.deep.equal({ dairy: {} });
+      expect(campaign.tags).to.deep.equal(["goat", "cheese"]);
+      expect(campaign.geohash).to.equal("9q8yz");
+
+      const index = await program.account.categoryIndex.fetch(categoryIndex);
+      expect(index.geohashCell).to.equal("9q8y");
+      expect(index.count.toNumber()).to.equal(1);
+
+      const page = await program.account.categoryIndexPage.fetch(categoryPage);
+      expect(page.index.toString()).to.equal(categoryIndex.toString());
+      expect(page.entries.map((key) => key.toString())).to.deep.equal([dairyCampaignPDA.toString()]);
+    });
+  });
+
+  describe("Role-Based Access Control", () => {
+    let pauser: Keypair;
+    let pauserRolePDA: PublicKey;
+
+    before(async () => {
+      pauser = Keypair.generate();
+      const sig = await provider.connection.requestAirdrop(
+        pauser.publicKey,
+        10 * LAMPORTS_PER_SOL
+      );
+      await provider.connection.confirmTransaction(sig);
+
+      [pauserRolePDA] = PublicKey.findProgramAddressSync(
+        [Buffer.from("role"), configPDA.toBuffer(), pauser.publicKey.toBuffer(), Buffer.from([1])],
+        program.programId
+      );
+    });
+
+    it("should fail to grant a role by non-authority", async () => {
+      try {
+        await program.methods
+          .grantRole(pauser.publicKey, { pauser: {} })
+          .accounts({
+            config: configPDA,
+            role: pauserRolePDA,
+            authority: pauser.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([pauser])
+          .rpc();
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("ConstraintSeeds");
+      }
+    });
+
+    it("should let a pauser pause but not unpause", async () => {
+      await program.methods
+        .grantRole(pauser.publicKey, { pauser: {} })
+        .accounts({
+          config: configPDA,
+          role: pauserRolePDA,
+          authority: authority.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([authority])
+        .rpc();
+
+      await program.methods
+        .pausePlatform()
+        .accounts({
+          config: configPDA,
+          role: pauserRolePDA,
+          signer: pauser.publicKey,
+        })
+        .signers([pauser])
+        .rpc();
+
+      let config = await program.account.config.fetch(configPDA);
+      expect(config.isPaused).to.be.true;
+
+      try {
+        await program.methods
+          .unpausePlatform()
+          .accounts({
+            config: configPDA,
+            role: pauserRolePDA,
+            signer: pauser.publicKey,
+          })
+          .signers([pauser])
+          .rpc();
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("MissingRole");
+      }
+
+      await program.methods
+        .unpausePlatform()
+        .accounts({
+          config: configPDA,
+          role: null,
+          signer: authority.publicKey,
+        })
+        .signers([authority])
+        .rpc();
+
+      config = await program.account.config.fetch(configPDA);
+      expect(config.isPaused).to.be.false;
+    });
+
+    it("should not let a pauser change the fee", async () => {
+      try {
+        await program.methods
+          .setFeeBps(0)
+          .accounts({
+            config: configPDA,
+            role: pauserRolePDA,
+            signer: pauser.publicKey,
+          })
+          .signers([pauser])
+          .rpc();
+        expect.fail("Should have thrown error");
+      } catch (error) {
+        expect(error.message).to.include("MissingRole");
+      }
+    });
+
+    it("should revoke a role", async () => {
+      await program.methods
+        .revokeRole()
+        .accounts({
+          config: configPDA,
+          role: pauserRolePDA,
+          authority: authority.publicKey,
+        })
+        .signers([authority])
+        .rpc();
+
+      const role = await program.account.role.fetchNullable(pauserRolePDA);
+      expect(role).to.be.null;
+    });
   });
 
   describe("Config Validation", () => {
@@ -101,8 +101,6 @@ const CreateCampaignModal: React.FC<CreateCampaignModalProps> = ({
     });
 
     if (success && sdk) {
-      // Get the campaign address for tier creation
-      const campaignId = sdk.generateCampaignId() - 1; // Use the ID we just created
       // For demo, we'll skip to success
       setStep('tiers');
       // In production, you'd store the actual campaign address
@@ -141,7 +139,7 @@ const CreateCampaignModal: React.FC<CreateCampaignModalProps> = ({
         minAmount: minLamports,
         maxAmount: maxLamports,
         benefits: tier.benefits,
-        maxBackers: parseInt(tier.maxBackers) || 0,
+        maxUnits: parseInt(tier.maxBackers) || 0,
       });
     }
 
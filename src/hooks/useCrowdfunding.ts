@@ -102,7 +102,7 @@ export const useCrowdfunding = () => {
   /**
    * Create a new campaign
    */
-  const createCampaign = useCallback(async (params: Omit<CreateCampaignParams, 'campaignId'>): Promise<boolean> => {
+  const createCampaign = useCallback(async (params: CreateCampaignParams): Promise<boolean> => {
     if (!sdk) {
       toast({ title: 'Error', description: 'Please connect your wallet', variant: 'destructive' });
       return false;
@@ -110,8 +110,7 @@ export const useCrowdfunding = () => {
 
     setIsLoading(true);
     try {
-      const campaignId = sdk.generateCampaignId();
-      const result = await sdk.createCampaign({ ...params, campaignId });
+      const result = await sdk.createCampaign(params);
 
       if (result.success) {
         toast({
@@ -8,16 +8,18 @@
 import { BN, Program, Provider } from "@coral-xyz/anchor";
 import { 
   ASSOCIATED_TOKEN_PROGRAM_ID, 
+  NATIVE_MINT,
   TOKEN_PROGRAM_ID, 
   getAssociatedTokenAddressSync,
 } from "@solana/spl-token";
 import { 
+  AccountMeta,
   LAMPORTS_PER_SOL, 
   PublicKey, 
   SystemProgram,
 } from "@solana/web3.js";
 import IDL from "../idl/crowdfundingIDL.json";
-import { configAddress, CROWDFUNDING_PROGRAM_ID } from "./configAddress";
+import { CROWDFUNDING_PROGRAM_ID } from "./configAddress";
 
 // Type Definitions
 export interface ConfigData {
@@ -56,8 +58,12 @@ export interface TierData {
   minAmount: BN;
   maxAmount: BN;
   benefits: string;
-  maxBackers: number;
+  maxUnits: number;
   currentBackers: number;
+  unitsSold: number;
+  availableFrom: BN;
+  availableUntil: BN;
+  minLoyaltyLevel: LoyaltyLevel;
 }
 
 export interface BackingData {
@@ -70,15 +76,32 @@ export interface BackingData {
   isRefunded: boolean;
 }
 
+// Anchor enum encodings, mirroring programs/workspace/src/lib.rs
+export type CampaignCategory =
+  | "vegetables"
+  | "fruit"
+  | "dairy"
+  | "meat"
+  | "grain"
+  | "eggs"
+  | "honey"
+  | "flowers"
+  | "other";
+
+export type LoyaltyLevel = "bronze" | "silver" | "gold" | "platinum";
+
 // Parameter interfaces
 export interface CreateCampaignParams {
-  campaignId: number;
   title: string;
   description: string;
   goalAmount: number; // in lamports or token smallest unit
   durationDays: number;
   useSol: boolean; // true for SOL, false for token (USDC)
   currencyMint?: PublicKey; // required if useSol is false
+  requiresVerification?: boolean; // needs a Verification attestation for the farmer
+  category?: CampaignCategory; // defaults to "other"
+  tags?: string[];
+  geohash?: string; // defaults to the farmer profile's geohash
 }
 
 export interface CreateTierParams {
@@ -88,7 +111,10 @@ export interface CreateTierParams {
   minAmount: number;
   maxAmount: number; // 0 for unlimited
   benefits: string;
-  maxBackers: number; // 0 for unlimited
+  maxUnits: number; // 0 for unlimited
+  availableFrom?: number; // unix seconds, 0 for no start
+  availableUntil?: number; // unix seconds, 0 for no end
+  minLoyaltyLevel?: LoyaltyLevel;
 }
 
 export interface BackCampaignParams {
@@ -97,8 +123,20 @@ export interface BackCampaignParams {
   tierId: number;
   amount: number;
   useSol: boolean;
+  quantity?: number; // units of the tier, defaults to 1
+  proof?: number[][]; // allowlist merkle proof, if the campaign has one
+  referrer?: PublicKey; // wallet of a registered referrer
 }
 
+// Entries per on-chain index page, mirroring index::INDEX_PAGE_SIZE
+const INDEX_PAGE_SIZE = 32;
+
+// Geohash prefix listings are indexed under, mirroring GEOHASH_INDEX_PRECISION
+const GEOHASH_INDEX_PRECISION = 4;
+
+const VERIFICATION_ATTESTATION = 0;
+const VERIFIER_ROLE = 3;
+
 // Generic result wrapper
 export interface SDKResult<T> {
   success: boolean;
@@ -112,12 +150,10 @@ export interface SDKResult<T> {
 export class CrowdfundingSDK {
   private readonly provider: Provider;
   private readonly program: Program<any>;
-  private readonly configAddress: PublicKey | null;
 
   constructor(provider: Provider) {
     this.provider = provider;
     this.program = new Program(IDL as any, provider);
-    this.configAddress = configAddress ? new PublicKey(configAddress) : null;
   }
 
   // Helper: Safe BN constructor
@@ -190,17 +226,27 @@ export class CrowdfundingSDK {
   }
 
   /**
-   * Generate a unique campaign ID
+   * Get Config PDA
    */
-  generateCampaignId(): number {
-    return Date.now();
+  getConfigPDA(): [PublicKey, number] {
+    return this.getPDA(["config"]);
   }
 
   /**
-   * Get Config PDA
+   * Get Farmer Profile PDA
    */
-  getConfigPDA(authority: PublicKey): [PublicKey, number] {
-    return this.getPDA(["config", authority]);
+  getFarmerProfilePDA(farmer: PublicKey): [PublicKey, number] {
+    return this.getPDA(["farmer_profile", farmer]);
+  }
+
+  /**
+   * Get the id the farmer's next campaign will be created with.
+   * Campaign ids are assigned by the program, counting up from 0 per farmer.
+   */
+  async getNextCampaignId(farmer: PublicKey): Promise<BN> {
+    const [farmerProfilePDA] = this.getFarmerProfilePDA(farmer);
+    const profile = await this.program.account.farmerProfile.fetch(farmerProfilePDA);
+    return profile.nextCampaignId as BN;
   }
 
   /**
@@ -238,6 +284,76 @@ export class CrowdfundingSDK {
     return this.getPDA(["backing", campaign, backer]);
   }
 
+  /**
+   * Get Customer Stats PDA
+   */
+  getCustomerStatsPDA(wallet: PublicKey): [PublicKey, number] {
+    return this.getPDA(["customer_stats", wallet]);
+  }
+
+  /**
+   * Get Referrer PDA
+   */
+  getReferrerPDA(campaign: PublicKey, wallet: PublicKey): [PublicKey, number] {
+    return this.getPDA(["referrer", campaign, wallet]);
+  }
+
+  /**
+   * Get Attestation PDA
+   */
+  getAttestationPDA(farmer: PublicKey, kind: number): [PublicKey, number] {
+    const [configPDA] = this.getConfigPDA();
+    return this.getPDA(["attestation", configPDA, farmer, Buffer.from([kind])]);
+  }
+
+  /**
+   * Get Role PDA
+   */
+  getRolePDA(member: PublicKey, kind: number): [PublicKey, number] {
+    const [configPDA] = this.getConfigPDA();
+    return this.getPDA(["role", configPDA, member, Buffer.from([kind])]);
+  }
+
+  // Helper: Seed of the index page holding the entry after `count` entries
+  private pageSeed(count: number): Buffer {
+    return this.bnToSeedBuffer(new BN(Math.floor(count / INDEX_PAGE_SIZE)), 4);
+  }
+
+  /**
+   * Get the category index and page the next campaign listing is appended to
+   */
+  async getCategoryIndexAccounts(category: CampaignCategory, geohash: string): Promise<{ categoryIndex: PublicKey; categoryPage: PublicKey }> {
+    const categories: CampaignCategory[] = ["vegetables", "fruit", "dairy", "meat", "grain", "eggs", "honey", "flowers", "other"];
+    const [categoryIndex] = this.getPDA([
+      "campaign_category",
+      Buffer.from([categories.indexOf(category)]),
+      geohash.slice(0, GEOHASH_INDEX_PRECISION),
+    ]);
+    const index = await this.program.account.categoryIndex.fetchNullable(categoryIndex);
+    const count = index ? this.safeBNToNumber(index.count, 0) : 0;
+    const [categoryPage] = this.getPDA(["category_page", categoryIndex, this.pageSeed(count)]);
+    return { categoryIndex, categoryPage };
+  }
+
+  /**
+   * Get the index pages the next backing of `campaign` by `backer` is appended to
+   */
+  async getBackingIndexAccounts(campaign: PublicKey, backer: PublicKey): Promise<{ campaignBackers: PublicKey; backerCampaigns: PublicKey }> {
+    const campaignAccount = await this.program.account.campaign.fetch(campaign);
+    const stats = await this.program.account.customerStats.fetchNullable(this.getCustomerStatsPDA(backer)[0]);
+    const [campaignBackers] = this.getPDA([
+      "campaign_backers",
+      campaign,
+      this.pageSeed(this.safeBNToNumber(campaignAccount.backersCount, 0)),
+    ]);
+    const [backerCampaigns] = this.getPDA([
+      "backer_campaigns",
+      backer,
+      this.pageSeed(stats ? (stats.backingsCount as number) : 0),
+    ]);
+    return { campaignBackers, backerCampaigns };
+  }
+
   /**
    * Initialize Platform Config
    */
@@ -247,7 +363,7 @@ export class CrowdfundingSDK {
     try {
       if (!(await this.testConnection())) return { success: false, error: "Network unavailable" };
 
-      const [configPDA] = this.getConfigPDA(this.provider.publicKey);
+      const [configPDA] = this.getConfigPDA();
 
       const tx = await this.program.methods
         .initializeConfig(feeBps)
@@ -272,7 +388,6 @@ export class CrowdfundingSDK {
    */
   async createCampaign(params: CreateCampaignParams): Promise<SDKResult<{ signature: string; campaignAddress: string }>> {
     if (!this.provider.publicKey) return { success: false, error: "Wallet not connected" };
-    if (!this.configAddress) return { success: false, error: "Platform not initialized" };
 
     try {
       if (!(await this.testConnection())) return { success: false, error: "Network unavailable" };
@@ -281,31 +396,55 @@ export class CrowdfundingSDK {
       if (params.goalAmount <= 0) return { success: false, error: "Goal must be > 0" };
       if (params.durationDays <= 0) return { success: false, error: "Duration must be > 0" };
 
-      const campaignIdBN = this.safeBN(params.campaignId);
+      if (!params.useSol && !params.currencyMint) return { success: false, error: "Currency mint required" };
+
+      const [configPDA] = this.getConfigPDA();
+      const [farmerProfilePDA] = this.getFarmerProfilePDA(this.provider.publicKey);
+      const profile = await this.program.account.farmerProfile.fetch(farmerProfilePDA);
+      const campaignIdBN = profile.nextCampaignId as BN;
       const goalAmountBN = this.safeBN(params.goalAmount);
       
       const [campaignPDA] = this.getCampaignPDA(this.provider.publicKey, campaignIdBN);
       const [vaultPDA] = this.getVaultPDA(this.provider.publicKey, campaignIdBN);
 
-      // Use system program pubkey for SOL, or actual mint for tokens
-      const currencyMint = params.useSol 
-        ? SystemProgram.programId 
-        : (params.currencyMint || SystemProgram.programId);
+      // SOL campaigns are recorded against the native mint
+      const currencyMint = params.useSol ? NATIVE_MINT : params.currencyMint!;
+
+      const category = params.category || "other";
+      const geohash = params.geohash || (profile.geohash as string);
+      const { categoryIndex, categoryPage } = await this.getCategoryIndexAccounts(category, geohash);
+
+      // Verified campaigns need the farmer's attestation and its issuer's Verifier role
+      let attestation: PublicKey | null = null;
+      let issuerRole: PublicKey | null = null;
+      if (params.requiresVerification) {
+        [attestation] = this.getAttestationPDA(this.provider.publicKey, VERIFICATION_ATTESTATION);
+        const attestationAccount = await this.program.account.attestation.fetch(attestation);
+        [issuerRole] = this.getRolePDA(attestationAccount.issuer, VERIFIER_ROLE);
+      }
 
       const tx = await this.program.methods
         .createCampaign(
-          campaignIdBN,
           params.title.trim(),
           params.description.trim(),
           goalAmountBN,
-          currencyMint,
-          this.safeBN(params.durationDays)
+          this.safeBN(params.durationDays),
+          !!params.requiresVerification,
+          { [category]: {} },
+          params.tags || [],
+          geohash
         )
         .accounts({
+          config: configPDA,
+          farmerProfile: farmerProfilePDA,
+          attestation,
+          issuerRole,
           campaign: campaignPDA,
           vault: vaultPDA,
+          currencyMint,
+          categoryIndex,
+          categoryPage,
           farmer: this.provider.publicKey,
-          config: this.configAddress,
           systemProgram: SystemProgram.programId,
         })
         .rpc();
@@ -339,12 +478,15 @@ export class CrowdfundingSDK {
           this.safeBN(params.minAmount),
           this.safeBN(params.maxAmount),
           params.benefits.trim(),
-          params.maxBackers
+          params.maxUnits,
+          this.safeBN(params.availableFrom),
+          this.safeBN(params.availableUntil),
+          { [params.minLoyaltyLevel || "bronze"]: {} }
         )
         .accounts({
           campaign: params.campaignAddress,
           tier: tierPDA,
-          farmer: this.provider.publicKey,
+          authority: this.provider.publicKey,
           systemProgram: SystemProgram.programId,
         })
         .rpc();
@@ -372,16 +514,26 @@ export class CrowdfundingSDK {
       // Fetch campaign to get farmer and campaign_id for PDA derivation
       const campaign = await this.program.account.campaign.fetch(params.campaignAddress);
       
+      const [configPDA] = this.getConfigPDA();
       const [vaultPDA] = this.getVaultPDA(campaign.farmer, campaign.campaignId);
       const [backingPDA] = this.getBackingPDA(params.campaignAddress, this.provider.publicKey);
+      const [customerStatsPDA] = this.getCustomerStatsPDA(this.provider.publicKey);
+      const indexPages = await this.getBackingIndexAccounts(params.campaignAddress, this.provider.publicKey);
+      const referrer = params.referrer
+        ? this.getReferrerPDA(params.campaignAddress, params.referrer)[0]
+        : null;
 
       const tx = await this.program.methods
-        .backCampaignSol(params.tierId, this.safeBN(params.amount))
+        .backCampaignSol(params.tierId, this.safeBN(params.amount), params.quantity ?? 1, params.proof || [])
         .accounts({
+          config: configPDA,
           campaign: params.campaignAddress,
           tier: params.tierAddress,
           vault: vaultPDA,
           backing: backingPDA,
+          referrer,
+          customerStats: customerStatsPDA,
+          ...indexPages,
           backer: this.provider.publicKey,
           systemProgram: SystemProgram.programId,
         })
@@ -407,19 +559,29 @@ export class CrowdfundingSDK {
 
       const campaign = await this.program.account.campaign.fetch(params.campaignAddress);
       
+      const [configPDA] = this.getConfigPDA();
       const [vaultTokenPDA] = this.getTokenVaultPDA(campaign.farmer, campaign.campaignId);
       const [backingPDA] = this.getBackingPDA(params.campaignAddress, this.provider.publicKey);
+      const [customerStatsPDA] = this.getCustomerStatsPDA(this.provider.publicKey);
+      const indexPages = await this.getBackingIndexAccounts(params.campaignAddress, this.provider.publicKey);
       const backerToken = getAssociatedTokenAddressSync(currencyMint, this.provider.publicKey);
+      const referrer = params.referrer
+        ? this.getReferrerPDA(params.campaignAddress, params.referrer)[0]
+        : null;
 
       const tx = await this.program.methods
-        .backCampaignToken(params.tierId, this.safeBN(params.amount))
+        .backCampaignToken(params.tierId, this.safeBN(params.amount), params.quantity ?? 1, params.proof || [])
         .accounts({
+          config: configPDA,
           campaign: params.campaignAddress,
           tier: params.tierAddress,
           vaultToken: vaultTokenPDA,
           currencyMint: currencyMint,
           backerToken: backerToken,
           backing: backingPDA,
+          referrer,
+          customerStats: customerStatsPDA,
+          ...indexPages,
           backer: this.provider.publicKey,
           tokenProgram: TOKEN_PROGRAM_ID,
           systemProgram: SystemProgram.programId,
@@ -437,19 +599,15 @@ export class CrowdfundingSDK {
    */
   async finalizeCampaign(campaignAddress: PublicKey): Promise<SDKResult<{ signature: string }>> {
     if (!this.provider.publicKey) return { success: false, error: "Wallet not connected" };
-    if (!this.configAddress) return { success: false, error: "Platform not initialized" };
 
     try {
       if (!(await this.testConnection())) return { success: false, error: "Network unavailable" };
 
-      const campaign = await this.program.account.campaign.fetch(campaignAddress);
-
       const tx = await this.program.methods
         .finalizeCampaign()
         .accounts({
           campaign: campaignAddress,
-          config: this.configAddress,
-          farmer: this.provider.publicKey,
+          authority: this.provider.publicKey,
         })
         .rpc();
 
@@ -464,26 +622,36 @@ export class CrowdfundingSDK {
    */
   async withdrawFundsSol(campaignAddress: PublicKey): Promise<SDKResult<{ signature: string }>> {
     if (!this.provider.publicKey) return { success: false, error: "Wallet not connected" };
-    if (!this.configAddress) return { success: false, error: "Platform not initialized" };
 
     try {
       if (!(await this.testConnection())) return { success: false, error: "Network unavailable" };
 
+      const [configPDA] = this.getConfigPDA();
       const campaign = await this.program.account.campaign.fetch(campaignAddress);
-      const config = await this.program.account.config.fetch(this.configAddress);
+      const config = await this.program.account.config.fetch(configPDA);
       
       const [vaultPDA] = this.getVaultPDA(campaign.farmer, campaign.campaignId);
+      const [farmerProfilePDA] = this.getFarmerProfilePDA(campaign.farmer);
+
+      // Fee split wallets first, then co-owner wallets, in their stored order
+      const recipients: AccountMeta[] = [
+        ...(config.feeSplits as Array<{ wallet: PublicKey }>),
+        ...(campaign.coOwners as Array<{ wallet: PublicKey }>),
+      ].map(({ wallet }) => ({ pubkey: wallet, isSigner: false, isWritable: true }));
 
       const tx = await this.program.methods
         .withdrawFundsSol()
         .accounts({
+          config: configPDA,
+          farmerProfile: farmerProfilePDA,
           campaign: campaignAddress,
           vault: vaultPDA,
-          config: this.configAddress,
-          farmer: this.provider.publicKey,
-          treasury: config.authority, // Platform treasury
+          treasury: config.treasury,
+          payoutWallet: campaign.payoutWallet,
+          authority: this.provider.publicKey,
           systemProgram: SystemProgram.programId,
         })
+        .remainingAccounts(recipients)
         .rpc();
 
       return { success: true, data: { signature: tx } };
@@ -505,6 +673,7 @@ export class CrowdfundingSDK {
       
       const [vaultPDA] = this.getVaultPDA(campaign.farmer, campaign.campaignId);
       const [backingPDA] = this.getBackingPDA(campaignAddress, this.provider.publicKey);
+      const [customerStatsPDA] = this.getCustomerStatsPDA(this.provider.publicKey);
 
       const tx = await this.program.methods
         .claimRefundSol()
@@ -512,6 +681,7 @@ export class CrowdfundingSDK {
           campaign: campaignAddress,
           vault: vaultPDA,
           backing: backingPDA,
+          customerStats: customerStatsPDA,
           backer: this.provider.publicKey,
           systemProgram: SystemProgram.programId,
         })
@@ -567,14 +737,28 @@ export class CrowdfundingSDK {
     const targetFarmer = farmer || this.provider.publicKey;
     if (!targetFarmer) return { success: false, error: "No farmer address" };
 
-    const result = await this.getAllCampaigns();
-    if (!result.success) return result;
+    try {
+      if (!(await this.testConnection())) return { success: false, error: "Network unavailable" };
 
-    const farmerCampaigns = result.data!.filter(
-      (c) => c.account.farmer.toString() === targetFarmer.toString()
-    );
+      // Campaign ids run from 0 to the profile's counter, so no program account scan is needed
+      const nextCampaignId = this.safeBNToNumber(await this.getNextCampaignId(targetFarmer), 0);
+      const addresses = Array.from({ length: nextCampaignId }, (_, id) =>
+        this.getCampaignPDA(targetFarmer, new BN(id))[0]
+      );
+      const accounts = await this.program.account.campaign.fetchMultiple(addresses);
+
+      // Closed campaigns come back as null
+      const farmerCampaigns = addresses
+        .map((publicKey, i) => ({ publicKey, account: accounts[i] as CampaignData | null }))
+        .filter((c): c is { publicKey: PublicKey; account: CampaignData } => c.account !== null);
 
-    return { success: true, data: farmerCampaigns };
+      return { success: true, data: farmerCampaigns };
+    } catch (error) {
+      if (error instanceof Error && error.message.includes('Account does not exist')) {
+        return { success: true, data: [] };
+      }
+      return { success: false, error: "Failed to fetch campaigns" };
+    }
   }
 
   /**